@@ -0,0 +1,98 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, Ident};
+
+#[proc_macro_derive(IntoObject)]
+pub fn derive_into_object(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand_into_object(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(FromObject)]
+pub fn derive_from_object(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand_from_object(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn named_fields(input: &DeriveInput) -> syn::Result<Vec<&Ident>> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "only structs with named fields can be converted to and from hashes",
+        ));
+    };
+
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "only structs with named fields can be converted to and from hashes",
+        ));
+    };
+
+    Ok(fields
+        .named
+        .iter()
+        .filter_map(|field| field.ident.as_ref())
+        .collect())
+}
+
+fn expand_into_object(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    let fields = named_fields(input)?;
+    let keys = fields.iter().map(|field| field.to_string());
+
+    Ok(quote! {
+        impl #impl_generics ::interpreter::evaluator::convert::IntoObject
+            for #name #type_generics #where_clause
+        {
+            fn into_object(self) -> ::interpreter::evaluator::object::Object {
+                let mut entries = ::std::collections::BTreeMap::new();
+                #(
+                    entries.insert(
+                        ::interpreter::evaluator::object::HashKey::String(#keys.to_string()),
+                        ::interpreter::evaluator::convert::IntoObject::into_object(self.#fields),
+                    );
+                )*
+                ::interpreter::evaluator::object::Object::Hash(entries)
+            }
+        }
+    })
+}
+
+fn expand_from_object(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    let fields = named_fields(input)?;
+    let keys = fields.iter().map(|field| field.to_string());
+
+    Ok(quote! {
+        impl #impl_generics ::interpreter::evaluator::convert::FromObject
+            for #name #type_generics #where_clause
+        {
+            fn from_object(
+                object: &::interpreter::evaluator::object::Object,
+            ) -> ::std::result::Result<Self, ::interpreter::evaluator::convert::ConversionError> {
+                let ::interpreter::evaluator::object::Object::Hash(entries) = object else {
+                    return Err(::interpreter::evaluator::convert::ConversionError::TypeMismatch {
+                        expected: "Hash",
+                        actual: object.type_name(),
+                    });
+                };
+
+                Ok(#name {
+                    #(
+                        #fields: ::interpreter::evaluator::convert::from_field(entries, #keys)?,
+                    )*
+                })
+            }
+        }
+    })
+}
@@ -0,0 +1,20 @@
+use std::{
+    io::{self, BufReader},
+    process::ExitCode,
+};
+
+use session::Session;
+
+mod session;
+
+fn main() -> ExitCode {
+    let session = Session::new(BufReader::new(io::stdin().lock()), io::stdout().lock());
+
+    match session.run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("monkey-dap: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,451 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeSet, HashMap},
+    fs,
+    io::{self, BufRead, Write},
+    mem,
+    rc::Rc,
+};
+
+use serde_json::{json, Value};
+
+use interpreter::{
+    debugger::{Action, Debugger, Frame, Frontend, Pause},
+    evaluator::{
+        environment::Environment, error::EvaluationErrorKind, evaluator::Evaluator, object::Object,
+    },
+    lexer::lexer::Lexer,
+    parser::parser::Parser,
+    rpc,
+};
+
+// Monkey programs are single threaded, so every request names this thread
+const THREAD: i64 = 1;
+
+struct Launch {
+    path: String,
+    source: String,
+    stop_on_entry: bool,
+}
+
+// what a variablesReference points at while the program is paused
+enum Handle {
+    Scope(Rc<RefCell<Environment>>),
+    Value(Object),
+}
+
+// a debug adapter protocol session: configures a launch, runs the program under the
+// debugger and answers requests each time it pauses
+pub struct Session<R, W> {
+    input: R,
+    output: W,
+    seq: i64,
+    breakpoints: HashMap<String, BTreeSet<usize>>,
+    launch: Option<Launch>,
+    configured: bool,
+    disconnected: bool,
+    stopped: bool,
+    handles: Vec<Handle>,
+    error: Option<io::Error>,
+}
+
+impl<R: BufRead + 'static, W: Write + 'static> Session<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Session {
+            input,
+            output,
+            seq: 0,
+            breakpoints: HashMap::new(),
+            launch: None,
+            configured: false,
+            disconnected: false,
+            stopped: false,
+            handles: vec![],
+            error: None,
+        }
+    }
+
+    pub fn run(mut self) -> io::Result<()> {
+        // the program starts once it is launched and its breakpoints are configured
+        while self.launch.is_none() || !self.configured {
+            let Some(request) = self.next_request() else {
+                return self.finish();
+            };
+            let result = self.request(&request);
+            self.reply(&request, result);
+
+            if self.disconnected {
+                return self.finish();
+            }
+        }
+
+        self = self.debug();
+        if self.disconnected {
+            return self.finish();
+        }
+
+        // the program is done, but the client still gets to disconnect
+        while let Some(request) = self.next_request() {
+            let result = self.request(&request);
+            self.reply(&request, result);
+
+            if self.disconnected {
+                break;
+            }
+        }
+
+        self.finish()
+    }
+
+    fn finish(self) -> io::Result<()> {
+        match self.error {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    fn debug(mut self) -> Self {
+        let launch = self.launch.take().unwrap();
+        let mut parser = Parser::new(Lexer::new(&launch.source));
+        let program = parser.parse_program();
+
+        if !parser.errors.is_empty() {
+            for error in &parser.errors {
+                self.output_event("stderr", &format!("{}: {}\n", launch.path, error));
+            }
+            self.exited(1);
+            return self;
+        }
+
+        let breakpoints = self.breakpoints_of(&launch.path);
+        let mut debugger = Debugger::new(self).with_breakpoints(breakpoints);
+        if launch.stop_on_entry {
+            debugger = debugger.stop_on_entry();
+        }
+        debugger.frontend_mut().launch = Some(launch);
+
+        let debugger = Rc::new(RefCell::new(debugger));
+        let mut evaluator = Evaluator::new();
+        evaluator.set_hook(Box::new(Rc::clone(&debugger)));
+        let result = evaluator.eval(program, Environment::new());
+        drop(evaluator);
+
+        let mut session = match Rc::try_unwrap(debugger) {
+            Ok(debugger) => debugger.into_inner().into_frontend(),
+            Err(_) => unreachable!("the evaluator held the only other handle"),
+        };
+
+        match result {
+            _ if session.disconnected => {}
+            Ok(_) => session.exited(0),
+            Err(err) => {
+                if *err.kind != EvaluationErrorKind::Interrupted {
+                    session.output_event("stderr", &format!("{}\n", err));
+                }
+                session.exited(1);
+            }
+        }
+
+        session
+    }
+
+    fn exited(&mut self, code: i64) {
+        self.event("exited", json!({"exitCode": code}));
+        self.event("terminated", json!({}));
+    }
+
+    // answers the requests that do not need a paused program
+    fn request(&mut self, request: &Value) -> Result<Value, String> {
+        let arguments = &request["arguments"];
+
+        match request["command"].as_str().unwrap_or_default() {
+            "initialize" => Ok(json!({
+                "supportsConfigurationDoneRequest": true,
+                "supportsEvaluateForHovers": true,
+                "supportsTerminateRequest": true,
+            })),
+            "launch" => {
+                let path = arguments["program"]
+                    .as_str()
+                    .ok_or_else(|| "launch needs a program".to_string())?;
+                let source = fs::read_to_string(path)
+                    .map_err(|err| format!("cannot read {}: {}", path, err))?;
+
+                self.launch = Some(Launch {
+                    path: path.to_string(),
+                    source,
+                    stop_on_entry: arguments["stopOnEntry"].as_bool().unwrap_or(false),
+                });
+                Ok(Value::Null)
+            }
+            "setBreakpoints" => {
+                let path = arguments["source"]["path"].as_str().unwrap_or_default();
+                let lines: BTreeSet<usize> = arguments["breakpoints"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|breakpoint| breakpoint["line"].as_u64())
+                    .map(|line| line as usize)
+                    .collect();
+
+                let breakpoints: Vec<Value> = lines
+                    .iter()
+                    .map(|line| json!({"verified": true, "line": line}))
+                    .collect();
+                self.breakpoints.insert(path.to_string(), lines);
+                Ok(json!({"breakpoints": breakpoints}))
+            }
+            "setExceptionBreakpoints" => Ok(json!({"breakpoints": []})),
+            "configurationDone" => {
+                self.configured = true;
+                Ok(Value::Null)
+            }
+            "threads" => Ok(json!({"threads": [{"id": THREAD, "name": "main"}]})),
+            "disconnect" => {
+                self.disconnected = true;
+                Ok(Value::Null)
+            }
+            command => Err(format!("unsupported request {}", command)),
+        }
+    }
+
+    fn breakpoints_of(&self, path: &str) -> BTreeSet<usize> {
+        self.breakpoints.get(path).cloned().unwrap_or_default()
+    }
+
+    fn stack_trace(&self, pause: &Pause) -> Value {
+        let path = self.launch.as_ref().map_or("", |launch| &launch.path);
+
+        let frames: Vec<Value> = pause
+            .frames()
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(index, frame)| match &frame.location {
+                Some(location) => json!({
+                    "id": index + 1,
+                    "name": frame.name,
+                    "source": {"path": path},
+                    "line": location.line(),
+                    "column": location.column(),
+                }),
+                // natives have no source to show
+                None => json!({"id": index + 1, "name": frame.name, "line": 0, "column": 0}),
+            })
+            .collect();
+
+        json!({"stackFrames": frames, "totalFrames": frames.len()})
+    }
+
+    fn frame<'p>(&self, pause: &'p Pause, arguments: &Value) -> Result<&'p Frame, String> {
+        arguments["frameId"]
+            .as_u64()
+            .and_then(|id| pause.frames().get((id as usize).checked_sub(1)?))
+            .ok_or_else(|| "unknown frame".to_string())
+    }
+
+    // one scope per level of the frame's environment chain
+    fn scopes(&mut self, pause: &Pause, arguments: &Value) -> Result<Value, String> {
+        let mut scopes = vec![];
+        let mut environment = self.frame(pause, arguments)?.environment.clone();
+
+        while let Some(current) = environment {
+            environment = current.borrow().outer();
+            let name = match (scopes.len(), &environment) {
+                (_, None) => "Globals",
+                (0, _) => "Locals",
+                _ => "Closure",
+            };
+            let reference = self.handle(Handle::Scope(current));
+            scopes.push(json!({"name": name, "variablesReference": reference, "expensive": false}));
+        }
+
+        Ok(json!({"scopes": scopes}))
+    }
+
+    fn variables(&mut self, arguments: &Value) -> Result<Value, String> {
+        let handle = arguments["variablesReference"]
+            .as_u64()
+            .and_then(|reference| self.handles.get((reference as usize).checked_sub(1)?))
+            .ok_or_else(|| "unknown variables reference".to_string())?;
+
+        let children: Vec<(String, Object)> = match handle {
+            Handle::Scope(environment) => environment.borrow().bindings(),
+            Handle::Value(Object::Array(elements)) => elements
+                .iter()
+                .enumerate()
+                .map(|(index, element)| (format!("[{}]", index), element.clone()))
+                .collect(),
+            Handle::Value(Object::Hash(entries)) => entries
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.clone()))
+                .collect(),
+            Handle::Value(_) => vec![],
+        };
+
+        let variables: Vec<Value> = children
+            .into_iter()
+            .map(|(name, value)| {
+                let mut variable = self.variable(value);
+                variable["name"] = json!(name);
+                variable
+            })
+            .collect();
+
+        Ok(json!({"variables": variables}))
+    }
+
+    // describes a value, with a reference to expand it when it is an array or a hash
+    fn variable(&mut self, value: Object) -> Value {
+        let text = match &value {
+            Object::Function {
+                name: Some(name), ..
+            } => format!("fn {}", name),
+            function @ Object::Function { .. } => function.function_name().unwrap_or_default(),
+            value => value.to_string(),
+        };
+        let kind = value.type_name();
+
+        let reference = match &value {
+            Object::Array(elements) if !elements.is_empty() => self.handle(Handle::Value(value)),
+            Object::Hash(entries) if !entries.is_empty() => self.handle(Handle::Value(value)),
+            _ => 0,
+        };
+
+        json!({"value": text, "type": kind, "variablesReference": reference})
+    }
+
+    fn evaluate(&mut self, pause: &mut Pause, arguments: &Value) -> Result<Value, String> {
+        let expression = arguments["expression"].as_str().unwrap_or_default();
+
+        let result = match arguments.get("frameId") {
+            Some(_) => {
+                let environment = self
+                    .frame(pause, arguments)?
+                    .environment
+                    .clone()
+                    .ok_or_else(|| "the frame has no scope".to_string())?;
+                pause.evaluate_in(expression, environment)
+            }
+            None => pause.evaluate(expression),
+        };
+
+        let mut body = self.variable(result.map_err(|err| err.to_string())?);
+        body["result"] = body["value"].take();
+        Ok(body)
+    }
+
+    fn handle(&mut self, handle: Handle) -> usize {
+        self.handles.push(handle);
+        self.handles.len()
+    }
+
+    fn next_request(&mut self) -> Option<Value> {
+        loop {
+            match rpc::read_message(&mut self.input) {
+                Ok(Some(message)) if message["type"] == "request" => return Some(message),
+                Ok(Some(_)) => continue,
+                Ok(None) => return None,
+                Err(err) => {
+                    self.error.get_or_insert(err);
+                    return None;
+                }
+            }
+        }
+    }
+
+    fn reply(&mut self, request: &Value, result: Result<Value, String>) {
+        let command = request["command"].as_str().unwrap_or_default();
+        let mut response = json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": command,
+            "success": result.is_ok(),
+        });
+        match result {
+            Ok(Value::Null) => {}
+            Ok(body) => response["body"] = body,
+            Err(message) => response["message"] = json!(message),
+        }
+        self.send(response);
+
+        // breakpoints may only be sent once the adapter says it is ready for them
+        if command == "initialize" {
+            self.event("initialized", json!({}));
+        }
+    }
+
+    fn event(&mut self, event: &str, body: Value) {
+        self.send(json!({"type": "event", "event": event, "body": body}));
+    }
+
+    fn output_event(&mut self, category: &str, output: &str) {
+        self.event("output", json!({"category": category, "output": output}));
+    }
+
+    fn send(&mut self, mut message: Value) {
+        self.seq += 1;
+        message["seq"] = json!(self.seq);
+
+        if let Err(err) = rpc::write_message(&mut self.output, &message) {
+            self.error.get_or_insert(err);
+        }
+    }
+}
+
+impl<R: BufRead + 'static, W: Write + 'static> Frontend for Session<R, W> {
+    fn paused(&mut self, pause: &mut Pause) -> Action {
+        // references only hold while the program stays paused
+        self.handles.clear();
+
+        let at_breakpoint = pause
+            .location()
+            .is_some_and(|location| pause.breakpoints.contains(&location.line()));
+        let reason = match (at_breakpoint, mem::replace(&mut self.stopped, true)) {
+            (true, _) => "breakpoint",
+            (false, false) => "entry",
+            (false, true) => "step",
+        };
+        self.event(
+            "stopped",
+            json!({"reason": reason, "threadId": THREAD, "allThreadsStopped": true}),
+        );
+
+        loop {
+            let Some(request) = self.next_request() else {
+                return Action::Quit;
+            };
+            let arguments = &request["arguments"];
+
+            let (result, action) = match request["command"].as_str().unwrap_or_default() {
+                "continue" => (
+                    Ok(json!({"allThreadsContinued": true})),
+                    Some(Action::Continue),
+                ),
+                "next" => (Ok(Value::Null), Some(Action::Next)),
+                "stepIn" => (Ok(Value::Null), Some(Action::Step)),
+                "stepOut" => (Ok(Value::Null), Some(Action::Finish)),
+                "terminate" => (Ok(Value::Null), Some(Action::Quit)),
+                "stackTrace" => (Ok(self.stack_trace(pause)), None),
+                "scopes" => (self.scopes(pause, arguments), None),
+                "variables" => (self.variables(arguments), None),
+                "evaluate" => (self.evaluate(pause, arguments), None),
+                "setBreakpoints" => {
+                    let result = self.request(&request);
+                    let path = self.launch.as_ref().map_or("", |launch| &launch.path);
+                    *pause.breakpoints = self.breakpoints_of(path);
+                    (result, None)
+                }
+                _ => {
+                    let result = self.request(&request);
+                    (result, self.disconnected.then_some(Action::Quit))
+                }
+            };
+
+            self.reply(&request, result);
+            if let Some(action) = action {
+                return action;
+            }
+        }
+    }
+}
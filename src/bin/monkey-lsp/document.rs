@@ -0,0 +1,117 @@
+use interpreter::{
+    lexer::{
+        lexer::Lexer,
+        location::Location,
+        token::{Token, TokenType},
+    },
+    linter::{
+        checker::{self, LintOptions},
+        symbols::{ScopeKind, Symbol, SymbolTable},
+        warning::Warning,
+    },
+    parser::{error::ParserError, parser::Parser},
+};
+
+// an open text document together with everything the requests about it need
+pub struct Document {
+    pub text: String,
+    pub errors: Vec<ParserError>,
+    pub warnings: Vec<Warning>,
+    pub symbols: SymbolTable,
+    tokens: Vec<Token>,
+}
+
+impl Document {
+    pub fn new(text: String) -> Self {
+        let mut parser = Parser::new(Lexer::new(&text));
+        let program = parser.parse_program();
+        let errors = std::mem::take(&mut parser.errors);
+
+        // statements that fail to parse are dropped, so the rest still gets analyzed
+        let warnings = checker::lint_program(
+            &program,
+            &parser.source_map.comments,
+            &LintOptions::default(),
+        );
+        let symbols = SymbolTable::new(&program);
+
+        let mut lexer = Lexer::new(&text);
+        let mut tokens = vec![];
+        loop {
+            let token = lexer.next_token();
+            if token.token_type == TokenType::EOF {
+                break;
+            }
+            tokens.push(token);
+        }
+
+        Document {
+            text,
+            errors,
+            warnings,
+            symbols,
+            tokens,
+        }
+    }
+
+    pub fn symbol_at(&self, location: &Location) -> Option<&Symbol> {
+        self.symbols
+            .symbol_at(location.line(), location.column())
+            .map(|symbol| &self.symbols.symbols[symbol])
+    }
+
+    // the names a completion at the given position can refer to
+    pub fn visible_at(&self, location: &Location) -> Vec<&Symbol> {
+        let scope = self
+            .symbols
+            .scopes
+            .iter()
+            .enumerate()
+            .filter(|(_, scope)| scope.kind == ScopeKind::Function)
+            .filter_map(|(id, scope)| {
+                let (start, end) = self.function_extent(scope.location.as_ref()?)?;
+                let inside =
+                    position(&start) <= position(location) && position(location) <= position(&end);
+                inside.then_some((id, start))
+            })
+            .max_by_key(|(_, start)| position(start))
+            .map_or(0, |(id, _)| id);
+
+        self.symbols.visible(scope)
+    }
+
+    // from the `fn` keyword to the brace closing the body, found by matching the
+    // parentheses around the parameters and then the braces around the body
+    fn function_extent(&self, start: &Location) -> Option<(Location, Location)> {
+        let index = self
+            .tokens
+            .iter()
+            .position(|token| token.location == *start)?;
+        let mut tokens = self.tokens[index + 1..].iter();
+
+        for (open, close) in [
+            (TokenType::LParen, TokenType::RParen),
+            (TokenType::LBrace, TokenType::RBrace),
+        ] {
+            let mut depth = 0;
+            let closing = tokens.find(|token| {
+                if token.token_type == open {
+                    depth += 1;
+                } else if token.token_type == close {
+                    depth -= 1;
+                }
+                depth == 0
+            })?;
+
+            if close == TokenType::RBrace {
+                return Some((start.clone(), closing.location.clone()));
+            }
+        }
+
+        None
+    }
+}
+
+fn position(location: &Location) -> (usize, usize) {
+    (location.line(), location.column())
+}
@@ -0,0 +1,44 @@
+use std::{
+    io::{self, BufReader},
+    process::ExitCode,
+};
+
+use interpreter::rpc;
+use server::Server;
+
+mod document;
+mod server;
+
+fn main() -> ExitCode {
+    let mut reader = BufReader::new(io::stdin().lock());
+    let mut writer = io::stdout().lock();
+    let mut server = Server::default();
+
+    loop {
+        let message = match rpc::read_message(&mut reader) {
+            Ok(Some(message)) => message,
+            Ok(None) => break,
+            Err(err) => {
+                eprintln!("monkey-lsp: {}", err);
+                return ExitCode::FAILURE;
+            }
+        };
+
+        for reply in server.handle(&message) {
+            if let Err(err) = rpc::write_message(&mut writer, &reply) {
+                eprintln!("monkey-lsp: {}", err);
+                return ExitCode::FAILURE;
+            }
+        }
+
+        if server.exited {
+            break;
+        }
+    }
+
+    // the protocol asks for a failure status when the client exits without a shutdown
+    match server.shutdown {
+        true => ExitCode::SUCCESS,
+        false => ExitCode::FAILURE,
+    }
+}
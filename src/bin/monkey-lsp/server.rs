@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Value};
+
+use interpreter::{
+    formatter::printer::{self, FormatOptions},
+    lexer::location::Location,
+    linter::symbols::SymbolKind,
+};
+
+use crate::document::Document;
+
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+const SEVERITY_ERROR: i64 = 1;
+const SEVERITY_WARNING: i64 = 2;
+const COMPLETION_FUNCTION: i64 = 3;
+const COMPLETION_VARIABLE: i64 = 6;
+
+#[derive(Default)]
+pub struct Server {
+    documents: HashMap<String, Document>,
+    pub shutdown: bool,
+    pub exited: bool,
+}
+
+impl Server {
+    // handles one incoming message and returns the messages to send back
+    pub fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+
+        let Some(id) = message.get("id") else {
+            return self.notification(method, params);
+        };
+
+        let result = match method {
+            "initialize" => Ok(Server::capabilities()),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Value::Null)
+            }
+            "textDocument/definition" => self.definition(params),
+            "textDocument/references" => self.references(params),
+            "textDocument/hover" => self.hover(params),
+            "textDocument/completion" => self.completion(params),
+            "textDocument/formatting" => self.formatting(params),
+            _ => Err((METHOD_NOT_FOUND, format!("unknown method {}", method))),
+        };
+
+        vec![match result {
+            Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": {"code": code, "message": message},
+            }),
+        }]
+    }
+
+    fn capabilities() -> Value {
+        json!({
+            "capabilities": {
+                "textDocumentSync": 1,
+                "definitionProvider": true,
+                "referencesProvider": true,
+                "hoverProvider": true,
+                "completionProvider": {},
+                "documentFormattingProvider": true,
+            },
+            "serverInfo": {"name": "monkey-lsp"},
+        })
+    }
+
+    fn notification(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+
+        let text = match method {
+            "exit" => {
+                self.exited = true;
+                return vec![];
+            }
+            "textDocument/didOpen" => params["textDocument"]["text"].as_str(),
+            // the server asks for full syncs, so the last change holds the whole text
+            "textDocument/didChange" => params["contentChanges"]
+                .as_array()
+                .and_then(|changes| changes.last())
+                .and_then(|change| change["text"].as_str()),
+            "textDocument/didClose" => {
+                self.documents.remove(uri);
+                return vec![Server::diagnostics(uri, vec![])];
+            }
+            _ => return vec![],
+        };
+
+        let Some(text) = text else {
+            return vec![];
+        };
+
+        let document = Document::new(text.to_string());
+        let diagnostics = document
+            .errors
+            .iter()
+            .map(|error| {
+                json!({
+                    "range": range(&error.location, 1),
+                    "severity": SEVERITY_ERROR,
+                    "source": "monkey",
+                    "message": error.kind.to_string(),
+                })
+            })
+            .chain(document.warnings.iter().map(|warning| {
+                json!({
+                    "range": range(&warning.location, 1),
+                    "severity": SEVERITY_WARNING,
+                    "source": "monkey",
+                    "code": warning.code.code(),
+                    "message": warning.message,
+                })
+            }))
+            .collect();
+        self.documents.insert(uri.to_string(), document);
+
+        vec![Server::diagnostics(uri, diagnostics)]
+    }
+
+    fn diagnostics(uri: &str, diagnostics: Vec<Value>) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": {"uri": uri, "diagnostics": diagnostics},
+        })
+    }
+
+    fn document(&self, params: &Value) -> Result<(&str, &Document), (i64, String)> {
+        let uri = params["textDocument"]["uri"]
+            .as_str()
+            .ok_or((INVALID_PARAMS, "missing textDocument.uri".to_string()))?;
+
+        self.documents
+            .get_key_value(uri)
+            .map(|(uri, document)| (uri.as_str(), document))
+            .ok_or((INVALID_PARAMS, format!("unknown document {}", uri)))
+    }
+
+    fn definition(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let (uri, document) = self.document(params)?;
+
+        Ok(match document.symbol_at(&location(&params["position"])?) {
+            Some(symbol) => json!({
+                "uri": uri,
+                "range": range(&symbol.location, symbol.name.chars().count()),
+            }),
+            None => Value::Null,
+        })
+    }
+
+    fn references(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let (uri, document) = self.document(params)?;
+        let Some(symbol) = document.symbol_at(&location(&params["position"])?) else {
+            return Ok(json!([]));
+        };
+
+        let declaration = params["context"]["includeDeclaration"]
+            .as_bool()
+            .unwrap_or(true)
+            .then_some(&symbol.location);
+        let width = symbol.name.chars().count();
+
+        Ok(declaration
+            .into_iter()
+            .chain(&symbol.references)
+            .map(|location| json!({"uri": uri, "range": range(location, width)}))
+            .collect())
+    }
+
+    fn hover(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let (_, document) = self.document(params)?;
+        let Some(symbol) = document.symbol_at(&location(&params["position"])?) else {
+            return Ok(Value::Null);
+        };
+
+        let signature = match (&symbol.signature, symbol.kind) {
+            (Some(signature), _) => format!("let {} = {}", symbol.name, signature),
+            (None, SymbolKind::Let) => format!("let {}", symbol.name),
+            (None, SymbolKind::Parameter) => format!("(parameter) {}", symbol.name),
+            (None, SymbolKind::Pattern) => format!("(binding) {}", symbol.name),
+        };
+
+        Ok(json!({
+            "contents": {"kind": "markdown", "value": format!("```monkey\n{}\n```", signature)},
+            "range": range(&symbol.location, symbol.name.chars().count()),
+        }))
+    }
+
+    fn completion(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let (_, document) = self.document(params)?;
+
+        Ok(document
+            .visible_at(&location(&params["position"])?)
+            .into_iter()
+            .map(|symbol| match &symbol.signature {
+                Some(signature) => json!({
+                    "label": symbol.name,
+                    "kind": COMPLETION_FUNCTION,
+                    "detail": signature,
+                }),
+                None => json!({"label": symbol.name, "kind": COMPLETION_VARIABLE}),
+            })
+            .collect())
+    }
+
+    fn formatting(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let (_, document) = self.document(params)?;
+        let options = FormatOptions {
+            indent_width: params["options"]["tabSize"].as_u64().unwrap_or(4) as usize,
+            ..FormatOptions::default()
+        };
+
+        // a document that does not parse is left alone; its diagnostics say why
+        let Ok(formatted) = printer::format(&document.text, &options) else {
+            return Ok(Value::Null);
+        };
+        if formatted == document.text {
+            return Ok(json!([]));
+        }
+
+        let lines = document.text.split('\n').count();
+        Ok(json!([{
+            "range": {
+                "start": {"line": 0, "character": 0},
+                "end": {"line": lines, "character": 0},
+            },
+            "newText": formatted,
+        }]))
+    }
+}
+
+// LSP positions count from zero, the lexer's from one
+fn location(position: &Value) -> Result<Location, (i64, String)> {
+    match (position["line"].as_u64(), position["character"].as_u64()) {
+        (Some(line), Some(character)) => {
+            Ok(Location::new(line as usize + 1, character as usize + 1))
+        }
+        _ => Err((INVALID_PARAMS, "invalid position".to_string())),
+    }
+}
+
+fn range(location: &Location, width: usize) -> Value {
+    let line = location.line().saturating_sub(1);
+    let character = location.column().saturating_sub(1);
+
+    json!({
+        "start": {"line": line, "character": character},
+        "end": {"line": line, "character": character + width},
+    })
+}
@@ -0,0 +1,785 @@
+use std::{
+    cell::RefCell,
+    fs,
+    io::{self, Read},
+    path::Path,
+    process::ExitCode,
+    rc::Rc,
+};
+
+use interpreter::{
+    debugger::Debugger,
+    evaluator::{
+        coverage::Coverage,
+        environment::Environment,
+        error::EvaluationErrorKind,
+        evaluator::Evaluator,
+        hook::Hook,
+        profile::Profiler,
+        trace::{TraceFormat, Tracer},
+    },
+    formatter::printer::{self, FormatOptions},
+    lexer::{lexer::Lexer, token::TokenType},
+    linter::checker::{self, LintOptions},
+    parser::parser::Parser,
+    testing::{self, Outcome},
+    Interpreter,
+};
+
+use crate::debug::Console;
+
+#[cfg(feature = "serde")]
+pub const USAGE: &str = "\
+usage: interpreter [--trace[=text|json]] [--profile[=table|collapsed]] [--coverage[=FILE]] [file]
+       interpreter --dump-ast=json|sexpr [file]
+       interpreter --dump-tokens [file]
+       interpreter fmt [--check] [--indent N] [--width N] [files...]
+       interpreter lint [--global NAME...] [files...]
+       interpreter debug [--break LINE...] file
+       interpreter test [--filter NAME] [dir]
+
+Without a file the REPL starts, and the other modes read from stdin.";
+
+#[cfg(not(feature = "serde"))]
+pub const USAGE: &str = "\
+usage: interpreter [--trace[=text]] [--profile[=table|collapsed]] [--coverage[=FILE]] [file]
+       interpreter --dump-ast=sexpr [file]
+       interpreter --dump-tokens [file]
+       interpreter fmt [--check] [--indent N] [--width N] [files...]
+       interpreter lint [--global NAME...] [files...]
+       interpreter debug [--break LINE...] file
+       interpreter test [--filter NAME] [dir]
+
+Without a file the REPL starts, and the other modes read from stdin.
+JSON output (--trace=json, --dump-ast=json) needs a build with --features serde.";
+
+#[cfg(not(feature = "serde"))]
+const NO_JSON: &str = "JSON output needs a build with --features serde";
+
+#[derive(Debug, PartialEq)]
+pub enum AstFormat {
+    #[cfg(feature = "serde")]
+    Json,
+    Sexpr,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ProfileFormat {
+    Table,
+    Collapsed,
+}
+
+#[derive(Debug, PartialEq, Default)]
+pub struct RunOptions {
+    pub trace: Option<TraceFormat>,
+    pub profile: Option<ProfileFormat>,
+    // where to write the lcov file
+    pub coverage: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Repl,
+    Run {
+        path: String,
+        options: RunOptions,
+    },
+    DumpAst {
+        format: AstFormat,
+        path: Option<String>,
+    },
+    DumpTokens(Option<String>),
+    Fmt {
+        check: bool,
+        options: FormatOptions,
+        paths: Vec<String>,
+    },
+    Lint {
+        options: LintOptions,
+        paths: Vec<String>,
+    },
+    Debug {
+        breakpoints: Vec<usize>,
+        path: String,
+    },
+    Test {
+        // only tests whose name contains it run
+        filter: Option<String>,
+        path: String,
+    },
+}
+
+impl Command {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+        let mut args = args.into_iter().peekable();
+
+        match args.peek().map(String::as_str) {
+            Some("fmt") => {
+                args.next();
+                Command::parse_fmt(args)
+            }
+            Some("lint") => {
+                args.next();
+                Command::parse_lint(args)
+            }
+            Some("debug") => {
+                args.next();
+                Command::parse_debug(args)
+            }
+            Some("test") => {
+                args.next();
+                Command::parse_test(args)
+            }
+            _ => Command::parse_run(args),
+        }
+    }
+
+    fn parse_fmt(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+        let mut check = false;
+        let mut options = FormatOptions::default();
+        let mut paths = vec![];
+
+        while let Some(arg) = args.next() {
+            match arg.split_once('=') {
+                _ if arg == "--check" => check = true,
+                Some(("--indent", value)) => options.indent_width = Command::number(&arg, value)?,
+                Some(("--width", value)) => options.line_width = Command::number(&arg, value)?,
+                _ if arg == "--indent" || arg == "--width" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("{} needs a value", arg))?;
+                    let value = Command::number(&arg, &value)?;
+                    match arg.as_str() {
+                        "--indent" => options.indent_width = value,
+                        _ => options.line_width = value,
+                    }
+                }
+                _ if arg.starts_with("--") => return Err(format!("unknown option: {}", arg)),
+                _ => paths.push(arg),
+            }
+        }
+
+        Ok(Command::Fmt {
+            check,
+            options,
+            paths,
+        })
+    }
+
+    fn parse_lint(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+        let mut options = LintOptions::default();
+        let mut paths = vec![];
+
+        while let Some(arg) = args.next() {
+            match arg.split_once('=') {
+                Some(("--global", name)) => options.globals.push(name.to_string()),
+                _ if arg == "--global" => options.globals.push(
+                    args.next()
+                        .ok_or_else(|| format!("{} needs a value", arg))?,
+                ),
+                _ if arg.starts_with("--") => return Err(format!("unknown option: {}", arg)),
+                _ => paths.push(arg),
+            }
+        }
+
+        Ok(Command::Lint { options, paths })
+    }
+
+    fn parse_debug(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+        let mut breakpoints = vec![];
+        let mut path = None;
+
+        while let Some(arg) = args.next() {
+            match arg.split_once('=') {
+                Some(("--break", line)) => breakpoints.push(Command::number(&arg, line)?),
+                _ if arg == "--break" => {
+                    let line = args
+                        .next()
+                        .ok_or_else(|| format!("{} needs a value", arg))?;
+                    breakpoints.push(Command::number(&arg, &line)?);
+                }
+                _ if arg.starts_with("--") => return Err(format!("unknown option: {}", arg)),
+                _ if path.is_some() => return Err(format!("unexpected argument: {}", arg)),
+                _ => path = Some(arg),
+            }
+        }
+
+        let path = path.ok_or_else(|| "debug needs a file".to_string())?;
+        Ok(Command::Debug { breakpoints, path })
+    }
+
+    fn parse_test(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+        let mut filter = None;
+        let mut path = None;
+
+        while let Some(arg) = args.next() {
+            match arg.split_once('=') {
+                Some(("--filter", name)) => filter = Some(name.to_string()),
+                _ if arg == "--filter" => {
+                    filter = Some(
+                        args.next()
+                            .ok_or_else(|| format!("{} needs a value", arg))?,
+                    )
+                }
+                _ if arg.starts_with("--") => return Err(format!("unknown option: {}", arg)),
+                _ if path.is_some() => return Err(format!("unexpected argument: {}", arg)),
+                _ => path = Some(arg),
+            }
+        }
+
+        Ok(Command::Test {
+            filter,
+            path: path.unwrap_or_else(|| ".".to_string()),
+        })
+    }
+
+    fn number(option: &str, value: &str) -> Result<usize, String> {
+        value
+            .parse()
+            .map_err(|_| format!("invalid value for {}: {}", option, value))
+    }
+
+    fn parse_run(args: impl Iterator<Item = String>) -> Result<Command, String> {
+        let mut dump = None;
+        let mut path = None;
+        let mut options = RunOptions::default();
+        // the last option that only makes sense when running a file
+        let mut run_option = None;
+
+        for arg in args {
+            if ["--trace", "--profile", "--coverage"]
+                .iter()
+                .any(|option| arg.starts_with(option))
+            {
+                run_option = arg.split('=').next().map(str::to_string);
+            }
+
+            if arg == "--trace" {
+                options.trace = Some(TraceFormat::Text);
+            } else if let Some(format) = arg.strip_prefix("--trace=") {
+                options.trace = Some(match format {
+                    "text" => TraceFormat::Text,
+                    #[cfg(feature = "serde")]
+                    "json" => TraceFormat::Json,
+                    #[cfg(not(feature = "serde"))]
+                    "json" => return Err(NO_JSON.to_string()),
+                    format => return Err(format!("unknown trace format: {}", format)),
+                });
+            } else if arg == "--profile" {
+                options.profile = Some(ProfileFormat::Table);
+            } else if let Some(format) = arg.strip_prefix("--profile=") {
+                options.profile = Some(match format {
+                    "table" => ProfileFormat::Table,
+                    "collapsed" => ProfileFormat::Collapsed,
+                    format => return Err(format!("unknown profile format: {}", format)),
+                });
+            } else if arg == "--coverage" {
+                options.coverage = Some("lcov.info".to_string());
+            } else if let Some(path) = arg.strip_prefix("--coverage=") {
+                options.coverage = Some(path.to_string());
+            } else if let Some(format) = arg.strip_prefix("--dump-ast=") {
+                let format = match format {
+                    #[cfg(feature = "serde")]
+                    "json" => AstFormat::Json,
+                    #[cfg(not(feature = "serde"))]
+                    "json" => return Err(NO_JSON.to_string()),
+                    "sexpr" => AstFormat::Sexpr,
+                    format => return Err(format!("unknown AST format: {}", format)),
+                };
+                dump = Some(Some(format));
+            } else if arg == "--dump-ast" {
+                #[cfg(feature = "serde")]
+                return Err("--dump-ast needs a format: json or sexpr".to_string());
+                #[cfg(not(feature = "serde"))]
+                return Err("--dump-ast needs a format: sexpr".to_string());
+            } else if arg == "--dump-tokens" {
+                dump = Some(None);
+            } else if arg.starts_with("--") {
+                return Err(format!("unknown option: {}", arg));
+            } else if path.is_some() {
+                return Err(format!("unexpected argument: {}", arg));
+            } else {
+                path = Some(arg);
+            }
+        }
+
+        if let Some(option) = run_option.filter(|_| dump.is_some() || path.is_none()) {
+            return Err(format!("{} needs a file to run", option));
+        }
+
+        Ok(match (dump, path) {
+            (Some(Some(format)), path) => Command::DumpAst { format, path },
+            (Some(None), path) => Command::DumpTokens(path),
+            (None, Some(path)) => Command::Run { path, options },
+            (None, None) => Command::Repl,
+        })
+    }
+}
+
+pub fn read_source(path: Option<&str>) -> Result<String, String> {
+    match path {
+        Some(path) => {
+            fs::read_to_string(path).map_err(|err| format!("failed to read {}: {}", path, err))
+        }
+        None => {
+            let mut source = String::new();
+            io::stdin()
+                .read_to_string(&mut source)
+                .map_err(|err| format!("failed to read stdin: {}", err))?;
+            Ok(source)
+        }
+    }
+}
+
+pub fn run(path: &str, options: &RunOptions) -> Result<ExitCode, String> {
+    let source = read_source(Some(path))?;
+    let mut interpreter = Interpreter::new();
+    let mut hooks: Vec<Box<dyn Hook>> = vec![];
+
+    if let Some(format) = options.trace {
+        hooks.push(Box::new(Tracer::new(io::stderr(), format)));
+    }
+    let profiler = Rc::new(RefCell::new(Profiler::new()));
+    if options.profile.is_some() {
+        hooks.push(Box::new(Rc::clone(&profiler)));
+    }
+    let coverage = options.coverage.as_ref().map(|_| {
+        let program = Parser::new(Lexer::new(&source)).parse_program();
+        Rc::new(RefCell::new(Coverage::new(&program)))
+    });
+    if let Some(coverage) = &coverage {
+        hooks.push(Box::new(Rc::clone(coverage)));
+    }
+    if !hooks.is_empty() {
+        interpreter.set_hook(Box::new(hooks));
+    }
+
+    let result = interpreter.eval_str(&source);
+
+    if let (Some(coverage), Some(lcov_path)) = (coverage, &options.coverage) {
+        let mut lcov = vec![];
+        coverage.borrow().write_lcov(path, &mut lcov).ok();
+        fs::write(lcov_path, lcov)
+            .map_err(|err| format!("failed to write {}: {}", lcov_path, err))?;
+    }
+
+    let profiler = profiler.borrow();
+    let written = match options.profile {
+        Some(ProfileFormat::Table) => profiler.write_table(&mut io::stderr()),
+        Some(ProfileFormat::Collapsed) => profiler.write_collapsed(&mut io::stderr()),
+        None => Ok(()),
+    };
+    written.map_err(|err| format!("failed to write the profile: {}", err))?;
+
+    match result {
+        Ok(_) => Ok(ExitCode::SUCCESS),
+        Err(err) => {
+            eprintln!("{}", err);
+            Ok(ExitCode::FAILURE)
+        }
+    }
+}
+
+pub fn dump_tokens(path: Option<&str>) -> Result<ExitCode, String> {
+    let source = read_source(path)?;
+    let mut lexer = Lexer::new(&source);
+
+    loop {
+        let token = lexer.next_token();
+        println!("{} {}", token.location, token.token_type);
+        if token.token_type == TokenType::EOF {
+            return Ok(ExitCode::SUCCESS);
+        }
+    }
+}
+
+pub fn dump_ast(format: &AstFormat, path: Option<&str>) -> Result<ExitCode, String> {
+    let source = read_source(path)?;
+    let mut parser = Parser::new(Lexer::new(&source));
+    let program = parser.parse_program();
+
+    if !parser.errors.is_empty() {
+        for error in parser.errors {
+            eprintln!("{}", error);
+        }
+        return Ok(ExitCode::FAILURE);
+    }
+
+    match format {
+        #[cfg(feature = "serde")]
+        AstFormat::Json => println!("{}", program_to_json(&program)?),
+        AstFormat::Sexpr => print!("{}", program.to_sexpr()),
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+pub fn fmt(check: bool, options: &FormatOptions, paths: &[String]) -> Result<ExitCode, String> {
+    if paths.is_empty() {
+        let source = read_source(None)?;
+        return match printer::format(&source, options) {
+            Ok(formatted) if check && formatted != source => {
+                eprintln!("stdin is not formatted");
+                Ok(ExitCode::FAILURE)
+            }
+            Ok(_) if check => Ok(ExitCode::SUCCESS),
+            Ok(formatted) => {
+                print!("{}", formatted);
+                Ok(ExitCode::SUCCESS)
+            }
+            Err(errors) => {
+                for error in errors {
+                    eprintln!("stdin: {}", error);
+                }
+                Ok(ExitCode::FAILURE)
+            }
+        };
+    }
+
+    let mut success = true;
+
+    for path in paths {
+        let source = read_source(Some(path))?;
+        match printer::format(&source, options) {
+            Ok(formatted) if formatted == source => {}
+            Ok(_) if check => {
+                eprintln!("{} is not formatted", path);
+                success = false;
+            }
+            Ok(formatted) => fs::write(path, formatted)
+                .map_err(|err| format!("failed to write {}: {}", path, err))?,
+            Err(errors) => {
+                for error in errors {
+                    eprintln!("{}: {}", path, error);
+                }
+                success = false;
+            }
+        }
+    }
+
+    Ok(match success {
+        true => ExitCode::SUCCESS,
+        false => ExitCode::FAILURE,
+    })
+}
+
+pub fn lint(options: &LintOptions, paths: &[String]) -> Result<ExitCode, String> {
+    let mut clean = true;
+    let sources = match paths.is_empty() {
+        true => vec![("stdin".to_string(), read_source(None)?)],
+        false => paths
+            .iter()
+            .map(|path| Ok((path.clone(), read_source(Some(path))?)))
+            .collect::<Result<_, String>>()?,
+    };
+
+    for (name, source) in sources {
+        match checker::lint(&source, options) {
+            Ok(warnings) => {
+                for warning in &warnings {
+                    println!("{}:{}", name, warning);
+                }
+                clean &= warnings.is_empty();
+            }
+            Err(errors) => {
+                for error in errors {
+                    eprintln!("{}: {}", name, error);
+                }
+                clean = false;
+            }
+        }
+    }
+
+    Ok(match clean {
+        true => ExitCode::SUCCESS,
+        false => ExitCode::FAILURE,
+    })
+}
+
+pub fn debug(path: &str, breakpoints: &[usize]) -> Result<ExitCode, String> {
+    let source = read_source(Some(path))?;
+    let mut parser = Parser::new(Lexer::new(&source));
+    let program = parser.parse_program();
+
+    if !parser.errors.is_empty() {
+        for error in parser.errors {
+            eprintln!("{}: {}", path, error);
+        }
+        return Ok(ExitCode::FAILURE);
+    }
+
+    let console = Console::new(path, &source, io::stdin().lock(), io::stdout());
+    let debugger = match breakpoints.is_empty() {
+        true => Debugger::new(console),
+        false => Debugger::new(console).with_breakpoints(breakpoints.iter().copied()),
+    };
+    let mut evaluator = Evaluator::new();
+    evaluator.set_hook(Box::new(debugger));
+
+    println!("debugging {}, type help for commands", path);
+    match evaluator.eval(program, Environment::new()) {
+        Ok(_) => Ok(ExitCode::SUCCESS),
+        // the user quit, which needs no explanation
+        Err(err) if *err.kind == EvaluationErrorKind::Interrupted => Ok(ExitCode::FAILURE),
+        Err(err) => {
+            eprintln!("{}", err);
+            Ok(ExitCode::FAILURE)
+        }
+    }
+}
+
+pub fn test(path: &str, filter: Option<&str>) -> Result<ExitCode, String> {
+    let files = testing::discover(Path::new(path))
+        .map_err(|err| format!("failed to read {}: {}", path, err))?;
+    let (mut passed, mut failed, mut filtered) = (0, 0, 0);
+    let mut failures = vec![];
+
+    for file in files {
+        let file = file.display().to_string();
+        let source = read_source(Some(&file))?;
+        let mut parser = Parser::new(Lexer::new(&source));
+        let program = parser.parse_program();
+
+        if !parser.errors.is_empty() {
+            for error in parser.errors {
+                eprintln!("{}: {}", file, error);
+            }
+            failed += 1;
+            continue;
+        }
+
+        let (tests, skipped): (Vec<_>, Vec<_>) = testing::test_cases(&program)
+            .into_iter()
+            .partition(|test| filter.is_none_or(|filter| test.name.contains(filter)));
+        filtered += skipped.len();
+        if tests.is_empty() {
+            continue;
+        }
+
+        let plural = match tests.len() {
+            1 => "",
+            _ => "s",
+        };
+        println!("running {} test{} in {}", tests.len(), plural, file);
+        for test in tests {
+            match testing::run_test(&program, &test.name) {
+                Outcome::Passed => {
+                    println!("test {} ... ok", test.name);
+                    passed += 1;
+                }
+                Outcome::Failed(failure) => {
+                    println!("test {} ... FAILED", test.name);
+                    failed += 1;
+                    let location = failure.location.unwrap_or(test.location);
+                    failures.push(format!(
+                        "---- {} at {}:{}:{} ----\n{}: {}",
+                        test.name,
+                        file,
+                        location.line(),
+                        location.column(),
+                        failure.kind,
+                        failure.message
+                    ));
+                }
+            }
+        }
+    }
+
+    if !failures.is_empty() {
+        println!("\nfailures:");
+        for failure in &failures {
+            println!("\n{}", failure);
+        }
+    }
+
+    let result = match failed {
+        0 => "ok",
+        _ => "FAILED",
+    };
+    println!(
+        "\ntest result: {}. {} passed; {} failed; {} filtered out",
+        result, passed, failed, filtered
+    );
+
+    Ok(match failed {
+        0 => ExitCode::SUCCESS,
+        _ => ExitCode::FAILURE,
+    })
+}
+
+#[cfg(feature = "serde")]
+fn program_to_json(program: &interpreter::parser::ast::program::Program) -> Result<String, String> {
+    serde_json::to_string_pretty(program).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        Command::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse(&[]), Ok(Command::Repl));
+        assert_eq!(
+            parse(&["main.mk"]),
+            Ok(Command::Run {
+                path: "main.mk".to_string(),
+                options: RunOptions::default(),
+            })
+        );
+        assert_eq!(
+            parse(&["--trace", "main.mk"]),
+            Ok(Command::Run {
+                path: "main.mk".to_string(),
+                options: RunOptions {
+                    trace: Some(TraceFormat::Text),
+                    profile: None,
+                    coverage: None,
+                },
+            })
+        );
+        assert_eq!(
+            parse(&["main.mk", "--profile=collapsed"]),
+            Ok(Command::Run {
+                path: "main.mk".to_string(),
+                options: RunOptions {
+                    trace: None,
+                    profile: Some(ProfileFormat::Collapsed),
+                    coverage: None,
+                },
+            })
+        );
+        assert_eq!(
+            parse(&["--coverage", "main.mk"]),
+            Ok(Command::Run {
+                path: "main.mk".to_string(),
+                options: RunOptions {
+                    trace: None,
+                    profile: None,
+                    coverage: Some("lcov.info".to_string()),
+                },
+            })
+        );
+        assert_eq!(
+            parse(&["--dump-ast=sexpr", "main.mk"]),
+            Ok(Command::DumpAst {
+                format: AstFormat::Sexpr,
+                path: Some("main.mk".to_string()),
+            })
+        );
+        #[cfg(feature = "serde")]
+        assert_eq!(
+            parse(&["--dump-ast=json"]),
+            Ok(Command::DumpAst {
+                format: AstFormat::Json,
+                path: None,
+            })
+        );
+        #[cfg(not(feature = "serde"))]
+        assert_eq!(parse(&["--dump-ast=json"]), Err(NO_JSON.to_string()));
+        assert_eq!(parse(&["--dump-tokens"]), Ok(Command::DumpTokens(None)));
+        assert_eq!(
+            parse(&["lint", "--global", "puts", "--global=len", "a.mk"]),
+            Ok(Command::Lint {
+                options: LintOptions {
+                    globals: vec!["puts".to_string(), "len".to_string()],
+                },
+                paths: vec!["a.mk".to_string()],
+            })
+        );
+        assert_eq!(
+            parse(&["debug", "--break", "3", "--break=7", "a.mk"]),
+            Ok(Command::Debug {
+                breakpoints: vec![3, 7],
+                path: "a.mk".to_string(),
+            })
+        );
+        assert_eq!(
+            parse(&["test"]),
+            Ok(Command::Test {
+                filter: None,
+                path: ".".to_string(),
+            })
+        );
+        assert_eq!(
+            parse(&["test", "--filter=add", "tests"]),
+            Ok(Command::Test {
+                filter: Some("add".to_string()),
+                path: "tests".to_string(),
+            })
+        );
+        assert_eq!(
+            parse(&[
+                "fmt",
+                "--check",
+                "--indent",
+                "2",
+                "--width=100",
+                "a.mk",
+                "b.mk"
+            ]),
+            Ok(Command::Fmt {
+                check: true,
+                options: FormatOptions {
+                    indent_width: 2,
+                    line_width: 100,
+                },
+                paths: vec!["a.mk".to_string(), "b.mk".to_string()],
+            })
+        );
+    }
+
+    #[test]
+    fn test_fmt_keeps_every_statement() {
+        let path = std::env::temp_dir().join(format!("monkey-fmt-{}.mk", std::process::id()));
+        fs::write(&path, "if (true) { 1 }\nimportant(2);\n").unwrap();
+
+        let paths = [path.to_string_lossy().to_string()];
+        let status = fmt(false, &FormatOptions::default(), &paths).unwrap();
+        let formatted = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(status, ExitCode::SUCCESS);
+        assert_eq!(formatted, "if (true) {\n    1\n};\nimportant(2);\n");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse(&["debug"]), Err("debug needs a file".to_string()));
+        assert_eq!(
+            parse(&["test", "--filter"]),
+            Err("--filter needs a value".to_string())
+        );
+        assert_eq!(
+            parse(&["--trace"]),
+            Err("--trace needs a file to run".to_string())
+        );
+        assert_eq!(
+            parse(&["--dump-tokens", "--profile", "a.mk"]),
+            Err("--profile needs a file to run".to_string())
+        );
+        assert_eq!(
+            parse(&["--trace=xml", "a.mk"]),
+            Err("unknown trace format: xml".to_string())
+        );
+        assert_eq!(
+            parse(&["--dump-ast=yaml"]),
+            Err("unknown AST format: yaml".to_string())
+        );
+        assert_eq!(
+            parse(&["--verbose"]),
+            Err("unknown option: --verbose".to_string())
+        );
+        assert_eq!(
+            parse(&["a.mk", "b.mk"]),
+            Err("unexpected argument: b.mk".to_string())
+        );
+        assert_eq!(
+            parse(&["fmt", "--width", "wide"]),
+            Err("invalid value for --width: wide".to_string())
+        );
+    }
+}
@@ -0,0 +1,213 @@
+use std::io::{BufRead, Write};
+
+use interpreter::{
+    debugger::{Action, Frontend, Pause},
+    Value,
+};
+
+pub const HELP: &str = "\
+step, s          run to the next statement, entering calls
+next, n          run to the next statement of this call
+finish, f        run until this call returns
+continue, c      run until a breakpoint
+break, b LINE    set a breakpoint
+delete, d LINE   remove a breakpoint
+env, e           print the scopes of the paused statement
+print, p EXPR    evaluate an expression here
+watch, w EXPR    evaluate an expression at every pause
+unwatch, u EXPR  stop watching an expression
+quit, q          abort the program";
+
+// drives the debugger from commands typed on a terminal
+pub struct Console<R, W> {
+    path: String,
+    lines: Vec<String>,
+    input: R,
+    output: W,
+    last: Option<Action>,
+}
+
+impl<R: BufRead, W: Write> Console<R, W> {
+    pub fn new(path: &str, source: &str, input: R, output: W) -> Self {
+        Console {
+            path: path.to_string(),
+            lines: source.lines().map(str::to_string).collect(),
+            input,
+            output,
+            last: None,
+        }
+    }
+
+    fn show(&mut self, pause: &mut Pause) {
+        if let Some(location) = pause.location() {
+            let line = location.line();
+            let text = self.lines.get(line - 1).map_or("", String::as_str);
+            writeln!(self.output, "{}:{}:{}", self.path, line, location.column()).ok();
+            writeln!(self.output, "{:>4} | {}", line, text).ok();
+        }
+
+        for (watch, value) in pause.watch_values() {
+            match value {
+                Ok(value) => writeln!(self.output, "{} = {}", watch, value),
+                Err(err) => writeln!(self.output, "{}: {}", watch, err),
+            }
+            .ok();
+        }
+    }
+
+    fn print_scopes(&mut self, pause: &Pause) {
+        let scopes = pause.scopes();
+        let last = scopes.len() - 1;
+
+        for (depth, scope) in scopes.into_iter().enumerate() {
+            let name = match depth {
+                0 => "local",
+                depth if depth == last => "global",
+                _ => "outer",
+            };
+            writeln!(self.output, "{} scope #{}:", name, depth).ok();
+            for (binding, value) in scope {
+                writeln!(self.output, "  {} = {}", binding, preview(&value)).ok();
+            }
+        }
+    }
+
+    fn command(&mut self, pause: &mut Pause, command: &str) -> Option<Action> {
+        let (name, argument) = command
+            .split_once(' ')
+            .map_or((command, ""), |(name, argument)| (name, argument.trim()));
+
+        match (name, argument) {
+            ("step" | "s", _) => return Some(Action::Step),
+            ("next" | "n", _) => return Some(Action::Next),
+            ("finish" | "f", _) => return Some(Action::Finish),
+            ("continue" | "c", _) => return Some(Action::Continue),
+            ("quit" | "q", _) => return Some(Action::Quit),
+            ("break" | "b", line) | ("delete" | "d", line) => match line.parse::<usize>() {
+                Ok(line) if name.starts_with('b') => {
+                    pause.breakpoints.insert(line);
+                    writeln!(self.output, "breakpoint at line {}", line)
+                }
+                Ok(line) => {
+                    pause.breakpoints.remove(&line);
+                    writeln!(self.output, "removed breakpoint at line {}", line)
+                }
+                Err(_) => writeln!(self.output, "{} needs a line number", name),
+            },
+            ("env" | "e", _) => {
+                self.print_scopes(pause);
+                Ok(())
+            }
+            ("print" | "p", expression) => match pause.evaluate(expression) {
+                Ok(value) => writeln!(self.output, "{}", value),
+                Err(err) => writeln!(self.output, "{}", err),
+            },
+            ("watch" | "w", expression) => {
+                pause.watches.push(expression.to_string());
+                writeln!(self.output, "watching {}", expression)
+            }
+            ("unwatch" | "u", expression) => {
+                pause.watches.retain(|watch| watch != expression);
+                writeln!(self.output, "stopped watching {}", expression)
+            }
+            ("help" | "h", _) => writeln!(self.output, "{}", HELP),
+            _ => writeln!(self.output, "unknown command: {} (try help)", command),
+        }
+        .ok();
+
+        None
+    }
+}
+
+impl<R: BufRead, W: Write> Frontend for Console<R, W> {
+    fn paused(&mut self, pause: &mut Pause) -> Action {
+        self.show(pause);
+
+        loop {
+            write!(self.output, "(debug) ").ok();
+            self.output.flush().ok();
+
+            let mut line = String::new();
+            match self.input.read_line(&mut line) {
+                Ok(0) | Err(_) => return Action::Quit,
+                Ok(_) => {}
+            }
+
+            // an empty line repeats the last stepping command
+            let action = match (line.trim(), self.last) {
+                ("", Some(last)) => Some(last),
+                ("", None) => None,
+                (command, _) => self.command(pause, command),
+            };
+
+            if let Some(action) = action {
+                self.last = Some(action);
+                return action;
+            }
+        }
+    }
+}
+
+// functions print their whole body, which is too much for a listing of variables
+fn preview(value: &Value) -> String {
+    match value {
+        Value::Function { .. } => "fn(...)".to_string(),
+        value => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use indoc::indoc;
+    use interpreter::{
+        debugger::Debugger,
+        evaluator::{environment::Environment, evaluator::Evaluator},
+        lexer::lexer::Lexer,
+        parser::parser::Parser,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_session() {
+        let source = indoc! {"
+            let double = fn(n) {
+                let result = n * 2;
+                result
+            };
+            let x = double(21);
+        "};
+        let input = "b 3\nw n + 1\nc\ne\np result\nbogus\nc\n";
+
+        let console = Console::new("main.mk", source, input.as_bytes(), vec![]);
+        let debugger = Rc::new(RefCell::new(Debugger::new(console)));
+        let mut evaluator = Evaluator::new();
+        evaluator.set_hook(Box::new(Rc::clone(&debugger)));
+        let program = Parser::new(Lexer::new(source)).parse_program();
+        let result = evaluator.eval(program, Environment::new());
+
+        assert_eq!(result, Ok(Value::Integer(42)));
+        assert_eq!(
+            String::from_utf8(debugger.borrow().frontend().output.clone()).unwrap(),
+            indoc! {"
+                main.mk:1:1
+                   1 | let double = fn(n) {
+                (debug) breakpoint at line 3
+                (debug) watching n + 1
+                (debug) main.mk:3:5
+                   3 |     result
+                n + 1 = 22
+                (debug) local scope #0:
+                  n = 21
+                  result = 42
+                outer scope #1:
+                global scope #2:
+                  double = fn(...)
+                (debug) 42
+                (debug) unknown command: bogus (try help)
+                (debug) "}
+        );
+    }
+}
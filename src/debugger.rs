@@ -0,0 +1,407 @@
+use std::{cell::RefCell, collections::BTreeSet, rc::Rc};
+
+use crate::{
+    evaluator::{
+        environment::Environment,
+        error::{EvaluationError, EvaluationErrorKind},
+        evaluator::Evaluator,
+        hook::Hook,
+        object::Object,
+    },
+    host::Error,
+    lexer::{lexer::Lexer, location::Location},
+    parser::{ast::statement::Statement, parser::Parser},
+};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Action {
+    // pause at the next statement, entering calls
+    Step,
+    // pause at the next statement of this call or of a caller
+    Next,
+    // pause once the current call has returned
+    Finish,
+    // run until a breakpoint
+    Continue,
+    // abort the evaluation
+    Quit,
+}
+
+// the interactive side of a debugger: decides what to do each time evaluation pauses
+pub trait Frontend {
+    fn paused(&mut self, pause: &mut Pause) -> Action;
+}
+
+// the program or a function call running in it, outermost first
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub name: String,
+    // the statement the frame is running and the scope it runs in, unset until the
+    // frame reaches its first statement
+    pub location: Option<Location>,
+    pub environment: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Frame {
+    fn new(name: String) -> Self {
+        Frame {
+            name,
+            location: None,
+            environment: None,
+        }
+    }
+}
+
+// an evaluation stopped before a statement
+pub struct Pause<'a> {
+    evaluator: &'a mut Evaluator,
+    statement: &'a Statement,
+    environment: &'a Rc<RefCell<Environment>>,
+    frames: &'a [Frame],
+    pub breakpoints: &'a mut BTreeSet<usize>,
+    pub watches: &'a mut Vec<String>,
+}
+
+impl Pause<'_> {
+    pub fn location(&self) -> Option<&Location> {
+        self.statement.location()
+    }
+
+    pub fn statement(&self) -> &Statement {
+        self.statement
+    }
+
+    pub fn call_depth(&self) -> usize {
+        self.evaluator.call_depth()
+    }
+
+    pub fn frames(&self) -> &[Frame] {
+        self.frames
+    }
+
+    // the bindings of the paused scope followed by those of each outer scope
+    pub fn scopes(&self) -> Vec<Vec<(String, Object)>> {
+        scopes(self.environment)
+    }
+
+    // runs source code in the paused scope, where it can read and bind variables
+    pub fn evaluate(&mut self, source: &str) -> Result<Object, Error> {
+        let environment = Rc::clone(self.environment);
+        self.evaluate_in(source, environment)
+    }
+
+    // like evaluate, in the scope of a frame further up the stack
+    pub fn evaluate_in(
+        &mut self,
+        source: &str,
+        environment: Rc<RefCell<Environment>>,
+    ) -> Result<Object, Error> {
+        let mut parser = Parser::new(Lexer::new(source));
+        let program = parser.parse_program();
+
+        if !parser.errors.is_empty() {
+            return Err(Error::Parse(parser.errors));
+        }
+
+        match self.evaluator.eval(program, environment)? {
+            Object::ReturnValue(value) => Ok(*value),
+            value => Ok(value),
+        }
+    }
+
+    pub fn watch_values(&mut self) -> Vec<(String, Result<Object, Error>)> {
+        self.watches
+            .clone()
+            .into_iter()
+            .map(|watch| {
+                let value = self.evaluate(&watch);
+                (watch, value)
+            })
+            .collect()
+    }
+}
+
+// the bindings of a scope followed by those of each of its outer scopes
+pub fn scopes(environment: &Rc<RefCell<Environment>>) -> Vec<Vec<(String, Object)>> {
+    let mut scopes = vec![];
+    let mut environment = Some(Rc::clone(environment));
+
+    while let Some(current) = environment {
+        scopes.push(current.borrow().bindings());
+        environment = current.borrow().outer();
+    }
+
+    scopes
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Mode {
+    Step,
+    Next(usize),
+    Finish(usize),
+    Continue,
+}
+
+// a hook that pauses evaluation at breakpoint lines and while stepping, handing
+// control to its front-end each time
+pub struct Debugger<F: Frontend> {
+    frontend: F,
+    breakpoints: BTreeSet<usize>,
+    watches: Vec<String>,
+    frames: Vec<Frame>,
+    mode: Mode,
+}
+
+impl<F: Frontend> Debugger<F> {
+    // the debugger starts out stepping, so the front-end gets control before the
+    // first statement runs
+    pub fn new(frontend: F) -> Self {
+        Debugger {
+            frontend,
+            breakpoints: BTreeSet::new(),
+            watches: vec![],
+            frames: vec![Frame::new("<program>".to_string())],
+            mode: Mode::Step,
+        }
+    }
+
+    pub fn with_breakpoints(mut self, lines: impl IntoIterator<Item = usize>) -> Self {
+        self.breakpoints.extend(lines);
+        self.mode = Mode::Continue;
+        self
+    }
+
+    // pauses before the first statement even when there are breakpoints to run to
+    pub fn stop_on_entry(mut self) -> Self {
+        self.mode = Mode::Step;
+        self
+    }
+
+    pub fn frontend(&self) -> &F {
+        &self.frontend
+    }
+
+    pub fn frontend_mut(&mut self) -> &mut F {
+        &mut self.frontend
+    }
+
+    pub fn into_frontend(self) -> F {
+        self.frontend
+    }
+
+    fn should_pause(&self, line: usize, depth: usize) -> bool {
+        self.breakpoints.contains(&line)
+            || match self.mode {
+                Mode::Step => true,
+                Mode::Next(from) => depth <= from,
+                Mode::Finish(from) => depth < from,
+                Mode::Continue => false,
+            }
+    }
+}
+
+impl<F: Frontend> Hook for Debugger<F> {
+    fn before_statement(
+        &mut self,
+        evaluator: &mut Evaluator,
+        statement: &Statement,
+        environment: &Rc<RefCell<Environment>>,
+    ) -> Result<(), EvaluationError> {
+        let Some(location) = statement.location() else {
+            return Ok(());
+        };
+
+        if let Some(frame) = self.frames.last_mut() {
+            frame.location = Some(location.clone());
+            frame.environment = Some(Rc::clone(environment));
+        }
+
+        let depth = evaluator.call_depth();
+        if !self.should_pause(location.line(), depth) {
+            return Ok(());
+        }
+
+        let mut pause = Pause {
+            evaluator,
+            statement,
+            environment,
+            frames: &self.frames,
+            breakpoints: &mut self.breakpoints,
+            watches: &mut self.watches,
+        };
+
+        self.mode = match self.frontend.paused(&mut pause) {
+            Action::Step => Mode::Step,
+            Action::Next => Mode::Next(depth),
+            Action::Finish => Mode::Finish(depth),
+            Action::Continue => Mode::Continue,
+            Action::Quit => return Err(EvaluationError::new(EvaluationErrorKind::Interrupted)),
+        };
+
+        Ok(())
+    }
+
+    fn enter_function(
+        &mut self,
+        _evaluator: &mut Evaluator,
+        function: &Object,
+        _arguments: &[Object],
+    ) {
+        let name = function.function_name().unwrap_or_default();
+        self.frames.push(Frame::new(name));
+    }
+
+    fn exit_function(
+        &mut self,
+        _evaluator: &mut Evaluator,
+        _result: &Result<Object, EvaluationError>,
+    ) {
+        // the program's own frame stays
+        if self.frames.len() > 1 {
+            self.frames.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use indoc::indoc;
+
+    use crate::evaluator::object::Object;
+
+    use super::*;
+
+    // answers pauses from a script and records where each one happened
+    struct Script {
+        actions: VecDeque<Action>,
+        pauses: Vec<(usize, usize)>,
+        watched: Vec<Vec<(String, Object)>>,
+        scopes: Vec<Vec<Vec<String>>>,
+        frames: Vec<Vec<(String, usize)>>,
+    }
+
+    impl Script {
+        fn new(actions: &[Action]) -> Self {
+            Script {
+                actions: actions.iter().copied().collect(),
+                pauses: vec![],
+                watched: vec![],
+                scopes: vec![],
+                frames: vec![],
+            }
+        }
+    }
+
+    impl Frontend for Script {
+        fn paused(&mut self, pause: &mut Pause) -> Action {
+            let line = pause.location().unwrap().line();
+            self.pauses.push((line, pause.call_depth()));
+            self.watched.push(
+                pause
+                    .watch_values()
+                    .into_iter()
+                    .map(|(watch, value)| (watch, value.unwrap_or(Object::Null)))
+                    .collect(),
+            );
+            self.scopes.push(
+                pause
+                    .scopes()
+                    .into_iter()
+                    .map(|scope| scope.into_iter().map(|(name, _)| name).collect())
+                    .collect(),
+            );
+            self.frames.push(
+                pause
+                    .frames()
+                    .iter()
+                    .map(|frame| (frame.name.clone(), frame.location.as_ref().unwrap().line()))
+                    .collect(),
+            );
+            self.actions.pop_front().unwrap_or(Action::Continue)
+        }
+    }
+
+    const SOURCE: &str = indoc! {"
+        let add = fn(a, b) {
+            let sum = a + b;
+            sum
+        };
+        let x = add(1, 2);
+        let y = add(x, 3);
+        y;
+    "};
+
+    fn debug(debugger: Debugger<Script>) -> (Result<Object, EvaluationError>, Script) {
+        let program = Parser::new(Lexer::new(SOURCE)).parse_program();
+        let debugger = Rc::new(RefCell::new(debugger));
+        let mut evaluator = Evaluator::new();
+
+        evaluator.set_hook(Box::new(Rc::clone(&debugger)));
+        let result = evaluator.eval(program, Environment::new());
+        drop(evaluator);
+
+        let debugger = Rc::try_unwrap(debugger).ok().unwrap().into_inner();
+        (result, debugger.frontend)
+    }
+
+    #[test]
+    fn test_stepping() {
+        use Action::*;
+
+        let (result, script) = debug(Debugger::new(Script::new(&[
+            Next, Step, Step, Step, Step, Finish, Continue,
+        ])));
+
+        assert_eq!(result, Ok(Object::Integer(6)));
+        assert_eq!(
+            script.pauses,
+            vec![(1, 0), (5, 0), (2, 1), (3, 1), (6, 0), (2, 1), (7, 0)]
+        );
+    }
+
+    #[test]
+    fn test_breakpoints_and_scopes() {
+        let (_, script) = debug(Debugger::new(Script::new(&[])).with_breakpoints([3]));
+
+        assert_eq!(script.pauses, vec![(3, 1), (3, 1)]);
+        assert_eq!(
+            script.frames[1],
+            vec![("<program>".to_string(), 6), ("add".to_string(), 3)]
+        );
+        assert_eq!(
+            script.scopes[1],
+            vec![
+                vec!["a".to_string(), "b".to_string(), "sum".to_string()],
+                vec![],
+                vec!["add".to_string(), "x".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_watches() {
+        let mut debugger = Debugger::new(Script::new(&[])).with_breakpoints([3]);
+        debugger.watches.push("sum * 10".to_string());
+        debugger.watches.push("missing".to_string());
+
+        let (_, script) = debug(debugger);
+
+        assert_eq!(
+            script.watched[0],
+            vec![
+                ("sum * 10".to_string(), Object::Integer(30)),
+                ("missing".to_string(), Object::Null),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_quit() {
+        let (result, script) = debug(Debugger::new(Script::new(&[Action::Quit])));
+
+        assert_eq!(*result.unwrap_err().kind, EvaluationErrorKind::Interrupted);
+        assert_eq!(script.pauses, vec![(1, 0)]);
+    }
+}
@@ -0,0 +1,353 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+};
+
+use super::object::{HashKey, Object};
+
+pub use interpreter_derive::{FromObject, IntoObject};
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ConversionError {
+    TypeMismatch {
+        expected: &'static str,
+        actual: &'static str,
+    },
+    MissingField(String),
+}
+
+impl Display for ConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConversionError::TypeMismatch { expected, actual } => {
+                write!(f, "expected {}, got {}", expected, actual)
+            }
+            ConversionError::MissingField(field) => write!(f, "missing field {}", field),
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+pub trait IntoObject {
+    fn into_object(self) -> Object;
+}
+
+pub trait FromObject: Sized {
+    fn from_object(object: &Object) -> Result<Self, ConversionError>;
+}
+
+fn mismatch(expected: &'static str, object: &Object) -> ConversionError {
+    ConversionError::TypeMismatch {
+        expected,
+        actual: object.type_name(),
+    }
+}
+
+// used by the derived FromObject impls: a missing key converts from null so
+// that Option fields may be left out
+#[doc(hidden)]
+pub fn from_field<T: FromObject>(
+    entries: &BTreeMap<HashKey, Object>,
+    field: &str,
+) -> Result<T, ConversionError> {
+    match entries.get(&HashKey::String(field.to_string())) {
+        Some(value) => T::from_object(value),
+        None => T::from_object(&Object::Null)
+            .map_err(|_| ConversionError::MissingField(field.to_string())),
+    }
+}
+
+impl IntoObject for Object {
+    fn into_object(self) -> Object {
+        self
+    }
+}
+
+impl FromObject for Object {
+    fn from_object(object: &Object) -> Result<Self, ConversionError> {
+        Ok(object.clone())
+    }
+}
+
+impl IntoObject for i64 {
+    fn into_object(self) -> Object {
+        Object::Integer(self)
+    }
+}
+
+impl FromObject for i64 {
+    fn from_object(object: &Object) -> Result<Self, ConversionError> {
+        match object {
+            Object::Integer(value) => Ok(*value),
+            object => Err(mismatch("Integer", object)),
+        }
+    }
+}
+
+impl IntoObject for bool {
+    fn into_object(self) -> Object {
+        Object::Boolean(self)
+    }
+}
+
+impl FromObject for bool {
+    fn from_object(object: &Object) -> Result<Self, ConversionError> {
+        match object {
+            Object::Boolean(value) => Ok(*value),
+            object => Err(mismatch("Boolean", object)),
+        }
+    }
+}
+
+impl IntoObject for () {
+    fn into_object(self) -> Object {
+        Object::Null
+    }
+}
+
+impl FromObject for () {
+    fn from_object(object: &Object) -> Result<Self, ConversionError> {
+        match object {
+            Object::Null => Ok(()),
+            object => Err(mismatch("Null", object)),
+        }
+    }
+}
+
+impl IntoObject for String {
+    fn into_object(self) -> Object {
+        Object::String(self)
+    }
+}
+
+impl IntoObject for &str {
+    fn into_object(self) -> Object {
+        Object::string(self)
+    }
+}
+
+impl FromObject for String {
+    fn from_object(object: &Object) -> Result<Self, ConversionError> {
+        match object {
+            Object::String(value) => Ok(value.clone()),
+            object => Err(mismatch("String", object)),
+        }
+    }
+}
+
+impl<T: IntoObject> IntoObject for Option<T> {
+    fn into_object(self) -> Object {
+        match self {
+            Some(value) => value.into_object(),
+            None => Object::Null,
+        }
+    }
+}
+
+impl<T: FromObject> FromObject for Option<T> {
+    fn from_object(object: &Object) -> Result<Self, ConversionError> {
+        match object {
+            Object::Null => Ok(None),
+            object => T::from_object(object).map(Some),
+        }
+    }
+}
+
+impl<T: IntoObject> IntoObject for Vec<T> {
+    fn into_object(self) -> Object {
+        Object::Array(self.into_iter().map(IntoObject::into_object).collect())
+    }
+}
+
+impl<T: FromObject> FromObject for Vec<T> {
+    fn from_object(object: &Object) -> Result<Self, ConversionError> {
+        match object {
+            Object::Array(elements) => elements.iter().map(T::from_object).collect(),
+            object => Err(mismatch("Array", object)),
+        }
+    }
+}
+
+impl<T: IntoObject> IntoObject for HashMap<String, T> {
+    fn into_object(self) -> Object {
+        Object::Hash(
+            self.into_iter()
+                .map(|(key, value)| (HashKey::String(key), value.into_object()))
+                .collect(),
+        )
+    }
+}
+
+impl<T: FromObject> FromObject for HashMap<String, T> {
+    fn from_object(object: &Object) -> Result<Self, ConversionError> {
+        let Object::Hash(entries) = object else {
+            return Err(mismatch("Hash", object));
+        };
+
+        entries
+            .iter()
+            .map(|(key, value)| match key {
+                HashKey::String(key) => Ok((key.clone(), T::from_object(value)?)),
+                HashKey::Integer(_) => Err(ConversionError::TypeMismatch {
+                    expected: "String",
+                    actual: "Integer",
+                }),
+                HashKey::Boolean(_) => Err(ConversionError::TypeMismatch {
+                    expected: "String",
+                    actual: "Boolean",
+                }),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, IntoObject, FromObject)]
+    struct Point {
+        x: i64,
+        y: i64,
+        label: Option<String>,
+    }
+
+    #[test]
+    fn test_primitives() {
+        assert_eq!(42.into_object(), Object::Integer(42));
+        assert_eq!(i64::from_object(&Object::Integer(42)), Ok(42));
+        assert_eq!(true.into_object(), Object::Boolean(true));
+        assert_eq!(bool::from_object(&Object::Boolean(false)), Ok(false));
+        assert_eq!(().into_object(), Object::Null);
+        assert_eq!(<()>::from_object(&Object::Null), Ok(()));
+        assert_eq!("foo".into_object(), Object::string("foo"));
+        assert_eq!(
+            String::from_object(&Object::string("foo")),
+            Ok("foo".to_string())
+        );
+    }
+
+    #[test]
+    fn test_containers() {
+        assert_eq!(None::<i64>.into_object(), Object::Null);
+        assert_eq!(Option::<i64>::from_object(&Object::Null), Ok(None));
+        assert_eq!(Option::<i64>::from_object(&Object::Integer(1)), Ok(Some(1)));
+
+        let array = vec![1, 2, 3].into_object();
+        assert_eq!(
+            array,
+            Object::Array(vec![
+                Object::Integer(1),
+                Object::Integer(2),
+                Object::Integer(3)
+            ])
+        );
+        assert_eq!(Vec::<i64>::from_object(&array), Ok(vec![1, 2, 3]));
+
+        let map = HashMap::from([("a".to_string(), true)]);
+        let hash = map.clone().into_object();
+        assert_eq!(
+            hash,
+            Object::Hash(BTreeMap::from([(
+                HashKey::String("a".to_string()),
+                Object::Boolean(true)
+            )]))
+        );
+        assert_eq!(HashMap::<String, bool>::from_object(&hash), Ok(map));
+    }
+
+    #[test]
+    fn test_conversion_errors() {
+        assert_eq!(
+            i64::from_object(&Object::string("1")),
+            Err(ConversionError::TypeMismatch {
+                expected: "Integer",
+                actual: "String",
+            })
+        );
+        assert_eq!(
+            Vec::<bool>::from_object(&Object::Array(vec![Object::Integer(1)])),
+            Err(ConversionError::TypeMismatch {
+                expected: "Boolean",
+                actual: "Integer",
+            })
+        );
+        assert_eq!(
+            HashMap::<String, i64>::from_object(&Object::Hash(BTreeMap::from([(
+                HashKey::Integer(1),
+                Object::Integer(1)
+            )]))),
+            Err(ConversionError::TypeMismatch {
+                expected: "String",
+                actual: "Integer",
+            })
+        );
+        assert_eq!(
+            ConversionError::TypeMismatch {
+                expected: "Integer",
+                actual: "String",
+            }
+            .to_string(),
+            "expected Integer, got String"
+        );
+    }
+
+    #[test]
+    fn test_derive() {
+        let point = Point {
+            x: 1,
+            y: 2,
+            label: Some("origin".to_string()),
+        };
+        let object = point.into_object();
+        assert_eq!(
+            object,
+            Object::Hash(BTreeMap::from([
+                (HashKey::String("x".to_string()), Object::Integer(1)),
+                (HashKey::String("y".to_string()), Object::Integer(2)),
+                (
+                    HashKey::String("label".to_string()),
+                    Object::string("origin")
+                ),
+            ]))
+        );
+        assert_eq!(
+            Point::from_object(&object),
+            Ok(Point {
+                x: 1,
+                y: 2,
+                label: Some("origin".to_string()),
+            })
+        );
+
+        let object = Object::Hash(BTreeMap::from([
+            (HashKey::String("x".to_string()), Object::Integer(3)),
+            (HashKey::String("y".to_string()), Object::Integer(4)),
+        ]));
+        assert_eq!(
+            Point::from_object(&object),
+            Ok(Point {
+                x: 3,
+                y: 4,
+                label: None,
+            })
+        );
+
+        let object = Object::Hash(BTreeMap::from([(
+            HashKey::String("x".to_string()),
+            Object::Integer(3),
+        )]));
+        assert_eq!(
+            Point::from_object(&object),
+            Err(ConversionError::MissingField("y".to_string()))
+        );
+        assert_eq!(
+            Point::from_object(&Object::Integer(1)),
+            Err(ConversionError::TypeMismatch {
+                expected: "Hash",
+                actual: "Integer",
+            })
+        );
+    }
+}
@@ -0,0 +1,294 @@
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    io::{self, Write},
+    rc::Rc,
+};
+
+use crate::{
+    lexer::location::Location,
+    parser::ast::{expression::Expression, program::Program, statement::Statement},
+};
+
+use super::{
+    environment::Environment,
+    error::EvaluationError,
+    evaluator::Evaluator,
+    hook::{Branch, Hook},
+};
+
+// nodes are identified by where they start in the source, as (line, column)
+type Site = (usize, usize);
+
+fn site(location: &Location) -> Site {
+    (location.line(), location.column())
+}
+
+// a hook that counts how often each statement of a program runs and which way each
+// of its `if`s goes
+pub struct Coverage {
+    statements: BTreeMap<Site, u64>,
+    // how often the consequence and the alternative ran
+    branches: BTreeMap<Site, [u64; 2]>,
+}
+
+impl Coverage {
+    pub fn new(program: &Program) -> Self {
+        let mut coverage = Coverage {
+            statements: BTreeMap::new(),
+            branches: BTreeMap::new(),
+        };
+        coverage.collect_statements(&program.statements);
+        coverage
+    }
+
+    // the execution count of each line that starts a statement
+    pub fn lines(&self) -> BTreeMap<usize, u64> {
+        let mut lines = BTreeMap::new();
+        for (&(line, _), &count) in &self.statements {
+            let hits = lines.entry(line).or_insert(count);
+            *hits = (*hits).max(count);
+        }
+        lines
+    }
+
+    pub fn branches(&self) -> impl Iterator<Item = (&Site, &[u64; 2])> {
+        self.branches.iter()
+    }
+
+    // writes the counts as an lcov tracefile for the source at path
+    pub fn write_lcov(&self, path: &str, output: &mut impl Write) -> io::Result<()> {
+        writeln!(output, "TN:")?;
+        writeln!(output, "SF:{}", path)?;
+
+        let mut branches_hit = 0;
+        for (block, (&(line, _), counts)) in self.branches.iter().enumerate() {
+            let evaluated = counts[0] + counts[1] > 0;
+            for (branch, &count) in counts.iter().enumerate() {
+                // lcov writes `-` for branches whose condition never ran
+                let taken = match evaluated {
+                    true => count.to_string(),
+                    false => "-".to_string(),
+                };
+                writeln!(output, "BRDA:{},{},{},{}", line, block, branch, taken)?;
+                branches_hit += (count > 0) as usize;
+            }
+        }
+        writeln!(output, "BRF:{}", self.branches.len() * 2)?;
+        writeln!(output, "BRH:{}", branches_hit)?;
+
+        let lines = self.lines();
+        for (line, count) in &lines {
+            writeln!(output, "DA:{},{}", line, count)?;
+        }
+        writeln!(output, "LF:{}", lines.len())?;
+        writeln!(
+            output,
+            "LH:{}",
+            lines.values().filter(|&&count| count > 0).count()
+        )?;
+
+        writeln!(output, "end_of_record")
+    }
+
+    fn collect_statements(&mut self, statements: &[Statement]) {
+        for statement in statements {
+            if let Some(location) = statement.location() {
+                self.statements.insert(site(location), 0);
+            }
+
+            match statement {
+                Statement::Let { value, .. }
+                | Statement::Return { value, .. }
+                | Statement::Throw { value, .. }
+                | Statement::Expression(value, _) => self.collect_expression(value),
+                Statement::Block(statements) => self.collect_statements(statements),
+            }
+        }
+    }
+
+    fn collect_expression(&mut self, expression: &Expression) {
+        match expression {
+            Expression::Int(_)
+            | Expression::Bool(_)
+            | Expression::Str(_)
+            | Expression::Null
+            | Expression::Identifier(..) => {}
+            Expression::Array(elements) => {
+                elements
+                    .iter()
+                    .for_each(|element| self.collect_expression(element));
+            }
+            Expression::Hash(entries) => {
+                for (key, value) in entries {
+                    self.collect_expression(key);
+                    self.collect_expression(value);
+                }
+            }
+            Expression::If {
+                condition,
+                consequence,
+                alternative,
+                position,
+            } => {
+                self.branches.insert(site(&position.0), [0, 0]);
+                self.collect_expression(condition);
+                self.collect_statements(consequence);
+                if let Some(alternative) = alternative {
+                    self.collect_statements(alternative);
+                }
+            }
+            Expression::Function {
+                parameters, body, ..
+            } => {
+                for default in parameters.iter().filter_map(|p| p.default.as_ref()) {
+                    self.collect_expression(default);
+                }
+                self.collect_statements(body);
+            }
+            Expression::Call {
+                function,
+                arguments,
+                named_arguments,
+            } => {
+                self.collect_expression(function);
+                arguments
+                    .iter()
+                    .for_each(|argument| self.collect_expression(argument));
+                named_arguments
+                    .iter()
+                    .for_each(|(_, argument)| self.collect_expression(argument));
+            }
+            Expression::Prefix { rhs, .. } => self.collect_expression(rhs),
+            Expression::Infix { lhs, rhs, .. } | Expression::Index { lhs, index: rhs } => {
+                self.collect_expression(lhs);
+                self.collect_expression(rhs);
+            }
+            Expression::Match { subject, arms, .. } => {
+                self.collect_expression(subject);
+                for arm in arms {
+                    if let Some(guard) = &arm.guard {
+                        self.collect_expression(guard);
+                    }
+                    self.collect_statements(&arm.body);
+                }
+            }
+            Expression::Try {
+                body,
+                catch,
+                finally,
+            } => {
+                self.collect_statements(body);
+                if let Some(catch) = catch {
+                    self.collect_statements(&catch.body);
+                }
+                if let Some(finally) = finally {
+                    self.collect_statements(finally);
+                }
+            }
+        }
+    }
+}
+
+impl Hook for Coverage {
+    fn before_statement(
+        &mut self,
+        _evaluator: &mut Evaluator,
+        statement: &Statement,
+        _environment: &Rc<RefCell<Environment>>,
+    ) -> Result<(), EvaluationError> {
+        // statements from other sources, such as a debugger's watches, are not counted
+        if let Some(count) = statement
+            .location()
+            .and_then(|location| self.statements.get_mut(&site(location)))
+        {
+            *count += 1;
+        }
+        Ok(())
+    }
+
+    fn branch(&mut self, _evaluator: &mut Evaluator, location: &Location, branch: Branch) {
+        if let Some(counts) = self.branches.get_mut(&site(location)) {
+            counts[branch as usize] += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::{lexer::lexer::Lexer, parser::parser::Parser};
+
+    use super::*;
+
+    const SOURCE: &str = indoc! {"
+        let sign = fn(n) {
+            if (n < 0) {
+                return -1;
+            }
+            if (n == 0) { 0 } else { 1 }
+        };
+        sign(5);
+        sign(7);
+        let unused = fn() { if (true) { 0 } };
+    "};
+
+    fn cover(source: &str) -> Coverage {
+        let program = Parser::new(Lexer::new(source)).parse_program();
+        let coverage = Rc::new(RefCell::new(Coverage::new(&program)));
+
+        let mut evaluator = Evaluator::new();
+        evaluator.set_hook(Box::new(Rc::clone(&coverage)));
+        evaluator.eval(program, Environment::new()).unwrap();
+        drop(evaluator);
+
+        Rc::try_unwrap(coverage).ok().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_counts() {
+        let coverage = cover(SOURCE);
+
+        assert_eq!(
+            coverage.lines().into_iter().collect::<Vec<_>>(),
+            vec![(1, 1), (2, 2), (3, 0), (5, 2), (7, 1), (8, 1), (9, 1)]
+        );
+        assert_eq!(
+            coverage.branches().collect::<Vec<_>>(),
+            vec![(&(2, 5), &[0, 2]), (&(5, 5), &[0, 2]), (&(9, 21), &[0, 0]),]
+        );
+    }
+
+    #[test]
+    fn test_lcov() {
+        let mut lcov = vec![];
+        cover(SOURCE).write_lcov("sign.mk", &mut lcov).unwrap();
+
+        assert_eq!(
+            String::from_utf8(lcov).unwrap(),
+            indoc! {"
+                TN:
+                SF:sign.mk
+                BRDA:2,0,0,0
+                BRDA:2,0,1,2
+                BRDA:5,1,0,0
+                BRDA:5,1,1,2
+                BRDA:9,2,0,-
+                BRDA:9,2,1,-
+                BRF:6
+                BRH:2
+                DA:1,1
+                DA:2,2
+                DA:3,0
+                DA:5,2
+                DA:7,1
+                DA:8,1
+                DA:9,1
+                LF:7
+                LH:6
+                end_of_record
+            "}
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use std::{cell::RefCell, collections::HashMap, fmt, rc::Rc};
 
-use super::object::Object;
+use super::{native::NativeFunction, object::Object};
 
 #[derive(PartialEq, Clone)]
 pub struct Environment {
@@ -36,6 +36,26 @@ impl Environment {
     pub fn set(&mut self, name: &str, val: Object) -> Option<Object> {
         self.store.insert(name.to_string(), val)
     }
+
+    // the bindings of this scope alone, sorted by name
+    pub fn bindings(&self) -> Vec<(String, Object)> {
+        let mut bindings: Vec<_> = self
+            .store
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        bindings.sort_by(|(a, _), (b, _)| a.cmp(b));
+        bindings
+    }
+
+    pub fn outer(&self) -> Option<Rc<RefCell<Environment>>> {
+        self.outer.clone()
+    }
+
+    pub fn register(&mut self, function: NativeFunction) -> Option<Object> {
+        let name = function.name.clone();
+        self.set(&name, Object::NativeFunction(function))
+    }
 }
 
 impl Environment {
@@ -95,6 +115,29 @@ mod tests {
         assert_eq!(env.borrow().get("a"), Some(Object::Integer(1)));
     }
 
+    #[test]
+    fn test_bindings() {
+        let env = Environment::new();
+        let env2 = Environment::with_outer(Rc::clone(&env));
+        env.borrow_mut().set("a", Object::Integer(1));
+        env2.borrow_mut().set("c", Object::Integer(3));
+        env2.borrow_mut().set("b", Object::Integer(2));
+
+        assert_eq!(
+            env2.borrow().bindings(),
+            vec![
+                ("b".to_string(), Object::Integer(2)),
+                ("c".to_string(), Object::Integer(3)),
+            ]
+        );
+        let outer = env2.borrow().outer().unwrap();
+        assert_eq!(
+            outer.borrow().bindings(),
+            vec![("a".to_string(), Object::Integer(1))]
+        );
+        assert!(outer.borrow().outer().is_none());
+    }
+
     #[test]
     fn test_env_outer() {
         let env = Environment::new();
@@ -0,0 +1,293 @@
+use std::fmt::Display;
+
+use crate::{
+    lexer::location::Location,
+    parser::ast::operator::{InfixOperator, PrefixOperator},
+};
+
+use super::{convert::ConversionError, object::Object};
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum EvaluationErrorKind {
+    TypeMismatch {
+        op: InfixOperator,
+        lhs: Object,
+        rhs: Object,
+    },
+    InvalidOperand {
+        op: PrefixOperator,
+        operand: Object,
+    },
+    UnboundIdentifier(String),
+    ArityMismatch {
+        min: usize,
+        max: Option<usize>,
+        got: usize,
+    },
+    MissingArgument(String),
+    UnexpectedNamedArgument(String),
+    DuplicateArgument(String),
+    DivisionByZero,
+    NotCallable(Object),
+    Overflow(String),
+    UnusableHashKey(Object),
+    IndexNotSupported {
+        lhs: Object,
+        index: Object,
+    },
+    PatternMismatch(String),
+    NoMatchingArm(Object),
+    StackOverflow(usize),
+    OutOfFuel(u64),
+    DeadlineExceeded,
+    AllocationLimitExceeded(usize),
+    CollectionTooLarge {
+        limit: usize,
+        size: usize,
+    },
+    Interrupted,
+    Conversion(ConversionError),
+    Thrown {
+        kind: String,
+        message: String,
+    },
+}
+
+impl EvaluationErrorKind {
+    pub fn name(&self) -> &str {
+        match self {
+            EvaluationErrorKind::TypeMismatch { .. } => "TypeMismatch",
+            EvaluationErrorKind::InvalidOperand { .. } => "InvalidOperand",
+            EvaluationErrorKind::UnboundIdentifier(_) => "UnboundIdentifier",
+            EvaluationErrorKind::ArityMismatch { .. } => "ArityMismatch",
+            EvaluationErrorKind::MissingArgument(_) => "MissingArgument",
+            EvaluationErrorKind::UnexpectedNamedArgument(_) => "UnexpectedNamedArgument",
+            EvaluationErrorKind::DuplicateArgument(_) => "DuplicateArgument",
+            EvaluationErrorKind::DivisionByZero => "DivisionByZero",
+            EvaluationErrorKind::NotCallable(_) => "NotCallable",
+            EvaluationErrorKind::Overflow(_) => "Overflow",
+            EvaluationErrorKind::UnusableHashKey(_) => "UnusableHashKey",
+            EvaluationErrorKind::IndexNotSupported { .. } => "IndexNotSupported",
+            EvaluationErrorKind::PatternMismatch(_) => "PatternMismatch",
+            EvaluationErrorKind::NoMatchingArm(_) => "NoMatchingArm",
+            EvaluationErrorKind::StackOverflow(_) => "StackOverflow",
+            EvaluationErrorKind::OutOfFuel(_) => "OutOfFuel",
+            EvaluationErrorKind::DeadlineExceeded => "DeadlineExceeded",
+            EvaluationErrorKind::AllocationLimitExceeded(_) => "AllocationLimitExceeded",
+            EvaluationErrorKind::CollectionTooLarge { .. } => "CollectionTooLarge",
+            EvaluationErrorKind::Interrupted => "Interrupted",
+            EvaluationErrorKind::Conversion(_) => "ConversionError",
+            EvaluationErrorKind::Thrown { kind, .. } => kind,
+        }
+    }
+
+    pub fn is_limit(&self) -> bool {
+        matches!(
+            self,
+            EvaluationErrorKind::OutOfFuel(_)
+                | EvaluationErrorKind::DeadlineExceeded
+                | EvaluationErrorKind::AllocationLimitExceeded(_)
+                | EvaluationErrorKind::CollectionTooLarge { .. }
+                | EvaluationErrorKind::Interrupted
+        )
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            EvaluationErrorKind::Thrown { message, .. } => message.clone(),
+            kind => kind.to_string(),
+        }
+    }
+
+    fn describe_arity(min: usize, max: Option<usize>) -> String {
+        match max {
+            None => format!("at least {}", min),
+            Some(max) if max == min => format!("{}", min),
+            Some(max) => format!("{} to {}", min, max),
+        }
+    }
+}
+
+impl Display for EvaluationErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvaluationErrorKind::TypeMismatch { op, lhs, rhs } => {
+                write!(f, "invalid operation: {} {} {}", lhs, op, rhs)
+            }
+            EvaluationErrorKind::InvalidOperand { op, operand } => {
+                write!(f, "invalid operation: {}{}", op, operand)
+            }
+            EvaluationErrorKind::UnboundIdentifier(identifier) => {
+                write!(f, "identifier not found: {}", identifier)
+            }
+            EvaluationErrorKind::ArityMismatch { min, max, got } => write!(
+                f,
+                "wrong number of arguments: got {}, but function wants {}",
+                got,
+                EvaluationErrorKind::describe_arity(*min, *max)
+            ),
+            EvaluationErrorKind::MissingArgument(parameter) => {
+                write!(f, "missing argument for parameter {}", parameter)
+            }
+            EvaluationErrorKind::UnexpectedNamedArgument(name) => {
+                write!(f, "unexpected named argument: {}", name)
+            }
+            EvaluationErrorKind::DuplicateArgument(parameter) => {
+                write!(f, "argument {} given more than once", parameter)
+            }
+            EvaluationErrorKind::DivisionByZero => write!(f, "cannot divide by zero"),
+            EvaluationErrorKind::NotCallable(object) => write!(f, "not a function: {}", object),
+            EvaluationErrorKind::Overflow(operation) => {
+                write!(f, "integer overflow: {}", operation)
+            }
+            EvaluationErrorKind::UnusableHashKey(key) => {
+                write!(f, "unusable as hash key: {}", key)
+            }
+            EvaluationErrorKind::IndexNotSupported { lhs, index } => {
+                write!(f, "index operator not supported: {}[{}]", lhs, index)
+            }
+            EvaluationErrorKind::PatternMismatch(reason) => write!(f, "{}", reason),
+            EvaluationErrorKind::NoMatchingArm(value) => {
+                write!(f, "no match arm matched value: {}", value)
+            }
+            EvaluationErrorKind::StackOverflow(limit) => {
+                write!(
+                    f,
+                    "stack overflow: maximum call depth of {} exceeded",
+                    limit
+                )
+            }
+            EvaluationErrorKind::OutOfFuel(limit) => {
+                write!(f, "evaluation step limit of {} exceeded", limit)
+            }
+            EvaluationErrorKind::DeadlineExceeded => write!(f, "evaluation deadline exceeded"),
+            EvaluationErrorKind::AllocationLimitExceeded(limit) => {
+                write!(f, "allocation limit of {} objects exceeded", limit)
+            }
+            EvaluationErrorKind::CollectionTooLarge { limit, size } => write!(
+                f,
+                "collection of size {} exceeds the limit of {}",
+                size, limit
+            ),
+            EvaluationErrorKind::Interrupted => write!(f, "interrupted"),
+            EvaluationErrorKind::Conversion(error) => write!(f, "{}", error),
+            EvaluationErrorKind::Thrown { kind, message } => {
+                write!(f, "uncaught {}: {}", kind, message)
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct EvaluationError {
+    pub kind: Box<EvaluationErrorKind>,
+    pub location: Option<Location>,
+}
+
+impl EvaluationError {
+    pub fn new(kind: EvaluationErrorKind) -> Self {
+        EvaluationError {
+            kind: Box::new(kind),
+            location: None,
+        }
+    }
+
+    pub fn with_location(kind: EvaluationErrorKind, location: &Location) -> Self {
+        EvaluationError {
+            kind: Box::new(kind),
+            location: Some(location.clone()),
+        }
+    }
+
+    pub fn thrown(value: Object, location: &Location) -> Self {
+        match value {
+            Object::Error {
+                kind,
+                message,
+                location,
+            } => EvaluationError {
+                kind: Box::new(EvaluationErrorKind::Thrown { kind, message }),
+                location,
+            },
+            value => EvaluationError::with_location(
+                EvaluationErrorKind::Thrown {
+                    kind: "Error".to_string(),
+                    message: value.to_string(),
+                },
+                location,
+            ),
+        }
+    }
+
+    // keeps a location the error already has, which is the more precise one; running
+    // out of a budget is not the fault of any one node, so limits stay unlocated
+    pub fn or_location(mut self, location: Option<Location>) -> Self {
+        if self.location.is_none() && !self.kind.is_limit() {
+            self.location = location;
+        }
+        self
+    }
+
+    pub fn into_object(self) -> Object {
+        Object::Error {
+            kind: self.kind.name().to_string(),
+            message: self.kind.message(),
+            location: self.location,
+        }
+    }
+}
+
+impl From<ConversionError> for EvaluationError {
+    fn from(error: ConversionError) -> Self {
+        EvaluationError::new(EvaluationErrorKind::Conversion(error))
+    }
+}
+
+impl Display for EvaluationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.location {
+            Some(location) => write!(f, "{} {}", self.kind, location),
+            None => write!(f, "{}", self.kind),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let error = EvaluationError::new(EvaluationErrorKind::ArityMismatch {
+            min: 1,
+            max: Some(2),
+            got: 3,
+        });
+        assert_eq!(
+            error.to_string(),
+            "wrong number of arguments: got 3, but function wants 1 to 2"
+        );
+
+        let error = EvaluationError::with_location(
+            EvaluationErrorKind::Thrown {
+                kind: "Error".to_string(),
+                message: "boom".to_string(),
+            },
+            &Location::new(2, 3),
+        );
+        assert_eq!(error.to_string(), "uncaught Error: boom @2:3");
+    }
+
+    #[test]
+    fn test_into_object() {
+        let error = EvaluationError::new(EvaluationErrorKind::DivisionByZero);
+        assert_eq!(
+            error.into_object(),
+            Object::Error {
+                kind: "DivisionByZero".to_string(),
+                message: "cannot divide by zero".to_string(),
+                location: None,
+            }
+        );
+    }
+}
@@ -1,37 +1,102 @@
-use std::{cell::RefCell, fmt::Display, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    io::Write,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
 
-use crate::parser::ast::{
-    expression::Expression,
-    node::Node,
-    operator::{InfixOperator, PrefixOperator},
-    statement::Statement,
+use crate::{
+    lexer::location::Location,
+    parser::ast::{
+        expression::{CatchClause, Expression, MatchArm, Parameter},
+        node::Node,
+        operator::{InfixOperator, PrefixOperator},
+        pattern::Pattern,
+        position::Position,
+        statement::Statement,
+    },
 };
 
-use super::{environment::Environment, object::Object};
+use super::{
+    environment::Environment,
+    error::{EvaluationError, EvaluationErrorKind},
+    hook::{Branch, Hook},
+    limits::{EvalLimits, EvalUsage},
+    object::{HashKey, Object},
+    trace::{TraceFormat, Tracer},
+};
 
-#[derive(Debug)]
-pub struct EvaluationError {
-    #[allow(dead_code)]
-    msg: String,
-}
+// the clock is only read every DEADLINE_CHECK_INTERVAL steps
+const DEADLINE_CHECK_INTERVAL: u64 = 1024;
+// evaluation recurses on the Rust stack, which is grown on the heap once less than
+// STACK_RED_ZONE is left so that deep programs hit max_call_depth instead of a SIGSEGV
+const STACK_RED_ZONE: usize = 128 * 1024;
+const STACK_GROWTH: usize = 4 * 1024 * 1024;
 
-impl EvaluationError {
-    pub fn new(msg: impl Into<String>) -> Self {
-        EvaluationError { msg: msg.into() }
-    }
+pub struct Evaluator {
+    call_depth: usize,
+    limits: EvalLimits,
+    usage: EvalUsage,
+    interrupted: Arc<AtomicBool>,
+    hook: Option<Box<dyn Hook>>,
 }
 
-impl Display for EvaluationError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.msg)
+impl Default for Evaluator {
+    fn default() -> Self {
+        Evaluator::new()
     }
 }
 
-pub struct Evaluator {}
-
 impl Evaluator {
     pub fn new() -> Self {
-        Evaluator {}
+        Evaluator::with_limits(EvalLimits::default())
+    }
+
+    pub fn with_limits(limits: EvalLimits) -> Self {
+        Evaluator {
+            call_depth: 0,
+            limits,
+            usage: EvalUsage::default(),
+            interrupted: Arc::new(AtomicBool::new(false)),
+            hook: None,
+        }
+    }
+
+    pub fn set_interrupt_handle(&mut self, interrupted: Arc<AtomicBool>) {
+        self.interrupted = interrupted;
+    }
+
+    // what evaluation has used since the evaluator was created or reset_usage was
+    // called; the limits apply to the same totals
+    pub fn usage(&self) -> &EvalUsage {
+        &self.usage
+    }
+
+    pub fn reset_usage(&mut self) {
+        self.usage = EvalUsage::default();
+    }
+
+    pub fn set_hook(&mut self, hook: Box<dyn Hook>) {
+        self.hook = Some(hook);
+    }
+
+    pub fn take_hook(&mut self) -> Option<Box<dyn Hook>> {
+        self.hook.take()
+    }
+
+    // logs the evaluation to output; this takes the place of any other hook
+    pub fn set_trace(&mut self, output: impl Write + 'static, format: TraceFormat) {
+        self.set_hook(Box::new(Tracer::new(output, format)));
+    }
+
+    // the number of Monkey function calls currently running, 0 at the top level
+    pub fn call_depth(&self) -> usize {
+        self.call_depth
     }
 
     pub fn eval(
@@ -40,11 +105,116 @@ impl Evaluator {
         environment: Rc<RefCell<Environment>>,
     ) -> Result<Object, EvaluationError> {
         let node = node.into();
-        match node {
+        self.consume_step()?;
+
+        // errors raised while evaluating the node point at it, unless an inner node
+        // already located them
+        let location = match &node {
+            Node::Expression(expression) => expression.location().cloned(),
+            Node::Statement(statement) => statement.location().cloned(),
+            Node::Program(_) => None,
+        };
+
+        // whole programs are not reported to the hook, only the nodes inside them
+        let reported = !matches!(node, Node::Program(_))
+            && self
+                .with_hook(|hook, evaluator| hook.before_node(evaluator, &node))
+                .is_some();
+
+        let result = stacker::maybe_grow(STACK_RED_ZONE, STACK_GROWTH, || match node {
             Node::Expression(expression) => self.eval_expression(expression, environment),
-            Node::Statement(statement) => self.eval_statement(statement, environment),
-            Node::Program(program) => self.eval_statements(program.statements, environment),
+            Node::Statement(statement) => {
+                self.before_statement(&statement, &environment)?;
+                self.eval_statement(statement, environment)
+            }
+            Node::Program(program) => {
+                let result = self.eval_statements(program.statements, environment);
+                self.resolve_tail_call(result)
+            }
+        })
+        .map_err(|err| err.or_location(location));
+
+        if reported {
+            self.with_hook(|hook, evaluator| hook.after_node(evaluator, &result));
+        }
+
+        result
+    }
+
+    fn consume_step(&mut self) -> Result<(), EvaluationError> {
+        self.usage.steps += 1;
+
+        if let Some(max_steps) = self.limits.max_steps {
+            if self.usage.steps > max_steps {
+                return Err(EvaluationError::new(EvaluationErrorKind::OutOfFuel(
+                    max_steps,
+                )));
+            }
+        }
+
+        if let Some(deadline) = self.limits.deadline {
+            if self.usage.steps % DEADLINE_CHECK_INTERVAL == 1 && Instant::now() >= deadline {
+                return Err(EvaluationError::new(EvaluationErrorKind::DeadlineExceeded));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn before_statement(
+        &mut self,
+        statement: &Statement,
+        environment: &Rc<RefCell<Environment>>,
+    ) -> Result<(), EvaluationError> {
+        if matches!(statement, Statement::Block(_)) {
+            return Ok(());
+        }
+
+        self.with_hook(|hook, evaluator| hook.before_statement(evaluator, statement, environment))
+            .unwrap_or(Ok(()))
+    }
+
+    // runs the hook, if any, detached from the evaluator so that it can evaluate code
+    fn with_hook<T>(&mut self, run: impl FnOnce(&mut dyn Hook, &mut Self) -> T) -> Option<T> {
+        let mut hook = self.hook.take()?;
+        let result = run(hook.as_mut(), self);
+        self.hook.get_or_insert(hook);
+
+        Some(result)
+    }
+
+    fn check_interrupt(&self) -> Result<(), EvaluationError> {
+        // the flag is cleared so that the next evaluation starts uninterrupted
+        if self.interrupted.swap(false, Ordering::Relaxed) {
+            return Err(EvaluationError::new(EvaluationErrorKind::Interrupted));
         }
+
+        Ok(())
+    }
+
+    fn allocate(&mut self, size: usize) -> Result<(), EvaluationError> {
+        self.usage.allocations += 1;
+
+        if let Some(max_allocations) = self.limits.max_allocations {
+            if self.usage.allocations > max_allocations {
+                return Err(EvaluationError::new(
+                    EvaluationErrorKind::AllocationLimitExceeded(max_allocations),
+                ));
+            }
+        }
+
+        if let Some(max_collection_size) = self.limits.max_collection_size {
+            if size > max_collection_size {
+                return Err(EvaluationError::new(
+                    EvaluationErrorKind::CollectionTooLarge {
+                        limit: max_collection_size,
+                        size,
+                    },
+                ));
+            }
+        }
+
+        Ok(())
     }
 
     fn eval_statements(
@@ -73,25 +243,45 @@ impl Evaluator {
         environment: Rc<RefCell<Environment>>,
     ) -> Result<Object, EvaluationError> {
         match statement {
-            Statement::Let { name, value } => self.eval_let_statement(name, value, environment),
-            Statement::Return { value } => {
+            Statement::Let { pattern, value, .. } => {
+                self.eval_let_statement(pattern, value, environment)
+            }
+            // only eval_tail_statement returns through a tail call, because the value
+            // of a `return` outside tail position can still be used by the code around it
+            Statement::Return { value, .. } => {
                 let value = self.eval(value, environment)?;
                 Ok(Object::return_value(value))
             }
-            Statement::Expression(expression) => self.eval(expression, environment),
+            Statement::Throw { value, position } => {
+                let value = self.eval(value, environment)?;
+                Err(EvaluationError::thrown(value, &position.0))
+            }
+            Statement::Expression(expression, _) => self.eval(expression, environment),
             Statement::Block(statements) => self.eval_statements(statements, environment),
         }
     }
 
     fn eval_let_statement(
         &mut self,
-        name: String,
+        pattern: Pattern,
         value: Expression,
         environment: Rc<RefCell<Environment>>,
     ) -> Result<Object, EvaluationError> {
-        let value = self.eval(value, Rc::clone(&environment))?;
+        let mut value = self.eval(value, Rc::clone(&environment))?;
 
-        environment.borrow_mut().set(&name, value.clone());
+        // an anonymous function is named after the first binding it gets, so that
+        // stack frames and traces can tell functions apart
+        if let (
+            Pattern::Identifier(binding, _),
+            Object::Function {
+                name: name @ None, ..
+            },
+        ) = (&pattern, &mut value)
+        {
+            *name = Some(binding.clone());
+        }
+
+        self.destructure(&pattern, &value, &environment)?;
 
         Ok(value)
     }
@@ -104,75 +294,726 @@ impl Evaluator {
         match expression {
             Expression::Int(int) => Ok(Object::Integer(int)),
             Expression::Bool(boolean) => Ok(Object::Boolean(boolean)),
-            Expression::Identifier(identifier) => self.eval_identifier(identifier, environment),
+            Expression::Str(string) => {
+                self.allocate(string.len())?;
+                Ok(Object::String(string))
+            }
+            Expression::Identifier(identifier, _) => self.eval_identifier(identifier, environment),
+            Expression::Array(elements) => self.eval_array_literal(elements, environment),
+            Expression::Hash(entries) => self.eval_hash_literal(entries, environment),
             Expression::If {
                 condition,
                 consequence,
                 alternative,
-            } => self.eval_if_expression(*condition, consequence, alternative, environment),
-            Expression::Function { parameters, body } => {
-                self.eval_function(parameters, body, environment)
+                position,
+            } => {
+                self.eval_if_expression(*condition, consequence, alternative, position, environment)
             }
+            Expression::Function {
+                parameters,
+                rest,
+                body,
+                position,
+            } => self.eval_function(parameters, rest, body, position, environment),
             Expression::Call {
                 function,
                 arguments,
-            } => self.eval_call(*function, arguments, environment),
+                named_arguments,
+            } => self.eval_call(*function, arguments, named_arguments, environment),
             Expression::Prefix { operator, rhs } => {
                 self.eval_prefix_expression(operator, *rhs, environment)
             }
             Expression::Infix { rhs, operator, lhs } => {
                 self.eval_infix_expression(operator, *lhs, *rhs, environment)
             }
+            Expression::Match {
+                subject,
+                arms,
+                position,
+            } => self.eval_match_expression(*subject, arms, position.0, environment),
+            Expression::Index { lhs, index } => {
+                self.eval_index_expression(*lhs, *index, environment)
+            }
+            Expression::Try {
+                body,
+                catch,
+                finally,
+            } => self.eval_try_expression(body, catch, finally, environment),
             Expression::Null => Ok(Object::Null),
         }
     }
 
+    fn eval_array_literal(
+        &mut self,
+        elements: Vec<Expression>,
+        environment: Rc<RefCell<Environment>>,
+    ) -> Result<Object, EvaluationError> {
+        let mut result = vec![];
+
+        for element in elements {
+            result.push(self.eval(element, Rc::clone(&environment))?);
+        }
+
+        self.allocate(result.len())?;
+
+        Ok(Object::Array(result))
+    }
+
+    fn eval_hash_literal(
+        &mut self,
+        entries: Vec<(Expression, Expression)>,
+        environment: Rc<RefCell<Environment>>,
+    ) -> Result<Object, EvaluationError> {
+        let mut result = BTreeMap::new();
+
+        for (key, value) in entries {
+            let key = self.eval(key, Rc::clone(&environment))?;
+            let Some(hash_key) = key.hash_key() else {
+                return Err(EvaluationError::new(EvaluationErrorKind::UnusableHashKey(
+                    key,
+                )));
+            };
+
+            let value = self.eval(value, Rc::clone(&environment))?;
+
+            result.insert(hash_key, value);
+        }
+
+        self.allocate(result.len())?;
+
+        Ok(Object::Hash(result))
+    }
+
+    fn eval_index_expression(
+        &mut self,
+        lhs: Expression,
+        index: Expression,
+        environment: Rc<RefCell<Environment>>,
+    ) -> Result<Object, EvaluationError> {
+        let lhs = self.eval(lhs, Rc::clone(&environment))?;
+        let index = self.eval(index, environment)?;
+
+        match (&lhs, &index) {
+            (Object::Array(elements), Object::Integer(i)) => Ok(usize::try_from(*i)
+                .ok()
+                .and_then(|i| elements.get(i))
+                .cloned()
+                .unwrap_or(Object::Null)),
+            (Object::Hash(entries), index) => match index.hash_key() {
+                Some(key) => Ok(entries.get(&key).cloned().unwrap_or(Object::Null)),
+                None => Err(EvaluationError::new(EvaluationErrorKind::UnusableHashKey(
+                    index.clone(),
+                ))),
+            },
+            (Object::Error { .. }, Object::String(field)) => Ok(self
+                .error_fields(&lhs)
+                .remove(&HashKey::String(field.clone()))
+                .unwrap_or(Object::Null)),
+            _ => Err(EvaluationError::new(
+                EvaluationErrorKind::IndexNotSupported { lhs, index },
+            )),
+        }
+    }
+
+    fn error_fields(&self, error: &Object) -> BTreeMap<HashKey, Object> {
+        let Object::Error {
+            kind,
+            message,
+            location,
+        } = error
+        else {
+            return BTreeMap::new();
+        };
+
+        let location = match location {
+            Some(location) => Object::Hash(BTreeMap::from([
+                (
+                    HashKey::String("line".to_string()),
+                    Object::Integer(location.line() as i64),
+                ),
+                (
+                    HashKey::String("column".to_string()),
+                    Object::Integer(location.column() as i64),
+                ),
+            ])),
+            None => Object::Null,
+        };
+
+        BTreeMap::from([
+            (HashKey::String("kind".to_string()), Object::string(kind)),
+            (
+                HashKey::String("message".to_string()),
+                Object::string(message),
+            ),
+            (HashKey::String("location".to_string()), location),
+        ])
+    }
+
+    fn eval_try_expression(
+        &mut self,
+        body: Vec<Statement>,
+        catch: Option<CatchClause>,
+        finally: Option<Vec<Statement>>,
+        environment: Rc<RefCell<Environment>>,
+    ) -> Result<Object, EvaluationError> {
+        // tail calls are resolved here so that the call still runs under this try
+        let result = self.eval_statements(body, Rc::clone(&environment));
+        let result = match (self.resolve_tail_call(result), catch) {
+            // running out of a resource budget aborts the whole evaluation
+            (Err(err), _) if err.kind.is_limit() => return Err(err),
+            (Err(err), Some(catch)) => {
+                let catch_env = Environment::with_outer(Rc::clone(&environment));
+                // a catch pattern that does not match still runs the finally block
+                match self.destructure(&catch.pattern, &err.into_object(), &catch_env) {
+                    Ok(()) => {
+                        let result = self.eval_statements(catch.body, catch_env);
+                        self.resolve_tail_call(result)
+                    }
+                    Err(err) => Err(err),
+                }
+            }
+            (result, _) => result,
+        };
+
+        if let Some(finally) = finally {
+            // a return from the finally block takes precedence over the try/catch outcome
+            if let finally @ Object::ReturnValue(_) = self.eval_statements(finally, environment)? {
+                return Ok(finally);
+            }
+        }
+
+        result
+    }
+
+    fn eval_match_expression(
+        &mut self,
+        subject: Expression,
+        arms: Vec<MatchArm>,
+        location: Location,
+        environment: Rc<RefCell<Environment>>,
+    ) -> Result<Object, EvaluationError> {
+        let (body, arm_env) = self.select_match_arm(subject, arms, location, environment)?;
+        self.eval_statements(body, arm_env)
+    }
+
+    // the body of the first arm that matches the subject, with the bindings it made
+    fn select_match_arm(
+        &mut self,
+        subject: Expression,
+        arms: Vec<MatchArm>,
+        location: Location,
+        environment: Rc<RefCell<Environment>>,
+    ) -> Result<(Vec<Statement>, Rc<RefCell<Environment>>), EvaluationError> {
+        let subject = self.eval(subject, Rc::clone(&environment))?;
+
+        for arm in arms {
+            let arm_env = Environment::with_outer(Rc::clone(&environment));
+
+            match self.destructure(&arm.pattern, &subject, &arm_env) {
+                // running out of a budget is not a reason to try the next arm
+                Err(err) if err.kind.is_limit() => return Err(err),
+                Err(_) => continue,
+                Ok(()) => {}
+            }
+
+            if let Some(guard) = arm.guard {
+                let guard = self.eval(guard, Rc::clone(&arm_env))?;
+                if !self.is_truthy(guard) {
+                    continue;
+                }
+            }
+
+            return Ok((arm.body, arm_env));
+        }
+
+        Err(EvaluationError::with_location(
+            EvaluationErrorKind::NoMatchingArm(subject),
+            &location,
+        ))
+    }
+
+    fn destructure(
+        &mut self,
+        pattern: &Pattern,
+        value: &Object,
+        environment: &Rc<RefCell<Environment>>,
+    ) -> Result<(), EvaluationError> {
+        match (pattern, value) {
+            (Pattern::Wildcard, _) => Ok(()),
+            (Pattern::Identifier(identifier, _), value) => {
+                environment.borrow_mut().set(identifier, value.clone());
+                Ok(())
+            }
+            (Pattern::Int(expected), Object::Integer(actual)) if expected == actual => Ok(()),
+            (Pattern::Bool(expected), Object::Boolean(actual)) if expected == actual => Ok(()),
+            (Pattern::Str(expected), Object::String(actual)) if expected == actual => Ok(()),
+            (Pattern::Null, Object::Null) => Ok(()),
+            (Pattern::Array { elements, rest }, Object::Array(values)) => {
+                match rest {
+                    Some(_) if values.len() < elements.len() => {
+                        return Err(self.pattern_mismatch(format!(
+                            "cannot destructure {}: expected at least {} elements, got {}",
+                            value,
+                            elements.len(),
+                            values.len()
+                        )))
+                    }
+                    None if values.len() != elements.len() => {
+                        return Err(self.pattern_mismatch(format!(
+                            "cannot destructure {}: expected {} elements, got {}",
+                            value,
+                            elements.len(),
+                            values.len()
+                        )))
+                    }
+                    _ => {}
+                }
+
+                for (element, value) in elements.iter().zip(values) {
+                    self.destructure(element, value, environment)?;
+                }
+
+                match rest {
+                    Some(rest) => {
+                        let rest_values = values[elements.len()..].to_vec();
+                        self.allocate(rest_values.len())?;
+                        self.destructure(rest, &Object::Array(rest_values), environment)
+                    }
+                    None => Ok(()),
+                }
+            }
+            (Pattern::Hash(_), Object::Error { .. }) => self.destructure(
+                pattern,
+                &Object::Hash(self.error_fields(value)),
+                environment,
+            ),
+            (Pattern::Hash(entries), Object::Hash(values)) => {
+                for (key, pattern) in entries {
+                    let Some(value) = values.get(&HashKey::String(key.clone())) else {
+                        return Err(self.pattern_mismatch(format!(
+                            "cannot destructure {}: missing key \"{}\"",
+                            value, key
+                        )));
+                    };
+
+                    self.destructure(pattern, value, environment)?;
+                }
+
+                Ok(())
+            }
+            (Pattern::Array { .. }, value) => {
+                Err(self.pattern_mismatch(format!("cannot destructure {} as an array", value)))
+            }
+            (Pattern::Hash(_), value) => {
+                Err(self.pattern_mismatch(format!("cannot destructure {} as a hash", value)))
+            }
+            (pattern, value) => {
+                Err(self.pattern_mismatch(format!("pattern {} does not match {}", pattern, value)))
+            }
+        }
+    }
+
+    fn pattern_mismatch(&self, reason: String) -> EvaluationError {
+        EvaluationError::new(EvaluationErrorKind::PatternMismatch(reason))
+    }
+
     fn eval_call(
         &mut self,
         function: Expression,
         arguments: Vec<Expression>,
+        named_arguments: Vec<(String, Expression)>,
+        environment: Rc<RefCell<Environment>>,
+    ) -> Result<Object, EvaluationError> {
+        match self.eval_call_operands(function, arguments, named_arguments, environment)? {
+            Object::TailCall {
+                function,
+                arguments,
+                named_arguments,
+                ..
+            } => self.apply_function(*function, arguments, named_arguments),
+            _ => unreachable!("call operands always evaluate to a tail call"),
+        }
+    }
+
+    fn eval_call_operands(
+        &mut self,
+        function: Expression,
+        arguments: Vec<Expression>,
+        named_arguments: Vec<(String, Expression)>,
         environment: Rc<RefCell<Environment>>,
     ) -> Result<Object, EvaluationError> {
         let function = self.eval(function, Rc::clone(&environment))?;
 
-        let Object::Function { parameters, environment: func_environment, body } = function else {
-            return Err(EvaluationError::new(format!("not a function: {}", function)));
-        };
+        let mut evaluated_arguments = vec![];
+        for argument in arguments {
+            evaluated_arguments.push(self.eval(argument, Rc::clone(&environment))?);
+        }
+
+        let mut evaluated_named_arguments = vec![];
+        for (name, argument) in named_arguments {
+            let argument = self.eval(argument, Rc::clone(&environment))?;
+            evaluated_named_arguments.push((name, argument));
+        }
 
-        if parameters.len() != arguments.len() {
-            return Err(EvaluationError::new(format!(
-                "wrong number of arguments: got {}, but function wants {}",
-                arguments.len(),
-                parameters.len()
+        Ok(Object::TailCall {
+            function: Box::new(function),
+            arguments: evaluated_arguments,
+            named_arguments: evaluated_named_arguments,
+            location: None,
+        })
+    }
+
+    pub fn call(
+        &mut self,
+        function: &Object,
+        arguments: Vec<Object>,
+    ) -> Result<Object, EvaluationError> {
+        self.apply_function(function.clone(), arguments, vec![])
+    }
+
+    fn apply_function(
+        &mut self,
+        function: Object,
+        arguments: Vec<Object>,
+        named_arguments: Vec<(String, Object)>,
+    ) -> Result<Object, EvaluationError> {
+        if self.call_depth >= self.limits.max_call_depth {
+            return Err(EvaluationError::new(EvaluationErrorKind::StackOverflow(
+                self.limits.max_call_depth,
             )));
         }
 
-        let local_env = Environment::with_outer(Rc::clone(&func_environment));
+        self.call_depth += 1;
+        self.usage.peak_call_depth = self.usage.peak_call_depth.max(self.call_depth);
+        let result = self.trampoline(function, arguments, named_arguments);
+        self.call_depth -= 1;
 
-        for (parameter, argument) in parameters.iter().zip(arguments) {
-            let argument = self.eval(argument, Rc::clone(&environment))?;
+        result
+    }
+
+    fn trampoline(
+        &mut self,
+        mut function: Object,
+        mut arguments: Vec<Object>,
+        mut named_arguments: Vec<(String, Object)>,
+    ) -> Result<Object, EvaluationError> {
+        // the first call is located by the caller, which is still running
+        let mut call_site = None;
+
+        // calls in tail position come back as TailCall and reuse this loop instead of the stack
+        loop {
+            self.check_interrupt()?;
+
+            self.with_hook(|hook, evaluator| hook.enter_function(evaluator, &function, &arguments));
+            let result = self
+                .eval_function_body(function, arguments, named_arguments)
+                .map_err(|err| err.or_location(call_site.take()));
+            self.with_hook(|hook, evaluator| hook.exit_function(evaluator, &result));
+
+            match result? {
+                Object::TailCall {
+                    function: next_function,
+                    arguments: next_arguments,
+                    named_arguments: next_named_arguments,
+                    location,
+                } => {
+                    function = *next_function;
+                    arguments = next_arguments;
+                    named_arguments = next_named_arguments;
+                    call_site = location;
+                }
+                value => return Ok(value),
+            }
+        }
+    }
+
+    fn eval_function_body(
+        &mut self,
+        function: Object,
+        arguments: Vec<Object>,
+        named_arguments: Vec<(String, Object)>,
+    ) -> Result<Object, EvaluationError> {
+        let (parameters, rest, environment, body) = match function {
+            Object::Function {
+                parameters,
+                rest,
+                environment,
+                body,
+                ..
+            } => (parameters, rest, environment, body),
+            Object::NativeFunction(native) => {
+                if let Some((name, _)) = named_arguments.into_iter().next() {
+                    return Err(EvaluationError::new(
+                        EvaluationErrorKind::UnexpectedNamedArgument(name),
+                    ));
+                }
 
-            local_env.borrow_mut().set(parameter, argument);
+                return native.call(self, &arguments);
+            }
+            function => {
+                return Err(EvaluationError::new(EvaluationErrorKind::NotCallable(
+                    function,
+                )))
+            }
+        };
+
+        let local_env =
+            self.bind_arguments(&parameters, &rest, environment, arguments, named_arguments)?;
+
+        match self.eval_tail_statement(Statement::Block(body), local_env)? {
+            Object::ReturnValue(value) => Ok(*value),
+            value => Ok(value),
+        }
+    }
+
+    fn bind_arguments(
+        &mut self,
+        parameters: &[Parameter],
+        rest: &Option<String>,
+        environment: Rc<RefCell<Environment>>,
+        arguments: Vec<Object>,
+        mut named_arguments: Vec<(String, Object)>,
+    ) -> Result<Rc<RefCell<Environment>>, EvaluationError> {
+        if let Some((name, _)) = named_arguments
+            .iter()
+            .find(|(name, _)| !parameters.iter().any(|p| p.name() == Some(name.as_str())))
+        {
+            return Err(EvaluationError::new(
+                EvaluationErrorKind::UnexpectedNamedArgument(name.clone()),
+            ));
+        }
+
+        let given = arguments.len() + named_arguments.len();
+        let required = parameters.iter().filter(|p| p.default.is_none()).count();
+
+        if given < required || (rest.is_none() && arguments.len() > parameters.len()) {
+            return Err(EvaluationError::new(EvaluationErrorKind::ArityMismatch {
+                min: required,
+                max: rest.as_ref().map_or(Some(parameters.len()), |_| None),
+                got: given,
+            }));
+        }
+
+        let local_env = Environment::with_outer(environment);
+
+        for (index, parameter) in parameters.iter().enumerate() {
+            let named_argument = named_arguments
+                .iter()
+                .position(|(name, _)| parameter.name() == Some(name.as_str()))
+                .map(|position| named_arguments.swap_remove(position).1);
+
+            let argument = match (arguments.get(index), named_argument) {
+                (Some(_), Some(_)) => {
+                    return Err(EvaluationError::new(
+                        EvaluationErrorKind::DuplicateArgument(parameter.pattern.to_string()),
+                    ))
+                }
+                (Some(argument), None) => argument.clone(),
+                (None, Some(argument)) => argument,
+                (None, None) => match &parameter.default {
+                    Some(default) => self.eval(default.clone(), Rc::clone(&local_env))?,
+                    None => {
+                        return Err(EvaluationError::new(EvaluationErrorKind::MissingArgument(
+                            parameter.pattern.to_string(),
+                        )))
+                    }
+                },
+            };
+
+            self.destructure(&parameter.pattern, &argument, &local_env)?;
+        }
+
+        if let Some(rest) = rest {
+            let rest_arguments = arguments
+                .get(parameters.len()..)
+                .map(|arguments| arguments.to_vec())
+                .unwrap_or_default();
+
+            self.allocate(rest_arguments.len())?;
+
+            local_env
+                .borrow_mut()
+                .set(rest, Object::Array(rest_arguments));
+        }
+
+        Ok(local_env)
+    }
+
+    fn eval_tail_statement(
+        &mut self,
+        statement: Statement,
+        environment: Rc<RefCell<Environment>>,
+    ) -> Result<Object, EvaluationError> {
+        match statement {
+            Statement::Block(mut statements) => {
+                let Some(last) = statements.pop() else {
+                    return Ok(Object::Null);
+                };
+
+                for statement in statements {
+                    let evaluated = match statement {
+                        // a `return` leaves the function from anywhere in its body, even
+                        // from an `if` or `match` whose value is dropped
+                        Statement::Return { .. }
+                        | Statement::Expression(
+                            Expression::If { .. } | Expression::Match { .. },
+                            _,
+                        ) => self.eval_tail_statement(statement, Rc::clone(&environment))?,
+                        statement => self.eval(statement, Rc::clone(&environment))?,
+                    };
+
+                    match evaluated {
+                        Object::ReturnValue(_) => return Ok(evaluated),
+                        // a call whose value is dropped still has to run
+                        Object::TailCall { .. } => {
+                            self.resolve_tail_call(Ok(evaluated))?;
+                        }
+                        _ => {}
+                    }
+                }
+
+                self.eval_tail_statement(last, environment)
+            }
+            Statement::Expression(..) | Statement::Return { .. } => {
+                self.eval_tail_node(Node::Statement(statement), environment)
+            }
+            statement => self.eval(statement, environment),
+        }
+    }
+
+    fn eval_tail_expression(
+        &mut self,
+        expression: Expression,
+        environment: Rc<RefCell<Environment>>,
+    ) -> Result<Object, EvaluationError> {
+        match expression {
+            Expression::Call {
+                function,
+                arguments,
+                named_arguments,
+            } => self.eval_call_operands(*function, arguments, named_arguments, environment),
+            Expression::If { .. } | Expression::Match { .. } => {
+                self.eval_tail_node(Node::Expression(expression), environment)
+            }
+            expression => self.eval(expression, environment),
         }
+    }
 
-        let body = match self.eval(Statement::Block(body), Rc::clone(&local_env))? {
-            Object::ReturnValue(value) => *value,
-            value => value,
+    // tail nodes skip `eval`, so they take their step and are reported to the hook here
+    // in the same way; their value can be a tail call that is still to be made
+    fn eval_tail_node(
+        &mut self,
+        node: Node,
+        environment: Rc<RefCell<Environment>>,
+    ) -> Result<Object, EvaluationError> {
+        self.consume_step()?;
+
+        let location = match &node {
+            Node::Expression(expression) => expression.location().cloned(),
+            Node::Statement(statement) => statement.location().cloned(),
+            Node::Program(_) => None,
         };
 
-        Ok(body)
+        let reported = self
+            .with_hook(|hook, evaluator| hook.before_node(evaluator, &node))
+            .is_some();
+
+        let result = stacker::maybe_grow(STACK_RED_ZONE, STACK_GROWTH, || match node {
+            Node::Statement(statement) => {
+                self.before_statement(&statement, &environment)?;
+                match statement {
+                    Statement::Expression(expression, position) => self
+                        .eval_tail_expression(expression, environment)
+                        .map(|value| value.at(&position.0)),
+                    Statement::Return { value, position } => self
+                        .eval_tail_expression(value, environment)
+                        .map(|value| Object::return_value(value.at(&position.0))),
+                    statement => self.eval_statement(statement, environment),
+                }
+            }
+            Node::Expression(Expression::If {
+                condition,
+                consequence,
+                alternative,
+                position,
+            }) => {
+                let condition = self.eval(*condition, Rc::clone(&environment))?;
+
+                if self.take_branch(&position, condition) {
+                    self.eval_tail_statement(Statement::Block(consequence), environment)
+                } else if let Some(alternative) = alternative {
+                    self.eval_tail_statement(Statement::Block(alternative), environment)
+                } else {
+                    Ok(Object::Null)
+                }
+            }
+            Node::Expression(Expression::Match {
+                subject,
+                arms,
+                position,
+            }) => {
+                let (body, arm_env) =
+                    self.select_match_arm(*subject, arms, position.0, environment)?;
+                self.eval_tail_statement(Statement::Block(body), arm_env)
+            }
+            Node::Expression(expression) => self.eval_expression(expression, environment),
+            Node::Program(program) => self.eval(program, environment),
+        })
+        .map_err(|err| err.or_location(location));
+
+        if reported {
+            self.with_hook(|hook, evaluator| hook.after_node(evaluator, &result));
+        }
+
+        result
+    }
+
+    fn resolve_tail_call(
+        &mut self,
+        result: Result<Object, EvaluationError>,
+    ) -> Result<Object, EvaluationError> {
+        match result? {
+            Object::TailCall {
+                function,
+                arguments,
+                named_arguments,
+                location,
+            } => self
+                .apply_function(*function, arguments, named_arguments)
+                .map_err(|err| err.or_location(location)),
+            Object::ReturnValue(value) => match *value {
+                Object::TailCall {
+                    function,
+                    arguments,
+                    named_arguments,
+                    location,
+                } => Ok(Object::return_value(
+                    self.apply_function(*function, arguments, named_arguments)
+                        .map_err(|err| err.or_location(location))?,
+                )),
+                value => Ok(Object::return_value(value)),
+            },
+            value => Ok(value),
+        }
     }
 
     fn eval_function(
         &mut self,
-        parameters: Vec<String>,
+        parameters: Vec<Parameter>,
+        rest: Option<String>,
         body: Vec<Statement>,
+        position: Position,
         environment: Rc<RefCell<Environment>>,
     ) -> Result<Object, EvaluationError> {
+        self.allocate(0)?;
+
         Ok(Object::Function {
+            name: None,
             parameters,
+            rest,
             body,
             environment: Environment::with_outer(Rc::clone(&environment)),
+            position,
         })
     }
 
@@ -181,11 +1022,12 @@ impl Evaluator {
         condition: Expression,
         consequence: Vec<Statement>,
         alternative: Option<Vec<Statement>>,
+        position: Position,
         environment: Rc<RefCell<Environment>>,
     ) -> Result<Object, EvaluationError> {
         let condition = self.eval(condition, Rc::clone(&environment))?;
 
-        if self.is_truthy(condition) {
+        if self.take_branch(&position, condition) {
             self.eval_statements(consequence, Rc::clone(&environment))
         } else if let Some(alternative) = alternative {
             self.eval_statements(alternative, environment)
@@ -194,6 +1036,18 @@ impl Evaluator {
         }
     }
 
+    // tells the hook which way an `if` goes
+    fn take_branch(&mut self, position: &Position, condition: Object) -> bool {
+        let truthy = self.is_truthy(condition);
+        let branch = match truthy {
+            true => Branch::Consequence,
+            false => Branch::Alternative,
+        };
+        self.with_hook(|hook, evaluator| hook.branch(evaluator, &position.0, branch));
+
+        truthy
+    }
+
     fn eval_identifier(
         &mut self,
         identifier: String,
@@ -201,10 +1055,9 @@ impl Evaluator {
     ) -> Result<Object, EvaluationError> {
         match environment.borrow().get(&identifier) {
             Some(object) => Ok(object),
-            None => Err(EvaluationError::new(format!(
-                "identifier not found: {}",
-                identifier
-            ))),
+            None => Err(EvaluationError::new(
+                EvaluationErrorKind::UnboundIdentifier(identifier),
+            )),
         }
     }
 
@@ -213,8 +1066,13 @@ impl Evaluator {
             Object::Integer(integer) => integer != 0,
             Object::Boolean(boolean) => boolean,
             Object::Null => false,
+            Object::String(string) => !string.is_empty(),
+            Object::Array(elements) => !elements.is_empty(),
+            Object::Hash(entries) => !entries.is_empty(),
+            Object::Error { .. } => true,
             Object::ReturnValue(value) => self.is_truthy(*value),
-            Object::Function { .. } => todo!(),
+            Object::TailCall { .. } => unreachable!("tail calls are resolved before use"),
+            Object::NativeFunction(_) | Object::Function { .. } => true,
         }
     }
 
@@ -243,22 +1101,25 @@ impl Evaluator {
 
         match (&operator, &lhs, &rhs) {
             (InfixOperator::Add, Object::Integer(int1), Object::Integer(int2)) => {
-                Ok(Object::Integer(int1 + int2))
+                self.checked_arithmetic(&operator, *int1, *int2, int1.checked_add(*int2))
             }
             (InfixOperator::Sub, Object::Integer(int1), Object::Integer(int2)) => {
-                Ok(Object::Integer(int1 - int2))
+                self.checked_arithmetic(&operator, *int1, *int2, int1.checked_sub(*int2))
             }
             (InfixOperator::Mult, Object::Integer(int1), Object::Integer(int2)) => {
-                Ok(Object::Integer(int1 * int2))
+                self.checked_arithmetic(&operator, *int1, *int2, int1.checked_mul(*int2))
             }
             (InfixOperator::Div, Object::Integer(int1), Object::Integer(int2)) => {
                 if *int2 == 0 {
-                    return Err(EvaluationError::new("cannot divide by zero"));
+                    return Err(EvaluationError::new(EvaluationErrorKind::DivisionByZero));
                 }
-                Ok(Object::Integer(int1 / int2))
+                self.checked_arithmetic(&operator, *int1, *int2, int1.checked_div(*int2))
             }
             (InfixOperator::Modulo, Object::Integer(int1), Object::Integer(int2)) => {
-                Ok(Object::Integer(int1 % int2))
+                if *int2 == 0 {
+                    return Err(EvaluationError::new(EvaluationErrorKind::DivisionByZero));
+                }
+                self.checked_arithmetic(&operator, *int1, *int2, int1.checked_rem(*int2))
             }
             (InfixOperator::Equal, Object::Integer(int1), Object::Integer(int2)) => {
                 Ok(Object::Boolean(int1 == int2))
@@ -278,12 +1139,36 @@ impl Evaluator {
             (InfixOperator::NotEqual, Object::Boolean(bool1), Object::Boolean(bool2)) => {
                 Ok(Object::Boolean(bool1 != bool2))
             }
-            _ => {
-                return Err(EvaluationError::new(format!(
-                    "invalid operation: {} {} {}",
-                    lhs, operator, rhs
-                )))
+            (InfixOperator::Add, Object::String(str1), Object::String(str2)) => {
+                self.allocate(str1.len() + str2.len())?;
+                Ok(Object::string(format!("{}{}", str1, str2)))
+            }
+            (InfixOperator::Equal, Object::String(str1), Object::String(str2)) => {
+                Ok(Object::Boolean(str1 == str2))
             }
+            (InfixOperator::NotEqual, Object::String(str1), Object::String(str2)) => {
+                Ok(Object::Boolean(str1 != str2))
+            }
+            _ => Err(EvaluationError::new(EvaluationErrorKind::TypeMismatch {
+                op: operator,
+                lhs,
+                rhs,
+            })),
+        }
+    }
+
+    fn checked_arithmetic(
+        &self,
+        operator: &InfixOperator,
+        lhs: i64,
+        rhs: i64,
+        result: Option<i64>,
+    ) -> Result<Object, EvaluationError> {
+        match result {
+            Some(result) => Ok(Object::Integer(result)),
+            None => Err(EvaluationError::new(EvaluationErrorKind::Overflow(
+                format!("{} {} {}", lhs, operator, rhs),
+            ))),
         }
     }
 
@@ -291,7 +1176,10 @@ impl Evaluator {
         match rhs {
             Object::Boolean(boolean) => Ok(Object::Boolean(!boolean)),
             Object::Integer(integer) => Ok(Object::Boolean(integer == 0)),
-            x => Err(EvaluationError::new(format!("invalid operation: !{}", x))),
+            x => Err(EvaluationError::new(EvaluationErrorKind::InvalidOperand {
+                op: PrefixOperator::Not,
+                operand: x,
+            })),
         }
     }
 
@@ -300,15 +1188,31 @@ impl Evaluator {
         rhs: Object,
     ) -> Result<Object, EvaluationError> {
         match rhs {
-            Object::Integer(integer) => Ok(Object::Integer(-integer)),
-            x => Err(EvaluationError::new(format!("invalid operation: -{}", x))),
+            Object::Integer(integer) => match integer.checked_neg() {
+                Some(negated) => Ok(Object::Integer(negated)),
+                None => Err(EvaluationError::new(EvaluationErrorKind::Overflow(
+                    format!("-({})", integer),
+                ))),
+            },
+            x => Err(EvaluationError::new(EvaluationErrorKind::InvalidOperand {
+                op: PrefixOperator::Negative,
+                operand: x,
+            })),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::rc::Rc;
+    use std::{
+        rc::Rc,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        thread,
+        time::{Duration, Instant},
+    };
 
     use indoc::indoc;
 
@@ -316,12 +1220,327 @@ mod tests {
         evaluator::{environment::Environment, object::Object},
         lexer::lexer::Lexer,
         parser::{
-            ast::{expression::Expression, operator::InfixOperator, statement::Statement},
+            ast::{
+                expression::{Expression, Parameter},
+                operator::{InfixOperator, PrefixOperator},
+                position::Position,
+                statement::Statement,
+            },
             parser::Parser,
         },
     };
 
-    use super::{EvaluationError, Evaluator};
+    use super::Evaluator;
+    use crate::evaluator::{
+        error::{EvaluationError, EvaluationErrorKind},
+        limits::EvalLimits,
+        native::{Arity, NativeFunction},
+    };
+
+    #[test]
+    fn test_return_outside_tail_position() {
+        let tests = vec![
+            "let f = fn() { [if (true) { return g(); }] }; f();",
+            "let f = fn() { 1 + if (true) { return g(); } }; f();",
+            "let f = fn() { if (if (true) { return g(); }) { 2 } }; f();",
+        ];
+        // outside tail position, returning a call's value works like returning the value
+        for test in tests {
+            let evaluated = evaluate(&format!("let g = fn() {{ 5 }}; {}", test));
+            let expected = evaluate(&test.replace("g()", "5"));
+            assert_eq!(
+                evaluated.map_err(|err| *err.kind),
+                expected.map_err(|err| *err.kind),
+                "{}",
+                test
+            );
+        }
+    }
+
+    #[test]
+    fn test_tail_calls() {
+        let tests = vec![
+            (
+                indoc! {"
+                    let count = fn(n, acc) {
+                        if (n == 0) { acc } else { count(n - 1, acc + 1) }
+                    };
+                    count(20000, 0);
+                "},
+                Object::Integer(20000),
+            ),
+            (
+                indoc! {"
+                    let count = fn(n) {
+                        if (n == 0) { return true; }
+                        return count(n - 1);
+                    };
+                    count(20000);
+                "},
+                Object::Boolean(true),
+            ),
+            (
+                indoc! {"
+                    let is_even = fn(n) { if (n == 0) { true } else { is_odd(n - 1) } };
+                    let is_odd = fn(n) { if (n == 0) { false } else { is_even(n - 1) } };
+                    is_even(20001);
+                "},
+                Object::Boolean(false),
+            ),
+            (
+                indoc! {"
+                    let count = fn(n) { match (n) { 0 => { true }, _ => { count(n - 1) } } };
+                    count(20000);
+                "},
+                Object::Boolean(true),
+            ),
+            (
+                indoc! {"
+                    let count = fn(n) {
+                        if (n > 0) { return count(n - 1); }
+                        match (n) { _ => { return true; } }
+                        false
+                    };
+                    count(20000);
+                "},
+                Object::Boolean(true),
+            ),
+            (
+                indoc! {"
+                    let fail = fn() { throw \"boom\"; };
+                    let f = fn() { try { return fail(); } catch (e) { e[\"message\"] } };
+                    f();
+                "},
+                Object::string("boom"),
+            ),
+        ];
+        for test in tests {
+            let evaluated = evaluate(test.0);
+            assert_eq!(evaluated.unwrap(), test.1, "{}", test.0);
+        }
+    }
+
+    #[test]
+    fn test_call_depth_limit() {
+        let input = indoc! {"
+            let sum = fn(n) { if (n == 0) { 0 } else { n + sum(n - 1) } };
+            sum(5000);
+        "};
+        let evaluated = evaluate(input);
+        assert_eq!(evaluated.unwrap(), Object::Integer(12502500));
+
+        let input = indoc! {"
+            let sum = fn(n) { if (n == 0) { 0 } else { n + sum(n - 1) } };
+            sum(100);
+        "};
+        let program = Parser::new(Lexer::new(input)).parse_program();
+        let mut evaluator = Evaluator::with_limits(EvalLimits {
+            max_call_depth: 100,
+            ..EvalLimits::default()
+        });
+        let evaluated = evaluator.eval(program, Environment::new());
+        assert_eq!(
+            *evaluated.unwrap_err().kind,
+            EvaluationErrorKind::StackOverflow(100)
+        );
+
+        let input = indoc! {"
+            let sum = fn(n) { if (n == 0) { 0 } else { n + sum(n - 1) } };
+            let result = try { sum(20000) } catch (e) { e[\"kind\"] };
+            [result, sum(10)];
+        "};
+        let evaluated = evaluate(input);
+        assert_eq!(
+            evaluated.unwrap(),
+            Object::Array(vec![Object::string("StackOverflow"), Object::Integer(55)])
+        );
+    }
+
+    #[test]
+    fn test_eval_limits() {
+        let tests = vec![
+            (
+                "let f = fn() { f() }; f();",
+                EvalLimits {
+                    max_steps: Some(1000),
+                    ..EvalLimits::default()
+                },
+                EvaluationErrorKind::OutOfFuel(1000),
+            ),
+            (
+                "let f = fn() { f() }; try { f() } catch (e) { 1 };",
+                EvalLimits {
+                    max_steps: Some(1000),
+                    ..EvalLimits::default()
+                },
+                EvaluationErrorKind::OutOfFuel(1000),
+            ),
+            (
+                "let f = fn() { f() }; f();",
+                EvalLimits {
+                    deadline: Some(Instant::now()),
+                    ..EvalLimits::default()
+                },
+                EvaluationErrorKind::DeadlineExceeded,
+            ),
+            (
+                "let f = fn(n) { [n]; f(n + 1) }; f(0);",
+                EvalLimits {
+                    max_allocations: Some(10),
+                    ..EvalLimits::default()
+                },
+                EvaluationErrorKind::AllocationLimitExceeded(10),
+            ),
+            (
+                "[1, 2, 3, 4]",
+                EvalLimits {
+                    max_collection_size: Some(3),
+                    ..EvalLimits::default()
+                },
+                EvaluationErrorKind::CollectionTooLarge { limit: 3, size: 4 },
+            ),
+            (
+                "let [a, ..rest] = [1, 2]; rest",
+                EvalLimits {
+                    max_allocations: Some(1),
+                    ..EvalLimits::default()
+                },
+                EvaluationErrorKind::AllocationLimitExceeded(1),
+            ),
+            (
+                "let s = \"ab\"; let s = s + s; s + s",
+                EvalLimits {
+                    max_collection_size: Some(4),
+                    ..EvalLimits::default()
+                },
+                EvaluationErrorKind::CollectionTooLarge { limit: 4, size: 8 },
+            ),
+        ];
+        for test in tests {
+            let program = Parser::new(Lexer::new(test.0)).parse_program();
+            let mut evaluator = Evaluator::with_limits(test.1);
+            let evaluated = evaluator.eval(program, Environment::new());
+            assert_eq!(*evaluated.unwrap_err().kind, test.2, "{}", test.0);
+        }
+    }
+
+    #[test]
+    fn test_eval_usage() {
+        let input = indoc! {"
+            let f = fn(n) { if (n == 0) { [] } else { [f(n - 1)] } };
+            f(4);
+        "};
+        let program = Parser::new(Lexer::new(input)).parse_program();
+        let mut evaluator = Evaluator::new();
+        evaluator.eval(program, Environment::new()).unwrap();
+
+        let usage = evaluator.usage();
+        assert_eq!(usage.peak_call_depth, 5);
+        assert_eq!(usage.allocations, 6);
+        assert!(usage.steps > 0);
+
+        let program = Parser::new(Lexer::new("let f = fn() { f() }; f();")).parse_program();
+        let mut evaluator = Evaluator::with_limits(EvalLimits {
+            max_steps: Some(50),
+            ..EvalLimits::default()
+        });
+        let err = evaluator.eval(program, Environment::new()).unwrap_err();
+        assert_eq!(*err.kind, EvaluationErrorKind::OutOfFuel(50));
+        assert_eq!(evaluator.usage().steps, 51);
+    }
+
+    #[test]
+    fn test_interrupt() {
+        let interrupted = Arc::new(AtomicBool::new(false));
+        let handle = Arc::clone(&interrupted);
+        let interrupter = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            handle.store(true, Ordering::Relaxed);
+        });
+
+        let input = "let f = fn() { f() }; try { f() } catch (e) { 1 };";
+        let program = Parser::new(Lexer::new(input)).parse_program();
+        let mut evaluator = Evaluator::new();
+        evaluator.set_interrupt_handle(Arc::clone(&interrupted));
+        let evaluated = evaluator.eval(program, Environment::new());
+        interrupter.join().unwrap();
+
+        let err = evaluated.unwrap_err();
+        assert_eq!(*err.kind, EvaluationErrorKind::Interrupted);
+        assert_eq!(err.to_string(), "interrupted");
+        assert!(!interrupted.load(Ordering::Relaxed));
+
+        let program = Parser::new(Lexer::new("let f = fn() { 1 }; f();")).parse_program();
+        let evaluated = evaluator.eval(program, Environment::new());
+        assert_eq!(evaluated.unwrap(), Object::Integer(1));
+    }
+
+    #[test]
+    fn test_native_functions() {
+        let environment = Environment::new();
+        environment.borrow_mut().register(NativeFunction::new(
+            "double",
+            Arity::exact(1),
+            |arguments| match &arguments[0] {
+                Object::Integer(value) => Ok(Object::Integer(value * 2)),
+                value => Err(EvaluationError::new(EvaluationErrorKind::Thrown {
+                    kind: "TypeError".to_string(),
+                    message: format!("cannot double {}", value),
+                })),
+            },
+        ));
+        environment
+            .borrow_mut()
+            .register(NativeFunction::with_context(
+                "map",
+                Arity::exact(2),
+                |evaluator, arguments| match &arguments[0] {
+                    Object::Array(elements) => {
+                        let mut result = vec![];
+                        for element in elements {
+                            result.push(evaluator.call(&arguments[1], vec![element.clone()])?);
+                        }
+                        Ok(Object::Array(result))
+                    }
+                    _ => Ok(Object::Null),
+                },
+            ));
+
+        let tests = vec![
+            ("double(21)", Ok(Object::Integer(42))),
+            (
+                "map([1, 2], fn(x) { x + 1 })",
+                Ok(Object::Array(vec![Object::Integer(2), Object::Integer(3)])),
+            ),
+            (
+                "map([1, 2], double)",
+                Ok(Object::Array(vec![Object::Integer(2), Object::Integer(4)])),
+            ),
+            (
+                "try { double(true) } catch (e) { e[\"kind\"] }",
+                Ok(Object::string("TypeError")),
+            ),
+            (
+                "double(1, 2)",
+                Err(EvaluationErrorKind::ArityMismatch {
+                    min: 1,
+                    max: Some(1),
+                    got: 2,
+                }),
+            ),
+            (
+                "double(x: 1)",
+                Err(EvaluationErrorKind::UnexpectedNamedArgument(
+                    "x".to_string(),
+                )),
+            ),
+        ];
+        for test in tests {
+            let program = Parser::new(Lexer::new(test.0)).parse_program();
+            let evaluated = Evaluator::new().eval(program, Rc::clone(&environment));
+            assert_eq!(evaluated.map_err(|err| *err.kind), test.1, "{}", test.0);
+        }
+    }
 
     #[test]
     fn test_recursion() {
@@ -401,33 +1620,404 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_default_and_rest_parameters() {
+        let tests = vec![
+            ("let f = fn(a, b = 2) { a + b }; f(1);", Object::Integer(3)),
+            (
+                "let f = fn(a, b = 2) { a + b }; f(1, 5);",
+                Object::Integer(6),
+            ),
+            (
+                "let f = fn(a, b = a * 10) { b }; f(3);",
+                Object::Integer(30),
+            ),
+            (
+                "let f = fn(a, ...rest) { rest }; f(1, 2, 3);",
+                Object::Array(vec![Object::Integer(2), Object::Integer(3)]),
+            ),
+            (
+                "let f = fn(a, ...rest) { rest }; f(1);",
+                Object::Array(vec![]),
+            ),
+            (
+                "let f = fn(...all) { all }; f(1, true);",
+                Object::Array(vec![Object::Integer(1), Object::Boolean(true)]),
+            ),
+            (
+                "let f = fn(a, b = 2, c = 3) { a * 100 + b * 10 + c }; f(1, c: 9);",
+                Object::Integer(129),
+            ),
+            (
+                "let f = fn(a, b) { a - b }; f(b: 1, a: 5);",
+                Object::Integer(4),
+            ),
+            (
+                "let f = fn(a = 1, b) { a + b }; f(b: 2);",
+                Object::Integer(3),
+            ),
+        ];
+        for test in tests {
+            let evaluated = evaluate(test.0);
+            assert_eq!(evaluated.unwrap(), test.1, "{}", test.0);
+        }
+    }
+
+    #[test]
+    fn test_arity_errors() {
+        let tests = vec![
+            (
+                "let f = fn(a, b) { a }; f(1);",
+                EvaluationErrorKind::ArityMismatch {
+                    min: 2,
+                    max: Some(2),
+                    got: 1,
+                },
+            ),
+            (
+                "let f = fn(a, b = 2) { a }; f(1, 2, 3);",
+                EvaluationErrorKind::ArityMismatch {
+                    min: 1,
+                    max: Some(2),
+                    got: 3,
+                },
+            ),
+            (
+                "let f = fn(a, b, ...rest) { a }; f(1);",
+                EvaluationErrorKind::ArityMismatch {
+                    min: 2,
+                    max: None,
+                    got: 1,
+                },
+            ),
+            (
+                "let f = fn(a) { a }; f(b: 1);",
+                EvaluationErrorKind::UnexpectedNamedArgument("b".to_string()),
+            ),
+            (
+                "let f = fn(a, b) { a }; f(1, a: 2);",
+                EvaluationErrorKind::DuplicateArgument("a".to_string()),
+            ),
+            (
+                "let f = fn(a = 1, b) { a }; f(1);",
+                EvaluationErrorKind::MissingArgument("b".to_string()),
+            ),
+        ];
+        for test in tests {
+            let evaluated = evaluate(test.0);
+            assert_eq!(*evaluated.unwrap_err().kind, test.1, "{}", test.0);
+        }
+
+        let evaluated = evaluate("let f = fn(a, b = 2) { a }; f(1, 2, 3);");
+        assert_eq!(
+            evaluated.unwrap_err().to_string(),
+            "wrong number of arguments: got 3, but function wants 1 to 2 @1:29"
+        );
+    }
+
+    #[test]
+    fn test_index_expressions() {
+        let tests = vec![
+            ("[1, 2, 3][0]", Object::Integer(1)),
+            ("[1, 2, 3][1 + 1]", Object::Integer(3)),
+            ("let xs = [1, 2, 3]; xs[2] * xs[0]", Object::Integer(3)),
+            ("[1, 2, 3][3]", Object::Null),
+            ("[1, 2, 3][-1]", Object::Null),
+            ("{\"a\": 5}[\"a\"]", Object::Integer(5)),
+            ("{1: true}[1]", Object::Boolean(true)),
+            ("{\"a\": 5}[\"b\"]", Object::Null),
+        ];
+        for test in tests {
+            let evaluated = evaluate(test.0);
+            assert_eq!(evaluated.unwrap(), test.1, "{}", test.0);
+        }
+
+        let evaluated = evaluate("5[0]");
+        assert_eq!(
+            *evaluated.unwrap_err().kind,
+            EvaluationErrorKind::IndexNotSupported {
+                lhs: Object::Integer(5),
+                index: Object::Integer(0),
+            }
+        );
+    }
+
+    #[test]
+    fn test_try_catch() {
+        let tests = vec![
+            ("try { 1 } catch (e) { 2 }", Object::Integer(1)),
+            ("try { throw 1; 2 } catch (e) { 3 }", Object::Integer(3)),
+            (
+                "try { throw \"boom\"; } catch (e) { e[\"message\"] }",
+                Object::string("boom"),
+            ),
+            (
+                "try { throw \"boom\"; } catch (e) { e[\"kind\"] }",
+                Object::string("Error"),
+            ),
+            (
+                "try { 1 / 0 } catch (e) { e[\"message\"] }",
+                Object::string("cannot divide by zero"),
+            ),
+            (
+                "try { 1 / 0 } catch (e) { e[\"kind\"] }",
+                Object::string("DivisionByZero"),
+            ),
+            (
+                "try { let x = 5; x() } catch (e) { e[\"message\"] }",
+                Object::string("not a function: 5"),
+            ),
+            (
+                "try { try { throw 1; } catch (e) { throw e; } } catch (e) { e[\"message\"] }",
+                Object::string("1"),
+            ),
+            (
+                "try { throw 1; } catch ({message}) { message }",
+                Object::string("1"),
+            ),
+            (
+                "let f = fn() { try { return 1; } finally { 2 } }; f()",
+                Object::Integer(1),
+            ),
+            (
+                "let f = fn() { try { return 1; } finally { return 2; } }; f()",
+                Object::Integer(2),
+            ),
+            (
+                "let f = fn() { try { throw 1; } finally { return 2; } }; f()",
+                Object::Integer(2),
+            ),
+            (
+                "let f = fn() { try { throw 1; } catch ([e]) { e } finally { return 2; } }; f()",
+                Object::Integer(2),
+            ),
+        ];
+        for test in tests {
+            let evaluated = evaluate(test.0);
+            assert_eq!(evaluated.unwrap(), test.1, "{}", test.0);
+        }
+    }
+
+    #[test]
+    fn test_error_location() {
+        let input = indoc! {"
+            try {
+                let x = 1;
+                throw x;
+            } catch (e) {
+                e[\"location\"]
+            }
+        "};
+        let evaluated = evaluate(input);
+        assert_eq!(evaluated.unwrap().to_string(), "{column: 5, line: 3}");
+
+        let evaluated = evaluate("try { 1 / 0 } catch (e) { e[\"location\"] }");
+        assert_eq!(evaluated.unwrap().to_string(), "{column: 7, line: 1}");
+
+        let input = indoc! {"
+            let f = fn(x) {
+                x + true
+            };
+            try { f(1) } catch (e) { e[\"location\"] }
+        "};
+        let evaluated = evaluate(input);
+        assert_eq!(evaluated.unwrap().to_string(), "{column: 5, line: 2}");
+
+        // a call in tail position runs after its statement is done
+        let input = indoc! {"
+            let g = fn(a) { a };
+            let f = fn() {
+                g()
+            };
+            try { f() } catch (e) { e[\"location\"] }
+        "};
+        let evaluated = evaluate(input);
+        assert_eq!(evaluated.unwrap().to_string(), "{column: 5, line: 3}");
+    }
+
+    #[test]
+    fn test_uncaught_errors() {
+        let evaluated = evaluate("throw \"boom\";");
+        assert_eq!(
+            evaluated.unwrap_err().to_string(),
+            "uncaught Error: boom @1:1"
+        );
+
+        let evaluated = evaluate("try { throw 1; } finally { 2 }");
+        assert_eq!(
+            *evaluated.unwrap_err().kind,
+            EvaluationErrorKind::Thrown {
+                kind: "Error".to_string(),
+                message: "1".to_string(),
+            }
+        );
+
+        let evaluated = evaluate("try { 1 } finally { 1 / 0 }");
+        assert_eq!(
+            *evaluated.unwrap_err().kind,
+            EvaluationErrorKind::DivisionByZero
+        );
+
+        let evaluated = evaluate("try { throw 1; } catch (e) { 1 / 0 }");
+        assert_eq!(
+            *evaluated.unwrap_err().kind,
+            EvaluationErrorKind::DivisionByZero
+        );
+    }
+
     #[test]
     fn test_function_object() {
         let evaluated = evaluate("fn(x) { x + 2 };");
         assert_eq!(
             evaluated.unwrap(),
             Object::Function {
-                parameters: vec!["x".to_string()],
-                body: vec![Statement::Expression(Expression::infix(
+                name: None,
+                parameters: vec![Parameter::from("x")],
+                rest: None,
+                body: vec![Statement::expression(Expression::infix(
                     Expression::identifier("x"),
                     Expression::Int(2),
                     InfixOperator::Add
                 ))],
                 environment: Environment::with_outer(Environment::new()),
+                position: Position::default(),
             }
         );
     }
 
+    #[test]
+    fn test_function_names() {
+        let tests = vec![
+            ("let add = fn(a, b) { a + b }; add;", "add"),
+            ("let add = fn(a, b) { a + b }; let plus = add; plus;", "add"),
+            ("let make = fn() { fn(x) { x } }; make();", "fn@1:19"),
+            (
+                "let make = fn() { fn(x) { x } }; let id = make(); id;",
+                "id",
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let evaluated = evaluate(input).unwrap();
+            assert_eq!(evaluated.function_name(), Some(expected.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_match_expression() {
+        let tests = vec![
+            (
+                "match (1) { 1 => \"one\", _ => \"other\" }",
+                Object::string("one"),
+            ),
+            (
+                "match (5) { 1 => \"one\", _ => \"other\" }",
+                Object::string("other"),
+            ),
+            (
+                "match (-1) { -1 => true, _ => false }",
+                Object::Boolean(true),
+            ),
+            ("match (null) { null => 1, _ => 2 }", Object::Integer(1)),
+            (
+                "match (\"a\") { \"b\" => 1, \"a\" => 2 }",
+                Object::Integer(2),
+            ),
+            ("match (7) { n => n * 2 }", Object::Integer(14)),
+            (
+                "match (7) { n if n > 10 => 1, n if n > 5 => 2, _ => 3 }",
+                Object::Integer(2),
+            ),
+            (
+                "match ([1, 2, 3]) { [a, b] => 0, [a, b, c] => a + b + c }",
+                Object::Integer(6),
+            ),
+            (
+                "match ([1, 2, 3]) { [head, ..tail] => tail }",
+                Object::Array(vec![Object::Integer(2), Object::Integer(3)]),
+            ),
+            (
+                "match ([]) { [head, ..tail] => 1, [] => 2 }",
+                Object::Integer(2),
+            ),
+            (
+                "match ([1, [2, 3]]) { [_, [_, x]] => x }",
+                Object::Integer(3),
+            ),
+            (
+                "match ({\"x\": 1, \"y\": 2}) { {x, y: renamed} => x + renamed }",
+                Object::Integer(3),
+            ),
+            (
+                "match ({\"x\": 1}) { {x, y} => 1, {x: 2} => 2, {x: 1} => 3 }",
+                Object::Integer(3),
+            ),
+            (
+                "match (1) { _ => { let a = 2; a * 3 } }",
+                Object::Integer(6),
+            ),
+        ];
+        for test in tests {
+            let evaluated = evaluate(test.0);
+            assert_eq!(evaluated.unwrap(), test.1, "{}", test.0);
+        }
+    }
+
+    #[test]
+    fn test_match_arm_scope() {
+        let input = indoc! {"
+            let x = 1;
+            let f = fn(value) {
+                match (value) {
+                    [x, y] if x > 10 => x,
+                    [y, ..] => x + y,
+                }
+            };
+            f([2, 3]);
+        "};
+        let evaluated = evaluate(input);
+        assert_eq!(evaluated.unwrap(), Object::Integer(3));
+
+        let evaluated = evaluate("match (1) { x => x }; x");
+        assert_eq!(
+            *evaluated.unwrap_err().kind,
+            EvaluationErrorKind::UnboundIdentifier("x".to_string())
+        );
+
+        let evaluated = evaluate("let f = fn() { match (1) { 1 => { return 2; } }; 3 }; f()");
+        assert_eq!(evaluated.unwrap(), Object::Integer(2));
+    }
+
+    #[test]
+    fn test_match_no_arm_matched() {
+        let evaluated = evaluate("let x = 3;\nmatch (x) { 1 => 1, 2 => 2 }");
+        let err = evaluated.unwrap_err();
+        assert_eq!(
+            *err.kind,
+            EvaluationErrorKind::NoMatchingArm(Object::Integer(3))
+        );
+        assert_eq!(err.to_string(), "no match arm matched value: 3 @2:1");
+    }
+
     #[test]
     fn test_eval_not_null() {
         let evaluated = evaluate("!null");
-        assert_eq!(evaluated.unwrap_err().msg, "invalid operation: !null");
+        assert_eq!(
+            *evaluated.unwrap_err().kind,
+            EvaluationErrorKind::InvalidOperand {
+                op: PrefixOperator::Not,
+                operand: Object::Null,
+            }
+        );
     }
 
     #[test]
     fn test_identifier_not_found() {
         let evaluated = evaluate("foobar");
-        assert_eq!(evaluated.unwrap_err().msg, "identifier not found: foobar");
+        assert_eq!(
+            *evaluated.unwrap_err().kind,
+            EvaluationErrorKind::UnboundIdentifier("foobar".to_string())
+        );
     }
 
     #[test]
@@ -446,18 +2036,109 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_destructuring_let_statements() {
+        let tests = vec![
+            ("let [a, b] = [1, 2]; a + b;", Object::Integer(3)),
+            (
+                "let [a, b, ..rest] = [1, 2, 3, 4]; rest;",
+                Object::Array(vec![Object::Integer(3), Object::Integer(4)]),
+            ),
+            ("let [a, ..rest] = [1]; rest;", Object::Array(vec![])),
+            ("let [_, [b, _]] = [1, [2, 3]]; b;", Object::Integer(2)),
+            (
+                "let {x, y: renamed} = {\"x\": 1, \"y\": 2, \"z\": 3}; x * 10 + renamed;",
+                Object::Integer(12),
+            ),
+            ("let {x: [a, ..]} = {\"x\": [7, 8]}; a;", Object::Integer(7)),
+        ];
+        for test in tests {
+            let evaluated = evaluate(test.0);
+            assert_eq!(evaluated.unwrap(), test.1, "{}", test.0);
+        }
+    }
+
+    #[test]
+    fn test_destructuring_function_parameters() {
+        let input = indoc! {"
+            let distance = fn({x, y}, [dx, dy]) { (x + dx) * (y + dy) };
+            distance({\"x\": 1, \"y\": 2}, [1, 1]);
+        "};
+        let evaluated = evaluate(input);
+        assert_eq!(evaluated.unwrap(), Object::Integer(6));
+    }
+
+    #[test]
+    fn test_destructuring_errors() {
+        let tests = vec![
+            (
+                "let [a, b] = [1, 2, 3];",
+                "cannot destructure [1, 2, 3]: expected 2 elements, got 3",
+            ),
+            (
+                "let [a, b, ..rest] = [1];",
+                "cannot destructure [1]: expected at least 2 elements, got 1",
+            ),
+            ("let [a] = 5;", "cannot destructure 5 as an array"),
+            ("let {x} = [1];", "cannot destructure [1] as a hash"),
+            (
+                "let {x, y} = {\"x\": 1};",
+                "cannot destructure {x: 1}: missing key \"y\"",
+            ),
+            (
+                "let f = fn([a]) { a }; f(1);",
+                "cannot destructure 1 as an array",
+            ),
+        ];
+        for test in tests {
+            let evaluated = evaluate(test.0);
+            assert_eq!(
+                *evaluated.unwrap_err().kind,
+                EvaluationErrorKind::PatternMismatch(test.1.to_string()),
+                "{}",
+                test.0
+            );
+        }
+    }
+
     #[test]
     fn test_error_handling() {
+        let type_mismatch = |lhs, rhs| EvaluationErrorKind::TypeMismatch {
+            op: InfixOperator::Add,
+            lhs,
+            rhs,
+        };
         let tests = vec![
-            ("5 + true;", "invalid operation: 5 + true"),
-            ("false + 5;", "invalid operation: false + 5"),
-            ("5 + true; 5;", "invalid operation: 5 + true"),
-            ("-true", "invalid operation: -true"),
-            ("true + false;", "invalid operation: true + false"),
-            ("5; true + false; 5", "invalid operation: true + false"),
+            (
+                "5 + true;",
+                type_mismatch(Object::Integer(5), Object::Boolean(true)),
+            ),
+            (
+                "false + 5;",
+                type_mismatch(Object::Boolean(false), Object::Integer(5)),
+            ),
+            (
+                "5 + true; 5;",
+                type_mismatch(Object::Integer(5), Object::Boolean(true)),
+            ),
+            (
+                "-true",
+                EvaluationErrorKind::InvalidOperand {
+                    op: PrefixOperator::Negative,
+                    operand: Object::Boolean(true),
+                },
+            ),
+            (
+                "true + false;",
+                type_mismatch(Object::Boolean(true), Object::Boolean(false)),
+            ),
+            (
+                "5; true + false; 5",
+                type_mismatch(Object::Boolean(true), Object::Boolean(false)),
+            ),
             (
                 "if (10 > 1) { true + false; }",
-                "invalid operation: true + false",
+                type_mismatch(Object::Boolean(true), Object::Boolean(false)),
             ),
             (
                 indoc! {"
@@ -468,14 +2149,33 @@ mod tests {
                         return 1;
                     }
                 "},
-                "invalid operation: true + false",
+                type_mismatch(Object::Boolean(true), Object::Boolean(false)),
+            ),
+            ("5 / 0", EvaluationErrorKind::DivisionByZero),
+            ("5 % 0", EvaluationErrorKind::DivisionByZero),
+            (
+                "9223372036854775807 + 1",
+                EvaluationErrorKind::Overflow("9223372036854775807 + 1".to_string()),
+            ),
+            (
+                "let min = -9223372036854775807 - 1; min * -1",
+                EvaluationErrorKind::Overflow("-9223372036854775808 * -1".to_string()),
+            ),
+            (
+                "let min = -9223372036854775807 - 1; -min",
+                EvaluationErrorKind::Overflow("-(-9223372036854775808)".to_string()),
             ),
         ];
         for test in tests {
             let evaluated = evaluate(test.0);
-            assert!(evaluated.is_err());
-            assert_eq!(evaluated.unwrap_err().msg, test.1);
+            assert_eq!(*evaluated.unwrap_err().kind, test.1, "{}", test.0);
         }
+
+        let evaluated = evaluate("5 + true;");
+        assert_eq!(
+            evaluated.unwrap_err().to_string(),
+            "invalid operation: 5 + true @1:1"
+        );
     }
 
     #[test]
@@ -509,6 +2209,11 @@ mod tests {
             ("if (1 > 2) { 10 }", Object::Null),
             ("if (1 > 2) { 10 } else { 20 }", Object::Integer(20)),
             ("if (1 < 2) { 10 } else { 20 }", Object::Integer(10)),
+            ("let f = fn(x) { x }; if (f) { 10 }", Object::Integer(10)),
+            (
+                "let f = fn(x) { x }; match (1) { _ if f => 10 }",
+                Object::Integer(10),
+            ),
             (
                 indoc! {"
                     if (10 > 1) {
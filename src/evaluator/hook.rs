@@ -0,0 +1,160 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    lexer::location::Location,
+    parser::ast::{node::Node, statement::Statement},
+};
+
+use super::{
+    environment::Environment, error::EvaluationError, evaluator::Evaluator, object::Object,
+};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Branch {
+    Consequence,
+    // taken when the condition is falsy, whether or not the `if` has an `else`
+    Alternative,
+}
+
+// lets a front-end such as a debugger observe an evaluation as it runs; the hook is
+// detached while one of its methods runs, so evaluating code from inside it is not
+// observed again
+pub trait Hook {
+    // called before each statement runs; an error aborts the evaluation
+    fn before_statement(
+        &mut self,
+        _evaluator: &mut Evaluator,
+        _statement: &Statement,
+        _environment: &Rc<RefCell<Environment>>,
+    ) -> Result<(), EvaluationError> {
+        Ok(())
+    }
+
+    // called before each expression or statement goes through Evaluator::eval
+    fn before_node(&mut self, _evaluator: &mut Evaluator, _node: &Node) {}
+
+    // called with the result of the node entered last
+    fn after_node(
+        &mut self,
+        _evaluator: &mut Evaluator,
+        _result: &Result<Object, EvaluationError>,
+    ) {
+    }
+
+    // called once an `if` at location has evaluated its condition
+    fn branch(&mut self, _evaluator: &mut Evaluator, _location: &Location, _branch: Branch) {}
+
+    // called when a function, native or not, starts running with its arguments; a tail
+    // call leaves the caller before entering the callee
+    fn enter_function(
+        &mut self,
+        _evaluator: &mut Evaluator,
+        _function: &Object,
+        _arguments: &[Object],
+    ) {
+    }
+
+    // called when the function entered last returns, with an Object::TailCall when it
+    // hands over to a tail call
+    fn exit_function(
+        &mut self,
+        _evaluator: &mut Evaluator,
+        _result: &Result<Object, EvaluationError>,
+    ) {
+    }
+}
+
+// lets the host keep a handle on a hook it hands to the evaluator
+impl<H: Hook> Hook for Rc<RefCell<H>> {
+    fn before_statement(
+        &mut self,
+        evaluator: &mut Evaluator,
+        statement: &Statement,
+        environment: &Rc<RefCell<Environment>>,
+    ) -> Result<(), EvaluationError> {
+        self.borrow_mut()
+            .before_statement(evaluator, statement, environment)
+    }
+
+    fn before_node(&mut self, evaluator: &mut Evaluator, node: &Node) {
+        self.borrow_mut().before_node(evaluator, node)
+    }
+
+    fn after_node(&mut self, evaluator: &mut Evaluator, result: &Result<Object, EvaluationError>) {
+        self.borrow_mut().after_node(evaluator, result)
+    }
+
+    fn branch(&mut self, evaluator: &mut Evaluator, location: &Location, branch: Branch) {
+        self.borrow_mut().branch(evaluator, location, branch)
+    }
+
+    fn enter_function(
+        &mut self,
+        evaluator: &mut Evaluator,
+        function: &Object,
+        arguments: &[Object],
+    ) {
+        self.borrow_mut()
+            .enter_function(evaluator, function, arguments)
+    }
+
+    fn exit_function(
+        &mut self,
+        evaluator: &mut Evaluator,
+        result: &Result<Object, EvaluationError>,
+    ) {
+        self.borrow_mut().exit_function(evaluator, result)
+    }
+}
+
+// runs several hooks, in order, on each event
+impl Hook for Vec<Box<dyn Hook>> {
+    fn before_statement(
+        &mut self,
+        evaluator: &mut Evaluator,
+        statement: &Statement,
+        environment: &Rc<RefCell<Environment>>,
+    ) -> Result<(), EvaluationError> {
+        self.iter_mut()
+            .try_for_each(|hook| hook.before_statement(evaluator, statement, environment))
+    }
+
+    fn before_node(&mut self, evaluator: &mut Evaluator, node: &Node) {
+        for hook in self {
+            hook.before_node(evaluator, node);
+        }
+    }
+
+    fn after_node(&mut self, evaluator: &mut Evaluator, result: &Result<Object, EvaluationError>) {
+        for hook in self {
+            hook.after_node(evaluator, result);
+        }
+    }
+
+    fn branch(&mut self, evaluator: &mut Evaluator, location: &Location, branch: Branch) {
+        for hook in self {
+            hook.branch(evaluator, location, branch);
+        }
+    }
+
+    fn enter_function(
+        &mut self,
+        evaluator: &mut Evaluator,
+        function: &Object,
+        arguments: &[Object],
+    ) {
+        for hook in self {
+            hook.enter_function(evaluator, function, arguments);
+        }
+    }
+
+    fn exit_function(
+        &mut self,
+        evaluator: &mut Evaluator,
+        result: &Result<Object, EvaluationError>,
+    ) {
+        for hook in self {
+            hook.exit_function(evaluator, result);
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use std::time::Instant;
+
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvalLimits {
+    pub max_call_depth: usize,
+    pub max_steps: Option<u64>,
+    pub deadline: Option<Instant>,
+    pub max_allocations: Option<usize>,
+    pub max_collection_size: Option<usize>,
+}
+
+impl Default for EvalLimits {
+    fn default() -> Self {
+        EvalLimits {
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            max_steps: None,
+            deadline: None,
+            max_allocations: None,
+            max_collection_size: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EvalUsage {
+    pub steps: u64,
+    pub allocations: usize,
+    pub peak_call_depth: usize,
+}
@@ -1,3 +1,13 @@
+pub mod convert;
+pub mod coverage;
 pub mod environment;
+pub mod error;
 pub mod evaluator;
+pub mod hook;
+pub mod limits;
+pub mod native;
 pub mod object;
+pub mod profile;
+#[cfg(feature = "serde")]
+pub mod serialization;
+pub mod trace;
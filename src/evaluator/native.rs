@@ -0,0 +1,137 @@
+use std::{fmt, rc::Rc};
+
+use super::{
+    error::{EvaluationError, EvaluationErrorKind},
+    evaluator::Evaluator,
+    object::Object,
+};
+
+type NativeFn = dyn Fn(&mut Evaluator, &[Object]) -> Result<Object, EvaluationError>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Arity {
+    pub min: usize,
+    pub max: Option<usize>,
+}
+
+impl Arity {
+    pub fn exact(count: usize) -> Self {
+        Arity {
+            min: count,
+            max: Some(count),
+        }
+    }
+
+    pub fn at_least(min: usize) -> Self {
+        Arity { min, max: None }
+    }
+
+    pub fn range(min: usize, max: usize) -> Self {
+        Arity {
+            min,
+            max: Some(max),
+        }
+    }
+
+    pub fn accepts(&self, count: usize) -> bool {
+        count >= self.min && self.max.is_none_or(|max| count <= max)
+    }
+}
+
+#[derive(Clone)]
+pub struct NativeFunction {
+    pub name: String,
+    pub arity: Arity,
+    function: Rc<NativeFn>,
+}
+
+impl NativeFunction {
+    pub fn new(
+        name: impl Into<String>,
+        arity: Arity,
+        function: impl Fn(&[Object]) -> Result<Object, EvaluationError> + 'static,
+    ) -> Self {
+        NativeFunction::with_context(name, arity, move |_, arguments| function(arguments))
+    }
+
+    pub fn with_context(
+        name: impl Into<String>,
+        arity: Arity,
+        function: impl Fn(&mut Evaluator, &[Object]) -> Result<Object, EvaluationError> + 'static,
+    ) -> Self {
+        NativeFunction {
+            name: name.into(),
+            arity,
+            function: Rc::new(function),
+        }
+    }
+
+    pub fn call(
+        &self,
+        evaluator: &mut Evaluator,
+        arguments: &[Object],
+    ) -> Result<Object, EvaluationError> {
+        if !self.arity.accepts(arguments.len()) {
+            return Err(EvaluationError::new(EvaluationErrorKind::ArityMismatch {
+                min: self.arity.min,
+                max: self.arity.max,
+                got: arguments.len(),
+            }));
+        }
+
+        (self.function)(evaluator, arguments)
+    }
+}
+
+impl PartialEq for NativeFunction {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && Rc::ptr_eq(&self.function, &other.function)
+    }
+}
+
+impl fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "NativeFunction {{ name: {:?}, arity: {:?} }}",
+            self.name, self.arity
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arity() {
+        assert!(Arity::exact(2).accepts(2));
+        assert!(!Arity::exact(2).accepts(3));
+        assert!(Arity::at_least(1).accepts(10));
+        assert!(!Arity::at_least(1).accepts(0));
+        assert!(Arity::range(1, 2).accepts(1));
+        assert!(!Arity::range(1, 2).accepts(3));
+    }
+
+    #[test]
+    fn test_call_checks_arity() {
+        let len = NativeFunction::new("len", Arity::exact(1), |arguments| match &arguments[0] {
+            Object::String(string) => Ok(Object::Integer(string.len() as i64)),
+            _ => Ok(Object::Null),
+        });
+        let mut evaluator = Evaluator::new();
+
+        assert_eq!(
+            len.call(&mut evaluator, &[Object::string("abc")]).unwrap(),
+            Object::Integer(3)
+        );
+        assert_eq!(
+            *len.call(&mut evaluator, &[]).unwrap_err().kind,
+            EvaluationErrorKind::ArityMismatch {
+                min: 1,
+                max: Some(1),
+                got: 0
+            }
+        );
+    }
+}
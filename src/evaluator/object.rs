@@ -1,30 +1,134 @@
 use std::{
     cell::RefCell,
+    collections::BTreeMap,
     fmt::{self, Display},
     rc::Rc,
 };
 
-use crate::parser::ast::statement::Statement;
+use crate::{
+    lexer::location::Location,
+    parser::ast::{expression::Parameter, position::Position, statement::Statement},
+};
 
-use super::environment::Environment;
+use super::{environment::Environment, native::NativeFunction};
 
 #[derive(PartialEq, Clone)]
 pub enum Object {
     Integer(i64),
     Boolean(bool),
+    String(String),
+    Array(Vec<Object>),
+    Hash(BTreeMap<HashKey, Object>),
     ReturnValue(Box<Object>),
+    // a call in tail position, which the function running it makes once it has returned;
+    // only hooks see it, as the result of the function that made it
+    #[doc(hidden)]
+    TailCall {
+        function: Box<Object>,
+        arguments: Vec<Object>,
+        named_arguments: Vec<(String, Object)>,
+        // the statement that made the call, which has finished by the time it runs
+        location: Option<Location>,
+    },
+    Error {
+        kind: String,
+        message: String,
+        location: Option<Location>,
+    },
     Null,
     Function {
-        parameters: Vec<String>,
+        name: Option<String>,
+        parameters: Vec<Parameter>,
+        rest: Option<String>,
         body: Vec<Statement>,
         environment: Rc<RefCell<Environment>>,
+        // where the `fn` literal is, which tells apart functions that share a name
+        position: Position,
     },
+    NativeFunction(NativeFunction),
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone)]
+pub enum HashKey {
+    Integer(i64),
+    Boolean(bool),
+    String(String),
 }
 
 impl Object {
     pub fn return_value(value: Object) -> Self {
         Object::ReturnValue(Box::new(value))
     }
+
+    pub fn string(string: impl Into<String>) -> Self {
+        Object::String(string.into())
+    }
+
+    // records where a tail call was made, unless a statement inside it already did
+    pub(crate) fn at(self, location: &Location) -> Self {
+        match self {
+            Object::TailCall {
+                function,
+                arguments,
+                named_arguments,
+                location: None,
+            } => Object::TailCall {
+                function,
+                arguments,
+                named_arguments,
+                location: Some(location.clone()),
+            },
+            value => value,
+        }
+    }
+
+    // how a function shows up in stack frames: the name it was bound to, or where an
+    // anonymous one is defined
+    pub fn function_name(&self) -> Option<String> {
+        match self {
+            Object::Function {
+                name: Some(name), ..
+            } => Some(name.clone()),
+            Object::Function { position, .. } => Some(format!("fn{}", position.0)),
+            Object::NativeFunction(native) => Some(native.name.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Object::Integer(_) => "Integer",
+            Object::Boolean(_) => "Boolean",
+            Object::String(_) => "String",
+            Object::Array(_) => "Array",
+            Object::Hash(_) => "Hash",
+            Object::ReturnValue(value) => value.type_name(),
+            Object::TailCall { .. } => "TailCall",
+            Object::Error { .. } => "Error",
+            Object::Null => "Null",
+            Object::Function { .. } => "Function",
+            Object::NativeFunction(_) => "NativeFunction",
+        }
+    }
+
+    pub fn hash_key(&self) -> Option<HashKey> {
+        match self {
+            Object::Integer(value) => Some(HashKey::Integer(*value)),
+            Object::Boolean(value) => Some(HashKey::Boolean(*value)),
+            Object::String(value) => Some(HashKey::String(value.clone())),
+            _ => None,
+        }
+    }
+}
+
+impl Display for HashKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HashKey::Integer(value) => write!(f, "{}", value),
+            HashKey::Boolean(value) => write!(f, "{}", value),
+            HashKey::String(value) => write!(f, "{}", value),
+        }
+    }
 }
 
 impl Display for Object {
@@ -32,20 +136,48 @@ impl Display for Object {
         match self {
             Object::Integer(value) => write!(f, "{}", value),
             Object::Boolean(value) => write!(f, "{}", value),
+            Object::String(value) => write!(f, "{}", value),
+            Object::Array(elements) => write!(
+                f,
+                "[{}]",
+                elements
+                    .iter()
+                    .map(|e| format!("{}", e))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Object::Hash(entries) => write!(
+                f,
+                "{{{}}}",
+                entries
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key, value))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
             Object::ReturnValue(value) => write!(f, "{}", *value),
+            Object::TailCall { function, .. } => write!(f, "tail call to {}", function),
+            Object::Error { kind, message, .. } => write!(f, "{}: {}", kind, message),
             Object::Null => write!(f, "null"),
+            Object::NativeFunction(native) => write!(f, "native fn {}", native.name),
             Object::Function {
-                body, parameters, ..
+                body,
+                parameters,
+                rest,
+                ..
             } => {
                 let mut result = String::new();
                 result.push_str("fn");
                 result.push('(');
                 for (i, parameter) in parameters.iter().enumerate() {
-                    result.push_str(&parameter);
-                    if i != parameters.len() - 1 {
+                    result.push_str(&parameter.to_string());
+                    if i != parameters.len() - 1 || rest.is_some() {
                         result.push_str(", ");
                     }
                 }
+                if let Some(rest) = rest {
+                    result.push_str(&format!("...{}", rest));
+                }
                 result.push(')');
                 result.push_str(" {\n");
                 for statement in body {
@@ -63,15 +195,37 @@ impl fmt::Debug for Object {
         match self {
             Object::Integer(value) => write!(f, "Integer({})", value),
             Object::Boolean(value) => write!(f, "Boolean({})", value),
+            Object::String(value) => write!(f, "String({:?})", value),
+            Object::Array(elements) => write!(f, "Array({:?})", elements),
+            Object::Hash(entries) => write!(f, "Hash({:?})", entries),
             Object::ReturnValue(value) => write!(f, "ReturnValue({:?})", *value),
+            Object::TailCall {
+                function,
+                arguments,
+                named_arguments,
+                ..
+            } => write!(
+                f,
+                "TailCall({:?}, {:?}, {:?})",
+                function, arguments, named_arguments
+            ),
+            Object::Error {
+                kind,
+                message,
+                location,
+            } => write!(f, "Error({}, {:?}, {:?})", kind, message, location),
             Object::Null => write!(f, "Null"),
+            Object::NativeFunction(native) => write!(f, "{:?}", native),
             Object::Function {
-                parameters, body, ..
+                parameters,
+                rest,
+                body,
+                ..
             } => {
                 write!(
                     f,
-                    "Function {{ parameters: {:?}, body: {:?}, environment: ... }}",
-                    parameters, body
+                    "Function {{ parameters: {:?}, rest: {:?}, body: {:?}, environment: ... }}",
+                    parameters, rest, body
                 )
             }
         }
@@ -91,5 +245,13 @@ mod tests {
             "1"
         );
         assert_eq!(format!("{}", Object::Null), "null");
+        assert_eq!(format!("{}", Object::string("foo")), "foo");
+        assert_eq!(
+            format!(
+                "{}",
+                Object::Array(vec![Object::Integer(1), Object::Boolean(true)])
+            ),
+            "[1, true]"
+        );
     }
 }
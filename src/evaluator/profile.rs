@@ -0,0 +1,262 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{self, Write},
+    time::{Duration, Instant},
+};
+
+use super::{error::EvaluationError, evaluator::Evaluator, hook::Hook, object::Object};
+
+// where a function comes from: most Monkey functions are anonymous `fn` literals, so
+// they are told apart by where they are defined
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+enum Site {
+    Function(usize, usize),
+    Native(String),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct FunctionProfile {
+    // the function's name and definition site, like `add@1:11` or `fn@4:9`
+    pub label: String,
+    pub calls: u64,
+    // time spent in the function, with and without the functions it calls
+    pub inclusive: Duration,
+    pub exclusive: Duration,
+}
+
+struct Call {
+    site: Site,
+    started: Instant,
+    children: Duration,
+}
+
+// a hook that times every function call and counts calls per definition site
+#[derive(Default)]
+pub struct Profiler {
+    functions: BTreeMap<Site, FunctionProfile>,
+    calls: Vec<Call>,
+    // exclusive time per call stack, for flame graphs
+    stacks: HashMap<Vec<Site>, Duration>,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Profiler::default()
+    }
+
+    // the functions that ran, the ones that took the most time on their own first
+    pub fn functions(&self) -> Vec<FunctionProfile> {
+        let mut functions: Vec<FunctionProfile> = self.functions.values().cloned().collect();
+        functions.sort_by(|a, b| {
+            b.exclusive
+                .cmp(&a.exclusive)
+                .then(b.inclusive.cmp(&a.inclusive))
+                .then(a.label.cmp(&b.label))
+        });
+        functions
+    }
+
+    pub fn write_table(&self, output: &mut impl Write) -> io::Result<()> {
+        writeln!(
+            output,
+            "{:>8}  {:>12}  {:>12}  function",
+            "calls", "inclusive", "exclusive"
+        )?;
+
+        for function in self.functions() {
+            writeln!(
+                output,
+                "{:>8}  {:>12}  {:>12}  {}",
+                function.calls,
+                milliseconds(function.inclusive),
+                milliseconds(function.exclusive),
+                function.label
+            )?;
+        }
+
+        Ok(())
+    }
+
+    // one `outer;inner microseconds` line per call stack, the input flamegraph.pl and
+    // inferno expect
+    pub fn write_collapsed(&self, output: &mut impl Write) -> io::Result<()> {
+        let mut stacks: Vec<(String, u128)> = self
+            .stacks
+            .iter()
+            .map(|(stack, time)| {
+                let labels: Vec<&str> = stack
+                    .iter()
+                    .map(|site| self.functions[site].label.as_str())
+                    .collect();
+                (labels.join(";"), time.as_micros())
+            })
+            .filter(|(_, micros)| *micros > 0)
+            .collect();
+        stacks.sort();
+
+        for (stack, micros) in stacks {
+            writeln!(output, "{} {}", stack, micros)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Hook for Profiler {
+    fn enter_function(
+        &mut self,
+        _evaluator: &mut Evaluator,
+        function: &Object,
+        _arguments: &[Object],
+    ) {
+        let site = match function {
+            Object::Function { position, .. } => Site::Function(position.line(), position.column()),
+            function => Site::Native(function.function_name().unwrap_or_default()),
+        };
+
+        self.functions
+            .entry(site.clone())
+            .or_insert_with(|| FunctionProfile {
+                label: label(function),
+                calls: 0,
+                inclusive: Duration::ZERO,
+                exclusive: Duration::ZERO,
+            })
+            .calls += 1;
+
+        self.calls.push(Call {
+            site,
+            started: Instant::now(),
+            children: Duration::ZERO,
+        });
+    }
+
+    fn exit_function(
+        &mut self,
+        _evaluator: &mut Evaluator,
+        _result: &Result<Object, EvaluationError>,
+    ) {
+        let stack: Vec<Site> = self.calls.iter().map(|call| call.site.clone()).collect();
+        let Some(call) = self.calls.pop() else {
+            return;
+        };
+
+        let elapsed = call.started.elapsed();
+        let exclusive = elapsed.saturating_sub(call.children);
+        if let Some(caller) = self.calls.last_mut() {
+            caller.children += elapsed;
+        }
+
+        let function = self.functions.get_mut(&call.site).unwrap();
+        function.exclusive += exclusive;
+        // a recursive call's time is already part of the outermost call's
+        if !self.calls.iter().any(|caller| caller.site == call.site) {
+            function.inclusive += elapsed;
+        }
+
+        *self.stacks.entry(stack).or_default() += exclusive;
+    }
+}
+
+fn label(function: &Object) -> String {
+    match function {
+        Object::Function {
+            name: Some(name),
+            position,
+            ..
+        } => format!("{}{}", name, position.0),
+        function => function.function_name().unwrap_or_default(),
+    }
+}
+
+fn milliseconds(duration: Duration) -> String {
+    format!("{:.3}ms", duration.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use indoc::indoc;
+
+    use crate::{evaluator::environment::Environment, lexer::lexer::Lexer, parser::parser::Parser};
+
+    use super::*;
+
+    fn profile(source: &str) -> Profiler {
+        let profiler = Rc::new(RefCell::new(Profiler::new()));
+        let mut evaluator = Evaluator::new();
+        evaluator.set_hook(Box::new(Rc::clone(&profiler)));
+
+        let program = Parser::new(Lexer::new(source)).parse_program();
+        evaluator.eval(program, Environment::new()).unwrap();
+        drop(evaluator);
+
+        Rc::try_unwrap(profiler).ok().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_calls() {
+        let profiler = profile(indoc! {"
+            let fib = fn(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } };
+            let twice = fn(f) { fn(x) { f(f(x)) } };
+            twice(fn(x) { x + 1 })(fib(5));
+        "});
+
+        let mut calls: Vec<(String, u64)> = profiler
+            .functions()
+            .into_iter()
+            .map(|function| (function.label, function.calls))
+            .collect();
+        calls.sort();
+
+        assert_eq!(
+            calls,
+            vec![
+                ("fib@1:11".to_string(), 15),
+                ("fn@2:21".to_string(), 1),
+                ("fn@3:7".to_string(), 2),
+                ("twice@2:13".to_string(), 1),
+            ]
+        );
+
+        for function in profiler.functions() {
+            assert!(function.exclusive <= function.inclusive);
+        }
+    }
+
+    #[test]
+    fn test_stacks() {
+        let profiler = profile(indoc! {"
+            let inner = fn() { 1 };
+            let outer = fn() { inner() + inner() };
+            outer();
+        "});
+
+        let mut stacks: Vec<Vec<String>> = profiler
+            .stacks
+            .keys()
+            .map(|stack| {
+                stack
+                    .iter()
+                    .map(|site| profiler.functions[site].label.clone())
+                    .collect()
+            })
+            .collect();
+        stacks.sort();
+
+        assert_eq!(
+            stacks,
+            vec![
+                vec!["outer@2:13".to_string()],
+                vec!["outer@2:13".to_string(), "inner@1:13".to_string()],
+            ]
+        );
+
+        let mut table = vec![];
+        profiler.write_table(&mut table).unwrap();
+        let table = String::from_utf8(table).unwrap();
+        assert!(table.starts_with("   calls     inclusive     exclusive  function\n"));
+        assert!(table.contains("       2  "));
+    }
+}
@@ -0,0 +1,285 @@
+use std::{collections::BTreeMap, fmt};
+
+use serde::{
+    de::{self, MapAccess, SeqAccess, Visitor},
+    ser::{self, SerializeMap, SerializeSeq},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use super::object::{HashKey, Object};
+
+pub const FUNCTION_HANDLE_KEY: &str = "$function";
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum FunctionPolicy {
+    #[default]
+    Reject,
+    Opaque,
+}
+
+pub struct Serializable<'a> {
+    object: &'a Object,
+    functions: FunctionPolicy,
+}
+
+impl Object {
+    pub fn serializable(&self, functions: FunctionPolicy) -> Serializable<'_> {
+        Serializable {
+            object: self,
+            functions,
+        }
+    }
+}
+
+impl Serialize for Object {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.serializable(FunctionPolicy::default())
+            .serialize(serializer)
+    }
+}
+
+impl Serialize for Serializable<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.object {
+            Object::Integer(value) => serializer.serialize_i64(*value),
+            Object::Boolean(value) => serializer.serialize_bool(*value),
+            Object::String(value) => serializer.serialize_str(value),
+            Object::Null => serializer.serialize_unit(),
+            Object::Array(elements) => {
+                let mut seq = serializer.serialize_seq(Some(elements.len()))?;
+                for element in elements {
+                    seq.serialize_element(&element.serializable(self.functions))?;
+                }
+                seq.end()
+            }
+            Object::Hash(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, &value.serializable(self.functions))?;
+                }
+                map.end()
+            }
+            Object::ReturnValue(value) => value.serializable(self.functions).serialize(serializer),
+            Object::Error { kind, message, .. } => {
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry("kind", kind)?;
+                map.serialize_entry("message", message)?;
+                map.end()
+            }
+            Object::TailCall { .. } => Err(ser::Error::custom("cannot serialize a tail call")),
+            function @ (Object::Function { .. } | Object::NativeFunction(_)) => {
+                match self.functions {
+                    FunctionPolicy::Reject => Err(ser::Error::custom(format!(
+                        "cannot serialize function {}",
+                        describe_function(function)
+                    ))),
+                    FunctionPolicy::Opaque => {
+                        let mut map = serializer.serialize_map(Some(1))?;
+                        map.serialize_entry(FUNCTION_HANDLE_KEY, &describe_function(function))?;
+                        map.end()
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn describe_function(function: &Object) -> String {
+    match function {
+        Object::NativeFunction(native) => native.name.clone(),
+        Object::Function {
+            parameters, rest, ..
+        } => {
+            let mut parameters = parameters
+                .iter()
+                .map(|parameter| parameter.to_string())
+                .collect::<Vec<String>>();
+            if let Some(rest) = rest {
+                parameters.push(format!("...{}", rest));
+            }
+            format!("fn({})", parameters.join(", "))
+        }
+        object => object.to_string(),
+    }
+}
+
+impl Serialize for HashKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            HashKey::Integer(value) => serializer.serialize_i64(*value),
+            HashKey::Boolean(value) => serializer.serialize_bool(*value),
+            HashKey::String(value) => serializer.serialize_str(value),
+        }
+    }
+}
+
+struct ObjectVisitor;
+
+impl<'de> Visitor<'de> for ObjectVisitor {
+    type Value = Object;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "an integer, boolean, string, null, array or map")
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<Object, E> {
+        Ok(Object::Boolean(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Object, E> {
+        Ok(Object::Integer(value))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Object, E> {
+        i64::try_from(value)
+            .map(Object::Integer)
+            .map_err(|_| E::invalid_value(de::Unexpected::Unsigned(value), &self))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Object, E> {
+        Ok(Object::string(value))
+    }
+
+    fn visit_string<E: de::Error>(self, value: String) -> Result<Object, E> {
+        Ok(Object::String(value))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Object, E> {
+        Ok(Object::Null)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Object, E> {
+        Ok(Object::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Object, D::Error> {
+        Object::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Object, A::Error> {
+        let mut elements = vec![];
+        while let Some(element) = seq.next_element()? {
+            elements.push(element);
+        }
+        Ok(Object::Array(elements))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Object, A::Error> {
+        let mut entries = BTreeMap::new();
+        while let Some((key, value)) = map.next_entry()? {
+            entries.insert(key, value);
+        }
+        Ok(Object::Hash(entries))
+    }
+}
+
+impl<'de> Deserialize<'de> for Object {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ObjectVisitor)
+    }
+}
+
+struct HashKeyVisitor;
+
+impl Visitor<'_> for HashKeyVisitor {
+    type Value = HashKey;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "an integer, boolean or string hash key")
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<HashKey, E> {
+        Ok(HashKey::Boolean(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<HashKey, E> {
+        Ok(HashKey::Integer(value))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<HashKey, E> {
+        i64::try_from(value)
+            .map(HashKey::Integer)
+            .map_err(|_| E::invalid_value(de::Unexpected::Unsigned(value), &self))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<HashKey, E> {
+        Ok(HashKey::String(value.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(self, value: String) -> Result<HashKey, E> {
+        Ok(HashKey::String(value))
+    }
+}
+
+impl<'de> Deserialize<'de> for HashKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(HashKeyVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{
+        evaluator::{
+            environment::Environment,
+            evaluator::Evaluator,
+            object::{HashKey, Object},
+        },
+        lexer::lexer::Lexer,
+        parser::parser::Parser,
+    };
+
+    use super::FunctionPolicy;
+
+    fn eval(input: &str) -> Object {
+        let program = Parser::new(Lexer::new(input)).parse_program();
+        Evaluator::new().eval(program, Environment::new()).unwrap()
+    }
+
+    #[test]
+    fn test_serialize() {
+        let object =
+            eval("{\"name\": \"monkey\", \"tags\": [1, true, null], \"nested\": {\"ok\": false}}");
+        assert_eq!(
+            serde_json::to_string(&object).unwrap(),
+            r#"{"name":"monkey","nested":{"ok":false},"tags":[1,true,null]}"#
+        );
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let object: Object = serde_json::from_str(r#"{"a": [1, "two", null], "b": true}"#).unwrap();
+        assert_eq!(
+            object,
+            Object::Hash(BTreeMap::from([
+                (
+                    HashKey::String("a".to_string()),
+                    Object::Array(vec![
+                        Object::Integer(1),
+                        Object::string("two"),
+                        Object::Null
+                    ])
+                ),
+                (HashKey::String("b".to_string()), Object::Boolean(true)),
+            ]))
+        );
+
+        assert!(serde_json::from_str::<Object>("1.5").is_err());
+        assert!(serde_json::from_str::<Object>("18446744073709551615").is_err());
+    }
+
+    #[test]
+    fn test_functions() {
+        let object = eval("[fn(a, b) { a + b }]");
+
+        let error = serde_json::to_string(&object).unwrap_err();
+        assert_eq!(error.to_string(), "cannot serialize function fn(a, b)");
+
+        assert_eq!(
+            serde_json::to_string(&object.serializable(FunctionPolicy::Opaque)).unwrap(),
+            r#"[{"$function":"fn(a, b)"}]"#
+        );
+    }
+}
@@ -0,0 +1,363 @@
+use std::{cell::RefCell, io::Write, rc::Rc};
+
+#[cfg(feature = "serde")]
+use serde_json::{json, Value};
+
+use crate::{
+    lexer::location::Location,
+    parser::ast::{node::Node, statement::Statement},
+};
+
+#[cfg(feature = "serde")]
+use super::serialization::FunctionPolicy;
+use super::{
+    environment::Environment, error::EvaluationError, evaluator::Evaluator, hook::Hook,
+    object::Object,
+};
+
+// longer node sources and values are cut short so that each event fits on a line
+const MAX_TEXT_WIDTH: usize = 60;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TraceFormat {
+    Text,
+    #[cfg(feature = "serde")]
+    Json,
+}
+
+struct OpenNode {
+    text: String,
+    location: Option<Location>,
+}
+
+// a hook that logs every evaluated node with its result, and every function call with
+// its arguments and return value, indented by call depth
+pub struct Tracer<W> {
+    output: W,
+    format: TraceFormat,
+    nodes: Vec<OpenNode>,
+    calls: Vec<String>,
+    // the statement running in the program and in each call, which locates the
+    // expressions that do not record a location
+    statements: Vec<Option<Location>>,
+}
+
+impl<W: Write> Tracer<W> {
+    pub fn new(output: W, format: TraceFormat) -> Self {
+        Tracer {
+            output,
+            format,
+            nodes: vec![],
+            calls: vec![],
+            statements: vec![None],
+        }
+    }
+
+    pub fn output(&self) -> &W {
+        &self.output
+    }
+
+    fn indent(depth: usize) -> String {
+        "  ".repeat(depth)
+    }
+
+    fn log_node(
+        &mut self,
+        call_depth: usize,
+        node: OpenNode,
+        result: &Result<Object, EvaluationError>,
+    ) {
+        let depth = self.nodes.len();
+        let (line, column) = node
+            .location
+            .as_ref()
+            .map_or((0, 0), |location| (location.line(), location.column()));
+
+        let written = match self.format {
+            TraceFormat::Text => writeln!(
+                self.output,
+                "{}[{}] {}:{} {} => {}",
+                Tracer::<W>::indent(call_depth),
+                depth,
+                line,
+                column,
+                node.text,
+                describe(result)
+            ),
+            #[cfg(feature = "serde")]
+            TraceFormat::Json => {
+                let mut event = json!({
+                    "event": "node",
+                    "depth": depth,
+                    "call_depth": call_depth,
+                    "line": line,
+                    "column": column,
+                    "node": node.text,
+                });
+                add_result(&mut event, result);
+                writeln!(self.output, "{}", event)
+            }
+        };
+        written.ok();
+    }
+}
+
+impl<W: Write> Hook for Tracer<W> {
+    fn before_statement(
+        &mut self,
+        _evaluator: &mut Evaluator,
+        statement: &Statement,
+        _environment: &Rc<RefCell<Environment>>,
+    ) -> Result<(), EvaluationError> {
+        if let (Some(current), Some(location)) = (self.statements.last_mut(), statement.location())
+        {
+            *current = Some(location.clone());
+        }
+        Ok(())
+    }
+
+    fn before_node(&mut self, _evaluator: &mut Evaluator, node: &Node) {
+        let (text, location) = match node {
+            Node::Expression(expression) => (expression.to_string(), expression.location()),
+            Node::Statement(statement) => (statement.to_string(), statement.location()),
+            Node::Program(_) => return,
+        };
+
+        let location = location
+            .cloned()
+            .or_else(|| self.statements.last().cloned().flatten());
+
+        self.nodes.push(OpenNode {
+            text: shorten(&text),
+            location,
+        });
+    }
+
+    fn after_node(&mut self, evaluator: &mut Evaluator, result: &Result<Object, EvaluationError>) {
+        if let Some(node) = self.nodes.pop() {
+            self.log_node(evaluator.call_depth(), node, result);
+        }
+    }
+
+    fn enter_function(
+        &mut self,
+        evaluator: &mut Evaluator,
+        function: &Object,
+        arguments: &[Object],
+    ) {
+        let name = function.function_name().unwrap_or_default();
+        let depth = evaluator.call_depth().saturating_sub(1);
+
+        let written = match self.format {
+            TraceFormat::Text => {
+                let arguments: Vec<String> =
+                    arguments.iter().map(|a| shorten(&a.to_string())).collect();
+                writeln!(
+                    self.output,
+                    "{}-> {}({})",
+                    Tracer::<W>::indent(depth),
+                    name,
+                    arguments.join(", ")
+                )
+            }
+            #[cfg(feature = "serde")]
+            TraceFormat::Json => {
+                let arguments: Vec<Value> = arguments.iter().map(to_json).collect();
+                let event = json!({
+                    "event": "enter",
+                    "call_depth": depth,
+                    "function": name,
+                    "arguments": arguments,
+                });
+                writeln!(self.output, "{}", event)
+            }
+        };
+        written.ok();
+
+        self.calls.push(name);
+        self.statements.push(None);
+    }
+
+    fn exit_function(
+        &mut self,
+        evaluator: &mut Evaluator,
+        result: &Result<Object, EvaluationError>,
+    ) {
+        let name = self.calls.pop().unwrap_or_default();
+        if self.statements.len() > 1 {
+            self.statements.pop();
+        }
+        let depth = evaluator.call_depth().saturating_sub(1);
+
+        let written = match self.format {
+            TraceFormat::Text => writeln!(
+                self.output,
+                "{}<- {} => {}",
+                Tracer::<W>::indent(depth),
+                name,
+                describe(result)
+            ),
+            #[cfg(feature = "serde")]
+            TraceFormat::Json => {
+                let mut event = json!({"event": "exit", "call_depth": depth, "function": name});
+                add_result(&mut event, result);
+                writeln!(self.output, "{}", event)
+            }
+        };
+        written.ok();
+    }
+}
+
+fn describe(result: &Result<Object, EvaluationError>) -> String {
+    match result {
+        Ok(Object::TailCall { function, .. }) => {
+            format!(
+                "tail call to {}",
+                function.function_name().unwrap_or_default()
+            )
+        }
+        Ok(value) => shorten(&value.to_string()),
+        Err(err) => format!("error: {}", err),
+    }
+}
+
+// puts text on one line and cuts it at MAX_TEXT_WIDTH characters
+fn shorten(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+
+    match text.char_indices().nth(MAX_TEXT_WIDTH) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text,
+    }
+}
+
+#[cfg(feature = "serde")]
+fn to_json(value: &Object) -> Value {
+    match value {
+        Object::TailCall { function, .. } => {
+            json!({"tail_call": function.function_name().unwrap_or_default()})
+        }
+        value => serde_json::to_value(value.serializable(FunctionPolicy::Opaque))
+            .unwrap_or_else(|_| json!(value.to_string())),
+    }
+}
+
+#[cfg(feature = "serde")]
+fn add_result(event: &mut Value, result: &Result<Object, EvaluationError>) {
+    match result {
+        Ok(value) => event["result"] = to_json(value),
+        Err(err) => event["error"] = json!(err.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::{lexer::lexer::Lexer, parser::parser::Parser};
+
+    use super::*;
+
+    fn trace(source: &str, format: TraceFormat) -> String {
+        let tracer = Rc::new(RefCell::new(Tracer::new(vec![], format)));
+        let mut evaluator = Evaluator::new();
+        evaluator.set_hook(Box::new(Rc::clone(&tracer)));
+
+        let program = Parser::new(Lexer::new(source)).parse_program();
+        evaluator.eval(program, Environment::new()).unwrap();
+
+        let output = tracer.borrow().output().clone();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_text() {
+        let source = indoc! {"
+            let double = fn(n) { n * 2 };
+            double(2 + 1);
+        "};
+
+        assert_eq!(
+            trace(source, TraceFormat::Text),
+            indoc! {"
+                [1] 1:14 fn(n) { (n * 2) } => fn(n) { (n * 2) }
+                [0] 1:1 let double = fn(n) { (n * 2) } => fn(n) { (n * 2) }
+                [2] 2:1 double => fn(n) { (n * 2) }
+                [3] 2:1 2 => 2
+                [3] 2:1 1 => 1
+                [2] 2:1 (2 + 1) => 3
+                -> double(3)
+                  [4] 1:22 n => 3
+                  [4] 1:22 2 => 2
+                  [3] 1:22 (n * 2) => 6
+                  [2] 1:22 (n * 2) => 6
+                <- double => 6
+                [1] 2:1 double((2 + 1)) => 6
+                [0] 2:1 double((2 + 1)) => 6
+            "}
+        );
+    }
+
+    #[test]
+    fn test_tail_if() {
+        let source = indoc! {"
+            let sign = fn(n) { if (n < 0) { -1 } else { 1 } };
+            sign(5);
+        "};
+
+        assert_eq!(
+            trace(source, TraceFormat::Text),
+            indoc! {"
+                [1] 1:12 fn(n) { if (n < 0) { (-1) } else { 1 } } => fn(n) { if (n < 0) { (-1) } else { 1 } }
+                [0] 1:1 let sign = fn(n) { if (n < 0) { (-1) } else { 1 } } => fn(n) { if (n < 0) { (-1) } else { 1 } }
+                [2] 2:1 sign => fn(n) { if (n < 0) { (-1) } else { 1 } }
+                [2] 2:1 5 => 5
+                -> sign(5)
+                  [5] 1:24 n => 5
+                  [5] 1:20 0 => 0
+                  [4] 1:20 (n < 0) => false
+                  [5] 1:45 1 => 1
+                  [4] 1:45 1 => 1
+                  [3] 1:20 if (n < 0) { (-1) } else { 1 } => 1
+                  [2] 1:20 if (n < 0) { (-1) } else { 1 } => 1
+                <- sign => 1
+                [1] 2:1 sign(5) => 1
+                [0] 2:1 sign(5) => 1
+            "}
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_json() {
+        let output = trace("let f = fn(x) { [x] }; f(1);", TraceFormat::Json);
+        let events: Vec<Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        let calls: Vec<&Value> = events
+            .iter()
+            .filter(|event| event["event"] != "node")
+            .collect();
+        assert_eq!(
+            calls,
+            [
+                &json!({"event": "enter", "call_depth": 0, "function": "f", "arguments": [1]}),
+                &json!({"event": "exit", "call_depth": 0, "function": "f", "result": [1]}),
+            ]
+        );
+        assert_eq!(
+            events[0],
+            json!({
+                "event": "node",
+                "depth": 1,
+                "call_depth": 0,
+                "line": 1,
+                "column": 9,
+                "node": "fn(x) { [x] }",
+                "result": {"$function": "fn(x)"},
+            })
+        );
+    }
+}
@@ -0,0 +1,161 @@
+#[derive(Debug, PartialEq, Clone)]
+pub enum Doc {
+    Text(String),
+    // a space when its group fits on the line, a line break otherwise
+    Line,
+    // nothing when its group fits on the line, a line break otherwise
+    SoftLine,
+    HardLine,
+    Nest(Box<Doc>),
+    Group(Box<Doc>),
+    Concat(Vec<Doc>),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+type Command<'a> = (usize, Mode, &'a Doc);
+
+impl Doc {
+    pub fn text(text: impl Into<String>) -> Self {
+        Doc::Text(text.into())
+    }
+
+    pub fn nest(doc: Doc) -> Self {
+        Doc::Nest(Box::new(doc))
+    }
+
+    pub fn group(doc: Doc) -> Self {
+        Doc::Group(Box::new(doc))
+    }
+
+    pub fn concat(docs: impl IntoIterator<Item = Doc>) -> Self {
+        Doc::Concat(docs.into_iter().collect())
+    }
+
+    pub fn render(&self, indent_width: usize, line_width: usize) -> String {
+        let mut output = String::new();
+        let mut column = 0;
+        // indentation is written lazily so that blank lines carry no trailing spaces
+        let mut pending_indent = None;
+        let mut commands: Vec<Command> = vec![(0, Mode::Break, self)];
+
+        while let Some((indent, mode, doc)) = commands.pop() {
+            match doc {
+                Doc::Text(text) => {
+                    if let Some(indent) = pending_indent.take() {
+                        output.push_str(&" ".repeat(indent));
+                    }
+                    output.push_str(text);
+                    column = match text.rfind('\n') {
+                        Some(index) => text[index + 1..].chars().count(),
+                        None => column + text.chars().count(),
+                    };
+                }
+                Doc::Line if mode == Mode::Flat => {
+                    output.push(' ');
+                    column += 1;
+                }
+                Doc::SoftLine if mode == Mode::Flat => {}
+                Doc::Line | Doc::SoftLine | Doc::HardLine => {
+                    output.push('\n');
+                    pending_indent = Some(indent);
+                    column = indent;
+                }
+                Doc::Nest(doc) => commands.push((indent + indent_width, mode, doc)),
+                Doc::Group(doc) => {
+                    let remaining = line_width as isize - column as isize;
+                    let mode = match Doc::fits(remaining, (indent, Mode::Flat, doc), &commands) {
+                        true => Mode::Flat,
+                        false => Mode::Break,
+                    };
+                    commands.push((indent, mode, doc));
+                }
+                Doc::Concat(docs) => {
+                    commands.extend(docs.iter().rev().map(|doc| (indent, mode, doc)));
+                }
+            }
+        }
+
+        output
+    }
+
+    // whether everything up to the next line break fits in `remaining` columns
+    fn fits(mut remaining: isize, next: Command, rest: &[Command]) -> bool {
+        let mut commands = vec![next];
+        let mut rest = rest.iter().rev();
+
+        while remaining >= 0 {
+            let Some((indent, mode, doc)) = commands.pop().or_else(|| rest.next().copied()) else {
+                return true;
+            };
+
+            match doc {
+                Doc::Text(text) => match text.find('\n') {
+                    Some(index) => return text[..index].chars().count() as isize <= remaining,
+                    None => remaining -= text.chars().count() as isize,
+                },
+                Doc::Line | Doc::SoftLine if mode == Mode::Break => return true,
+                Doc::Line => remaining -= 1,
+                Doc::SoftLine => {}
+                Doc::HardLine => return true,
+                Doc::Nest(doc) | Doc::Group(doc) => commands.push((indent, mode, doc)),
+                Doc::Concat(docs) => {
+                    commands.extend(docs.iter().rev().map(|doc| (indent, mode, doc)));
+                }
+            }
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Doc;
+
+    fn list(items: &[&str]) -> Doc {
+        let mut inner = vec![Doc::SoftLine];
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                inner.push(Doc::text(","));
+                inner.push(Doc::Line);
+            }
+            inner.push(Doc::text(*item));
+        }
+
+        Doc::group(Doc::concat([
+            Doc::text("["),
+            Doc::nest(Doc::concat(inner)),
+            Doc::SoftLine,
+            Doc::text("]"),
+        ]))
+    }
+
+    #[test]
+    fn test_group_fits() {
+        assert_eq!(list(&["1", "2", "3"]).render(4, 80), "[1, 2, 3]");
+    }
+
+    #[test]
+    fn test_group_breaks() {
+        assert_eq!(
+            list(&["alpha", "beta", "gamma"]).render(2, 10),
+            "[\n  alpha,\n  beta,\n  gamma\n]"
+        );
+    }
+
+    #[test]
+    fn test_hard_line() {
+        let doc = Doc::concat([
+            Doc::text("{"),
+            Doc::nest(Doc::concat([Doc::HardLine, Doc::text("x"), Doc::HardLine])),
+            Doc::HardLine,
+            Doc::text("}"),
+        ]);
+        assert_eq!(doc.render(4, 80), "{\n    x\n\n}");
+    }
+}
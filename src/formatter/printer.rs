@@ -0,0 +1,742 @@
+use std::{iter::Peekable, vec::IntoIter};
+
+use crate::{
+    lexer::{lexer::Lexer, location::Location, token::Comment},
+    parser::{
+        ast::{
+            expression::{Expression, MatchArm, Parameter},
+            program::Program,
+            statement::Statement,
+        },
+        error::ParserError,
+        parser::Parser,
+        precedence::Precedence,
+        source_map::{List, SourceMap, Span},
+    },
+};
+
+use super::doc::Doc;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatOptions {
+    pub indent_width: usize,
+    pub line_width: usize,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            indent_width: 4,
+            line_width: 80,
+        }
+    }
+}
+
+pub fn format(source: &str, options: &FormatOptions) -> Result<String, Vec<ParserError>> {
+    let mut parser = Parser::new(Lexer::new(source));
+    let program = parser.parse_program();
+
+    if !parser.errors.is_empty() {
+        return Err(parser.errors);
+    }
+
+    let doc = Printer::new(parser.source_map).program(&program);
+    let mut output = doc.render(options.indent_width, options.line_width);
+    if !output.is_empty() {
+        output.push('\n');
+    }
+
+    Ok(output)
+}
+
+struct Printer {
+    statements: Peekable<IntoIter<Span>>,
+    bodies: Peekable<IntoIter<Option<Location>>>,
+    lists: IntoIter<List>,
+    comments: Peekable<IntoIter<Comment>>,
+}
+
+// a printed line of a statement list, and whether a blank line precedes it
+type Line = (bool, Doc);
+
+impl Printer {
+    fn new(source_map: SourceMap) -> Self {
+        Printer {
+            statements: source_map.statements.into_iter().peekable(),
+            bodies: source_map.bodies.into_iter().peekable(),
+            lists: source_map.lists.into_iter(),
+            comments: source_map.comments.into_iter().peekable(),
+        }
+    }
+
+    fn program(&mut self, program: &Program) -> Doc {
+        let end_of_file = Location::new(usize::MAX, usize::MAX);
+        let lines = self.statements(&program.statements, true, Some(&end_of_file));
+
+        Printer::join_lines(lines)
+    }
+
+    fn statements(
+        &mut self,
+        statements: &[Statement],
+        top_level: bool,
+        close: Option<&Location>,
+    ) -> Vec<Line> {
+        let mut lines = vec![];
+        let mut last_line = None;
+
+        for (index, statement) in statements.iter().enumerate() {
+            let span = self
+                .statements
+                .next()
+                .expect("source map out of sync with the program");
+
+            for comment in self.take_comments_before(&span.start) {
+                Printer::push_comment(&mut lines, &mut last_line, comment);
+            }
+
+            let blank = Printer::is_blank_before(last_line, span.start.line());
+            let mut doc = vec![self.statement(statement)];
+
+            // the last expression of a block is its value and reads better without a semicolon;
+            // top-level statements always need one since the parser resyncs on semicolons
+            if top_level
+                || index + 1 < statements.len()
+                || !matches!(statement, Statement::Expression(..))
+            {
+                doc.push(Doc::text(";"));
+            }
+
+            // comments inside an expression have no statement to attach to, so they move above it
+            let inner = self.take_comments_before(&Location::new(span.end.line(), 0));
+            let blank_after_inner = blank && inner.is_empty();
+            for (i, comment) in inner.into_iter().enumerate() {
+                lines.push((blank && i == 0, Printer::comment(&comment)));
+            }
+
+            // a comment after a closing brace or before another statement on the same line
+            // is not this statement's
+            let next = self.statements.peek().map(|next| next.start.clone());
+            let limit = match (close, next.as_ref()) {
+                (Some(close), Some(next)) if Printer::is_before(next, close) => Some(next),
+                (Some(close), _) => Some(close),
+                (None, next) => next,
+            };
+            doc.extend(self.trailing_comment(span.end.line(), limit));
+            lines.push((blank_after_inner, Doc::concat(doc)));
+            last_line = Some(span.end.line());
+        }
+
+        if let Some(close) = close {
+            for comment in self.take_comments_before(close) {
+                Printer::push_comment(&mut lines, &mut last_line, comment);
+            }
+        }
+
+        lines
+    }
+
+    fn statement(&mut self, statement: &Statement) -> Doc {
+        match statement {
+            Statement::Let { pattern, value, .. } => Doc::concat([
+                Doc::text(format!("let {} = ", pattern)),
+                self.expression(value),
+            ]),
+            Statement::Return { value, .. } => {
+                Doc::concat([Doc::text("return "), self.expression(value)])
+            }
+            Statement::Throw { value, .. } => {
+                Doc::concat([Doc::text("throw "), self.expression(value)])
+            }
+            Statement::Expression(expression, _) => self.expression(expression),
+            // only built by the evaluator, never by the parser
+            Statement::Block(statements) => {
+                Doc::concat(statements.iter().flat_map(|statement| {
+                    [self.statement(statement), Doc::text(";"), Doc::HardLine]
+                }))
+            }
+        }
+    }
+
+    fn block(&mut self, statements: &[Statement]) -> Doc {
+        self.block_within(statements, None)
+    }
+
+    // `end` bounds the comments of a body that has no closing brace of its own
+    fn block_within(&mut self, statements: &[Statement], end: Option<&Location>) -> Doc {
+        let close = self.bodies.next().flatten();
+        let lines = self.statements(statements, false, close.as_ref().or(end));
+
+        if lines.is_empty() {
+            return Doc::text("{}");
+        }
+
+        Doc::concat([
+            Doc::text("{"),
+            Doc::nest(Doc::concat([Doc::HardLine, Printer::join_lines(lines)])),
+            Doc::HardLine,
+            Doc::text("}"),
+        ])
+    }
+
+    fn expression(&mut self, expression: &Expression) -> Doc {
+        match expression {
+            Expression::Int(value) => Doc::text(value.to_string()),
+            Expression::Bool(value) => Doc::text(value.to_string()),
+            Expression::Str(value) => Doc::text(format!("\"{}\"", value)),
+            Expression::Identifier(identifier, _) => Doc::text(identifier),
+            Expression::Null => Doc::text("null"),
+            Expression::Array(elements) => {
+                self.list("[", elements.len(), "]", false, |printer, i, _| {
+                    printer.expression(&elements[i])
+                })
+            }
+            Expression::Hash(entries) => {
+                self.list("{", entries.len(), "}", false, |printer, i, _| {
+                    let (key, value) = &entries[i];
+                    Doc::concat([
+                        printer.expression(key),
+                        Doc::text(": "),
+                        printer.expression(value),
+                    ])
+                })
+            }
+            Expression::If {
+                condition,
+                consequence,
+                alternative,
+                ..
+            } => {
+                let mut doc = vec![
+                    Doc::text("if ("),
+                    self.expression(condition),
+                    Doc::text(") "),
+                    self.block(consequence),
+                ];
+                if let Some(alternative) = alternative {
+                    doc.push(Doc::text(" else "));
+                    doc.push(self.block(alternative));
+                }
+                Doc::concat(doc)
+            }
+            Expression::Function {
+                parameters,
+                rest,
+                body,
+                ..
+            } => {
+                let count = parameters.len() + usize::from(rest.is_some());
+                let parameters =
+                    self.list("(", count, ")", false, |printer, i, _| {
+                        match parameters.get(i) {
+                            Some(parameter) => printer.parameter(parameter),
+                            None => {
+                                Doc::text(format!("...{}", rest.as_deref().unwrap_or_default()))
+                            }
+                        }
+                    });
+                Doc::concat([
+                    Doc::text("fn"),
+                    parameters,
+                    Doc::text(" "),
+                    self.block(body),
+                ])
+            }
+            Expression::Call {
+                function,
+                arguments,
+                named_arguments,
+            } => {
+                let function = self.operand(function, Precedence::CALL, false);
+                let count = arguments.len() + named_arguments.len();
+                let arguments = self.list("(", count, ")", false, |printer, i, _| match arguments
+                    .get(i)
+                {
+                    Some(argument) => printer.expression(argument),
+                    None => {
+                        let (name, argument) = &named_arguments[i - arguments.len()];
+                        Doc::concat([
+                            Doc::text(format!("{}: ", name)),
+                            printer.expression(argument),
+                        ])
+                    }
+                });
+                Doc::concat([function, arguments])
+            }
+            Expression::Prefix { rhs, operator } => Doc::concat([
+                Doc::text(operator.to_string()),
+                self.operand(rhs, Precedence::PREFIX, true),
+            ]),
+            Expression::Infix { lhs, operator, rhs } => {
+                let precedence = Precedence::from(operator);
+                Doc::concat([
+                    self.operand(lhs, precedence, false),
+                    Doc::text(format!(" {} ", operator)),
+                    self.operand(rhs, precedence, true),
+                ])
+            }
+            Expression::Match { subject, arms, .. } => {
+                let subject = self.expression(subject);
+                let arms = self.list("{", arms.len(), "}", true, |printer, i, span| {
+                    printer.match_arm(&arms[i], &span.end)
+                });
+                Doc::concat([Doc::text("match ("), subject, Doc::text(") "), arms])
+            }
+            Expression::Index { lhs, index } => Doc::concat([
+                self.operand(lhs, Precedence::CALL, false),
+                Doc::text("["),
+                self.expression(index),
+                Doc::text("]"),
+            ]),
+            Expression::Try {
+                body,
+                catch,
+                finally,
+            } => {
+                let mut doc = vec![Doc::text("try "), self.block(body)];
+                if let Some(catch) = catch {
+                    doc.push(Doc::text(format!(" catch ({}) ", catch.pattern)));
+                    doc.push(self.block(&catch.body));
+                }
+                if let Some(finally) = finally {
+                    doc.push(Doc::text(" finally "));
+                    doc.push(self.block(finally));
+                }
+                Doc::concat(doc)
+            }
+        }
+    }
+
+    // parenthesizes `expression` when it binds looser than its position allows
+    fn operand(&mut self, expression: &Expression, minimum: Precedence, strict: bool) -> Doc {
+        let precedence = match expression {
+            Expression::Infix { operator, .. } => Some(Precedence::from(operator)),
+            Expression::Prefix { .. } => Some(Precedence::PREFIX),
+            _ => None,
+        };
+        let doc = self.expression(expression);
+
+        match precedence {
+            Some(precedence) if precedence < minimum || (strict && precedence == minimum) => {
+                Doc::concat([Doc::text("("), doc, Doc::text(")")])
+            }
+            _ => doc,
+        }
+    }
+
+    fn parameter(&mut self, parameter: &Parameter) -> Doc {
+        match &parameter.default {
+            Some(default) => Doc::concat([
+                Doc::text(format!("{} = ", parameter.pattern)),
+                self.expression(default),
+            ]),
+            None => Doc::text(parameter.pattern.to_string()),
+        }
+    }
+
+    fn match_arm(&mut self, arm: &MatchArm, end: &Location) -> Doc {
+        let mut doc = vec![Doc::text(arm.pattern.to_string())];
+        if let Some(guard) = &arm.guard {
+            doc.push(Doc::text(" if "));
+            doc.push(self.expression(guard));
+        }
+        doc.push(Doc::text(" => "));
+
+        let close = self.bodies.peek().cloned().flatten();
+        let span = self.statements.peek().cloned();
+
+        // a lone expression is written without braces unless that would lose a comment
+        // or turn a hash literal into a block
+        if let ([Statement::Expression(expression, _)], Some(span)) = (arm.body.as_slice(), &span) {
+            let limit = close.clone().unwrap_or_else(|| span.end.clone());
+            let has_comments = self
+                .comments
+                .peek()
+                .is_some_and(|comment| Printer::is_before(&comment.location, &limit));
+
+            if !has_comments && !matches!(expression, Expression::Hash(_)) {
+                self.bodies.next();
+                self.statements.next();
+                doc.push(self.expression(expression));
+                return Doc::concat(doc);
+            }
+        }
+
+        doc.push(self.block_within(&arm.body, Some(end)));
+        Doc::concat(doc)
+    }
+
+    // prints a comma-separated list on one line when it fits and one item per line
+    // otherwise; comments keep their place next to the items, which needs the latter
+    fn list(
+        &mut self,
+        open: &str,
+        count: usize,
+        close: &str,
+        always_break: bool,
+        mut item: impl FnMut(&mut Self, usize, &Span) -> Doc,
+    ) -> Doc {
+        let list = self
+            .lists
+            .next()
+            .expect("source map out of sync with the program");
+
+        let mut items = vec![];
+        let mut has_comments = false;
+        for (index, span) in list.items.iter().enumerate().take(count) {
+            let leading = self.take_comments_before(&span.start);
+            let doc = item(self, index, span);
+            let next = list
+                .items
+                .get(index + 1)
+                .map_or(&list.close, |next| &next.start);
+            let trailing = self.trailing_comments(&span.end, next);
+            has_comments |= !leading.is_empty() || !trailing.is_empty();
+            items.push((leading, doc, trailing));
+        }
+        let remaining = self.take_comments_before(&list.close);
+        has_comments |= !remaining.is_empty();
+
+        if items.is_empty() && !has_comments {
+            return Doc::text(format!("{}{}", open, close));
+        }
+
+        if !always_break && !has_comments {
+            let mut inner = vec![Doc::SoftLine];
+            for (index, (_, doc, _)) in items.into_iter().enumerate() {
+                if index > 0 {
+                    inner.push(Doc::text(","));
+                    inner.push(Doc::Line);
+                }
+                inner.push(doc);
+            }
+
+            return Doc::group(Doc::concat([
+                Doc::text(open),
+                Doc::nest(Doc::concat(inner)),
+                Doc::SoftLine,
+                Doc::text(close),
+            ]));
+        }
+
+        let mut inner = vec![];
+        let last = items.len().saturating_sub(1);
+        for (index, (leading, doc, trailing)) in items.into_iter().enumerate() {
+            for comment in leading {
+                inner.push(Doc::HardLine);
+                inner.push(Printer::comment(&comment));
+            }
+            inner.push(Doc::HardLine);
+            inner.push(doc);
+            if index < last {
+                inner.push(Doc::text(","));
+            }
+            for (i, comment) in trailing.iter().enumerate() {
+                inner.push(if i == 0 {
+                    Doc::text(" ")
+                } else {
+                    Doc::HardLine
+                });
+                inner.push(Printer::comment(comment));
+            }
+        }
+        for comment in remaining {
+            inner.push(Doc::HardLine);
+            inner.push(Printer::comment(&comment));
+        }
+
+        Doc::concat([
+            Doc::text(open),
+            Doc::nest(Doc::concat(inner)),
+            Doc::HardLine,
+            Doc::text(close),
+        ])
+    }
+
+    fn comment(comment: &Comment) -> Doc {
+        Doc::text(format!("//{}", comment.text))
+    }
+
+    fn trailing_comment(&mut self, line: usize, limit: Option<&Location>) -> Option<Doc> {
+        self.comments
+            .next_if(|comment| {
+                comment.location.line() == line
+                    && limit.is_none_or(|limit| Printer::is_before(&comment.location, limit))
+            })
+            .map(|comment| Doc::concat([Doc::text(" "), Printer::comment(&comment)]))
+    }
+
+    // the comments inside a list item, and the one after the comma that ends it on its line
+    fn trailing_comments(&mut self, end: &Location, next: &Location) -> Vec<Comment> {
+        let mut comments = self.take_comments_before(end);
+        comments.extend(self.comments.next_if(|comment| {
+            comment.location.line() == end.line() && Printer::is_before(&comment.location, next)
+        }));
+        comments
+    }
+
+    fn take_comments_before(&mut self, location: &Location) -> Vec<Comment> {
+        let mut comments = vec![];
+        while let Some(comment) = self
+            .comments
+            .next_if(|comment| Printer::is_before(&comment.location, location))
+        {
+            comments.push(comment);
+        }
+        comments
+    }
+
+    fn push_comment(lines: &mut Vec<Line>, last_line: &mut Option<usize>, comment: Comment) {
+        let line = comment.location.line();
+        lines.push((
+            Printer::is_blank_before(*last_line, line),
+            Printer::comment(&comment),
+        ));
+        *last_line = Some(line);
+    }
+
+    fn is_blank_before(last_line: Option<usize>, line: usize) -> bool {
+        last_line.is_some_and(|last_line| line > last_line + 1)
+    }
+
+    fn is_before(location: &Location, other: &Location) -> bool {
+        (location.line(), location.column()) < (other.line(), other.column())
+    }
+
+    fn join_lines(lines: Vec<Line>) -> Doc {
+        let mut docs = vec![];
+        for (index, (blank, doc)) in lines.into_iter().enumerate() {
+            if index > 0 {
+                docs.push(Doc::HardLine);
+                if blank {
+                    docs.push(Doc::HardLine);
+                }
+            }
+            docs.push(doc);
+        }
+        Doc::concat(docs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::{lexer::lexer::Lexer, parser::parser::Parser};
+
+    use super::{format, FormatOptions};
+
+    fn sexpr(source: &str) -> String {
+        Parser::new(Lexer::new(source)).parse_program().to_sexpr()
+    }
+
+    fn assert_formats(source: &str, expected: &str) {
+        let formatted = format(source, &FormatOptions::default()).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(sexpr(&formatted), sexpr(source), "AST changed");
+        assert_eq!(
+            format(&formatted, &FormatOptions::default()).unwrap(),
+            formatted,
+            "formatting is not idempotent"
+        );
+    }
+
+    #[test]
+    fn test_statements() {
+        assert_formats(
+            "let   x=1;return x+ 2 ;throw \"boom\";puts(x)",
+            indoc! {r#"
+                let x = 1;
+                return x + 2;
+                throw "boom";
+                puts(x);
+            "#},
+        );
+    }
+
+    #[test]
+    fn test_precedence() {
+        assert_formats(
+            "(1 + 2) * 3; 1 + (2 * 3); 1 - (2 - 3); (1 - 2) - 3; -(a + b); (-a)(b); f(x)[0]; -f(x);",
+            indoc! {"
+                (1 + 2) * 3;
+                1 + 2 * 3;
+                1 - (2 - 3);
+                1 - 2 - 3;
+                -(a + b);
+                (-a)(b);
+                f(x)[0];
+                -f(x);
+            "},
+        );
+    }
+
+    #[test]
+    fn test_blocks() {
+        assert_formats(
+            "let f = fn(a, b = 2, ...rest) { let c = a + b; if (c > 2) { c } else { rest } }; fn() {};",
+            indoc! {"
+                let f = fn(a, b = 2, ...rest) {
+                    let c = a + b;
+                    if (c > 2) {
+                        c
+                    } else {
+                        rest
+                    }
+                };
+                fn() {};
+            "},
+        );
+    }
+
+    #[test]
+    fn test_match_and_try() {
+        assert_formats(
+            "match (x) { [h, ..t] if h > 0 => h, {a} => { {\"a\": a} }, _ => { let y = 1; y } }; try { f() } catch ({kind}) { kind } finally { cleanup() };",
+            indoc! {r#"
+                match (x) {
+                    [h, ..t] if h > 0 => h,
+                    {a} => {
+                        {"a": a}
+                    },
+                    _ => {
+                        let y = 1;
+                        y
+                    }
+                };
+                try {
+                    f()
+                } catch ({kind}) {
+                    kind
+                } finally {
+                    cleanup()
+                };
+            "#},
+        );
+    }
+
+    #[test]
+    fn test_line_width() {
+        assert_formats(
+            "let values = [\"alpha\", \"beta\", \"gamma\", \"delta\", \"epsilon\", \"zeta\", \"eta\", \"theta\"];",
+            indoc! {r#"
+                let values = [
+                    "alpha",
+                    "beta",
+                    "gamma",
+                    "delta",
+                    "epsilon",
+                    "zeta",
+                    "eta",
+                    "theta"
+                ];
+            "#},
+        );
+
+        let options = FormatOptions {
+            indent_width: 2,
+            line_width: 16,
+        };
+        assert_eq!(
+            format("let f = fn(x) { g(first, second) };", &options).unwrap(),
+            indoc! {"
+                let f = fn(x) {
+                  g(
+                    first,
+                    second
+                  )
+                };
+            "}
+        );
+    }
+
+    #[test]
+    fn test_comments() {
+        assert_formats(
+            indoc! {"
+                // leading
+                let x = 1; // trailing
+
+
+                // about f
+                let f = fn() {
+                    // inside
+                    x // value
+                    // before close
+                };
+                let xs = [
+                    1, // one
+                    2
+                ];
+                match (x) {
+                    1 => 2, // arm
+                    _ => 3
+                };
+                // end
+            "},
+            indoc! {"
+                // leading
+                let x = 1; // trailing
+
+                // about f
+                let f = fn() {
+                    // inside
+                    x // value
+                    // before close
+                };
+                let xs = [
+                    1, // one
+                    2
+                ];
+                match (x) {
+                    1 => 2, // arm
+                    _ => 3
+                };
+                // end
+            "},
+        );
+    }
+
+    #[test]
+    fn test_comments_stay_in_their_scope() {
+        assert_formats(
+            indoc! {"
+                let f = fn(x) { x }; // after f
+                if (x) { 1 } else { // in else
+                    2
+                };
+                f(
+                    // first
+                    a,
+                    b // last
+                );
+                match (x) { _ => 1 }; // after match
+            "},
+            indoc! {"
+                let f = fn(x) {
+                    x
+                }; // after f
+                if (x) {
+                    1
+                } else {
+                    // in else
+                    2
+                };
+                f(
+                    // first
+                    a,
+                    b // last
+                );
+                match (x) {
+                    _ => 1
+                }; // after match
+            "},
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(format("let = 1;", &FormatOptions::default()).is_err());
+        assert_eq!(format("", &FormatOptions::default()).unwrap(), "");
+    }
+}
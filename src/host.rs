@@ -0,0 +1,319 @@
+use std::{
+    cell::RefCell,
+    fmt::Display,
+    io::Write,
+    rc::Rc,
+    sync::{atomic::AtomicBool, Arc},
+};
+
+use crate::{
+    evaluator::{
+        environment::Environment,
+        error::{EvaluationError, EvaluationErrorKind},
+        evaluator::Evaluator,
+        hook::Hook,
+        limits::{EvalLimits, EvalUsage},
+        native::NativeFunction,
+        object::Object,
+        trace::TraceFormat,
+    },
+    lexer::lexer::Lexer,
+    parser::{error::ParserError, parser::Parser},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    Parse(Vec<ParserError>),
+    Evaluation(EvaluationError),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Parse(errors) => write!(
+                f,
+                "{}",
+                errors
+                    .iter()
+                    .map(|error| error.to_string())
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            ),
+            Error::Evaluation(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<EvaluationError> for Error {
+    fn from(error: EvaluationError) -> Self {
+        Error::Evaluation(error)
+    }
+}
+
+pub struct Interpreter {
+    environment: Rc<RefCell<Environment>>,
+    evaluator: Evaluator,
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Interpreter::with_limits(EvalLimits::default())
+    }
+
+    pub fn with_limits(limits: EvalLimits) -> Self {
+        Interpreter {
+            environment: Environment::new(),
+            evaluator: Evaluator::with_limits(limits),
+        }
+    }
+
+    pub fn eval_str(&mut self, input: &str) -> Result<Object, Error> {
+        let mut parser = Parser::new(Lexer::new(input));
+        let program = parser.parse_program();
+
+        if !parser.errors.is_empty() {
+            return Err(Error::Parse(parser.errors));
+        }
+
+        // the limits are a budget for each evaluation, not for the interpreter's lifetime
+        self.evaluator.reset_usage();
+        match self.evaluator.eval(program, Rc::clone(&self.environment))? {
+            Object::ReturnValue(value) => Ok(*value),
+            value => Ok(value),
+        }
+    }
+
+    pub fn set_global(&mut self, name: &str, value: Object) {
+        self.environment.borrow_mut().set(name, value);
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<Object> {
+        self.environment.borrow().get(name)
+    }
+
+    pub fn register(&mut self, function: NativeFunction) {
+        self.environment.borrow_mut().register(function);
+    }
+
+    pub fn call_function(&mut self, name: &str, arguments: Vec<Object>) -> Result<Object, Error> {
+        let Some(function) = self.get_global(name) else {
+            return Err(EvaluationError::new(EvaluationErrorKind::UnboundIdentifier(
+                name.to_string(),
+            ))
+            .into());
+        };
+
+        self.call(&function, arguments)
+    }
+
+    pub fn call(&mut self, function: &Object, arguments: Vec<Object>) -> Result<Object, Error> {
+        self.evaluator.reset_usage();
+        Ok(self.evaluator.call(function, arguments)?)
+    }
+
+    pub fn set_interrupt_handle(&mut self, interrupted: Arc<AtomicBool>) {
+        self.evaluator.set_interrupt_handle(interrupted);
+    }
+
+    // what the last eval_str or call used
+    pub fn usage(&self) -> &EvalUsage {
+        self.evaluator.usage()
+    }
+
+    pub fn set_hook(&mut self, hook: Box<dyn Hook>) {
+        self.evaluator.set_hook(hook);
+    }
+
+    pub fn set_trace(&mut self, output: impl Write + 'static, format: TraceFormat) {
+        self.evaluator.set_trace(output, format);
+    }
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Interpreter::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use indoc::indoc;
+
+    use crate::{
+        evaluator::{
+            error::EvaluationErrorKind,
+            limits::EvalLimits,
+            native::{Arity, NativeFunction},
+            object::Object,
+        },
+        parser::error::ParserErrorKind,
+    };
+
+    use super::{Error, Interpreter};
+
+    #[test]
+    fn test_eval_str_keeps_globals() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(
+            interpreter.eval_str("let x = 2;").unwrap(),
+            Object::Integer(2)
+        );
+        assert_eq!(interpreter.eval_str("x * 21").unwrap(), Object::Integer(42));
+        assert_eq!(
+            interpreter.eval_str("return x;").unwrap(),
+            Object::Integer(2)
+        );
+    }
+
+    #[test]
+    fn test_eval_str_errors() {
+        let mut interpreter = Interpreter::new();
+
+        let Err(Error::Parse(errors)) = interpreter.eval_str("let = 1;") else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            errors[0].kind,
+            ParserErrorKind::UnexpectedToken { .. }
+        ));
+
+        let Err(Error::Evaluation(error)) = interpreter.eval_str("1 / 0") else {
+            panic!("expected an evaluation error");
+        };
+        assert_eq!(*error.kind, EvaluationErrorKind::DivisionByZero);
+    }
+
+    #[test]
+    fn test_limits_apply_to_each_evaluation() {
+        let mut interpreter = Interpreter::with_limits(EvalLimits {
+            max_steps: Some(100),
+            ..EvalLimits::default()
+        });
+
+        for _ in 0..100 {
+            assert_eq!(interpreter.eval_str("1 + 1").unwrap(), Object::Integer(2));
+        }
+        assert!(interpreter.usage().steps < 100);
+    }
+
+    #[test]
+    fn test_globals() {
+        let mut interpreter = Interpreter::new();
+
+        interpreter.set_global("limit", Object::Integer(10));
+        assert_eq!(
+            interpreter.eval_str("limit + 1").unwrap(),
+            Object::Integer(11)
+        );
+
+        interpreter.eval_str("let name = \"monkey\";").unwrap();
+        assert_eq!(
+            interpreter.get_global("name"),
+            Some(Object::string("monkey"))
+        );
+        assert_eq!(interpreter.get_global("missing"), None);
+    }
+
+    #[test]
+    fn test_call_function() {
+        let mut interpreter = Interpreter::new();
+        interpreter
+            .eval_str(indoc! {"
+                let allowed = fn(user, limit = 3) {
+                    user[\"requests\"] < limit
+                };
+            "})
+            .unwrap();
+
+        let user = interpreter.eval_str("{\"requests\": 2}").unwrap();
+        assert_eq!(
+            interpreter.call_function("allowed", vec![user]).unwrap(),
+            Object::Boolean(true)
+        );
+
+        let Err(Error::Evaluation(error)) = interpreter.call_function("missing", vec![]) else {
+            panic!("expected an evaluation error");
+        };
+        assert_eq!(
+            *error.kind,
+            EvaluationErrorKind::UnboundIdentifier("missing".to_string())
+        );
+
+        let Err(Error::Evaluation(error)) = interpreter.call_function("allowed", vec![]) else {
+            panic!("expected an evaluation error");
+        };
+        assert_eq!(
+            *error.kind,
+            EvaluationErrorKind::ArityMismatch {
+                min: 1,
+                max: Some(2),
+                got: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_register() {
+        let mut interpreter = Interpreter::new();
+        interpreter.register(NativeFunction::new(
+            "lookup_quota",
+            Arity::exact(1),
+            |arguments| match &arguments[0] {
+                Object::String(user) if user == "alice" => Ok(Object::Integer(5)),
+                _ => Ok(Object::Null),
+            },
+        ));
+
+        assert_eq!(
+            interpreter.eval_str("lookup_quota(\"alice\")").unwrap(),
+            Object::Integer(5)
+        );
+        assert_eq!(
+            interpreter.eval_str("lookup_quota").unwrap().to_string(),
+            "native fn lookup_quota"
+        );
+    }
+
+    #[test]
+    fn test_callbacks() {
+        let handlers = Rc::new(RefCell::new(vec![]));
+        let mut interpreter = Interpreter::new();
+        interpreter.register(NativeFunction::new("on_event", Arity::exact(1), {
+            let handlers = Rc::clone(&handlers);
+            move |arguments| {
+                handlers.borrow_mut().push(arguments[0].clone());
+                Ok(Object::Null)
+            }
+        }));
+
+        interpreter
+            .eval_str(indoc! {"
+                let offset = 1;
+                on_event(fn(e) { e * 2 + offset });
+                on_event(fn(e, scale = 10) { e * scale });
+            "})
+            .unwrap();
+
+        let results = handlers
+            .borrow()
+            .iter()
+            .map(|handler| interpreter.call(handler, vec![Object::Integer(4)]))
+            .collect::<Result<Vec<Object>, Error>>()
+            .unwrap();
+        assert_eq!(results, vec![Object::Integer(9), Object::Integer(40)]);
+
+        let Err(Error::Evaluation(error)) = interpreter.call(&Object::Integer(1), vec![]) else {
+            panic!("expected an evaluation error");
+        };
+        assert_eq!(
+            *error.kind,
+            EvaluationErrorKind::NotCallable(Object::Integer(1))
+        );
+    }
+}
@@ -2,13 +2,14 @@ use std::iter::Peekable;
 use std::str::Chars;
 
 use super::location::Location;
-use super::token::{Token, TokenType};
+use super::token::{Comment, Token, TokenType};
 
 pub struct Lexer<'a> {
     chars: Peekable<Chars<'a>>,
     ch: Option<char>,
     line: usize,
     column: usize,
+    comments: Vec<Comment>,
 }
 
 impl<'a> Lexer<'a> {
@@ -18,6 +19,7 @@ impl<'a> Lexer<'a> {
             ch: None,
             line: 1,
             column: 0,
+            comments: vec![],
         };
 
         lexer.read_char();
@@ -28,6 +30,11 @@ impl<'a> Lexer<'a> {
     pub fn next_token(&mut self) -> Token {
         self.skip_whitespace();
 
+        while self.ch == Some('/') && self.peek_char() == Some(&'/') {
+            self.read_comment();
+            self.skip_whitespace();
+        }
+
         let location = Location::new(self.line, self.column);
 
         let token_type = match self.ch {
@@ -36,7 +43,27 @@ impl<'a> Lexer<'a> {
             Some(')') => TokenType::RParen,
             Some('{') => TokenType::LBrace,
             Some('}') => TokenType::RBrace,
+            Some('[') => TokenType::LBracket,
+            Some(']') => TokenType::RBracket,
+            Some(':') => TokenType::Colon,
             Some(';') => TokenType::Semicolon,
+            Some('.') => match self.peek_char() {
+                Some('.') => {
+                    self.read_char();
+                    match self.peek_char() {
+                        Some('.') => {
+                            self.read_char();
+                            TokenType::Ellipsis
+                        }
+                        _ => TokenType::DotDot,
+                    }
+                }
+                _ => TokenType::Illegal('.'),
+            },
+            Some('"') => match self.read_string() {
+                Some(string) => TokenType::string(string),
+                None => TokenType::Illegal('"'),
+            },
             Some('!') => match self.peek_char() {
                 Some('=') => {
                     self.read_char();
@@ -44,14 +71,17 @@ impl<'a> Lexer<'a> {
                 }
                 _ => TokenType::Bang,
             },
-            Some('=') => {
-                if let Some('=') = self.peek_char() {
+            Some('=') => match self.peek_char() {
+                Some('=') => {
                     self.read_char();
                     TokenType::Eq
-                } else {
-                    TokenType::Assign
                 }
-            }
+                Some('>') => {
+                    self.read_char();
+                    TokenType::FatArrow
+                }
+                _ => TokenType::Assign,
+            },
             Some('*') => TokenType::Asterisk,
             Some('/') => TokenType::Slash,
             Some('+') => TokenType::Plus,
@@ -74,6 +104,11 @@ impl<'a> Lexer<'a> {
                     "else" => TokenType::Else,
                     "return" => TokenType::Return,
                     "null" => TokenType::Null,
+                    "match" => TokenType::Match,
+                    "throw" => TokenType::Throw,
+                    "try" => TokenType::Try,
+                    "catch" => TokenType::Catch,
+                    "finally" => TokenType::Finally,
                     _ => TokenType::identifier(word),
                 };
 
@@ -88,6 +123,10 @@ impl<'a> Lexer<'a> {
         return Token::new(token_type, location);
     }
 
+    pub fn take_comments(&mut self) -> Vec<Comment> {
+        std::mem::take(&mut self.comments)
+    }
+
     fn peek_char(&mut self) -> Option<&char> {
         self.chars.peek()
     }
@@ -135,6 +174,46 @@ impl<'a> Lexer<'a> {
         return number;
     }
 
+    // None when the input ends before the closing quote
+    fn read_string(&mut self) -> Option<String> {
+        let mut string = String::new();
+
+        self.read_char();
+
+        while let Some(ch) = self.ch {
+            if ch == '"' {
+                return Some(string);
+            }
+
+            string.push(ch);
+            self.read_char();
+        }
+
+        None
+    }
+
+    fn read_comment(&mut self) {
+        let location = Location::new(self.line, self.column);
+        let mut text = String::new();
+
+        self.read_char();
+        self.read_char();
+
+        while let Some(ch) = self.ch {
+            if ch == '\n' {
+                break;
+            }
+
+            text.push(ch);
+            self.read_char();
+        }
+
+        self.comments.push(Comment {
+            text: text.trim_end().to_string(),
+            location,
+        });
+    }
+
     fn read_word(&mut self) -> String {
         let mut word = String::new();
 
@@ -209,6 +288,85 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_read_string() {
+        let mut lexer = Lexer::new("\"foo bar\" \"\"");
+
+        assert_eq!(
+            lexer.next_token(),
+            Token::new(TokenType::string("foo bar"), Location::new(1, 1))
+        );
+        assert_eq!(
+            lexer.next_token(),
+            Token::new(TokenType::string(""), Location::new(1, 11))
+        );
+        assert_eq!(
+            lexer.next_token(),
+            Token::new(TokenType::EOF, Location::new(1, 12))
+        );
+    }
+
+    #[test]
+    fn test_read_unterminated_string() {
+        let mut lexer = Lexer::new("let s = \"foo");
+
+        lexer.next_token();
+        lexer.next_token();
+        lexer.next_token();
+        assert_eq!(
+            lexer.next_token(),
+            Token::new(TokenType::Illegal('"'), Location::new(1, 9))
+        );
+        assert_eq!(
+            lexer.next_token(),
+            Token::new(TokenType::EOF, Location::new(1, 12))
+        );
+    }
+
+    #[test]
+    fn test_read_comment() {
+        let mut lexer = Lexer::new(indoc! {"
+            // leading
+            let x = 1; // trailing
+            10 / 2
+        "});
+
+        let tokens = std::iter::from_fn(|| match lexer.next_token() {
+            Token {
+                token_type: TokenType::EOF,
+                ..
+            } => None,
+            token => Some(token.token_type),
+        })
+        .collect::<Vec<TokenType>>();
+        assert_eq!(
+            tokens,
+            vec![
+                TokenType::Let,
+                TokenType::identifier("x"),
+                TokenType::Assign,
+                TokenType::integer("1"),
+                TokenType::Semicolon,
+                TokenType::integer("10"),
+                TokenType::Slash,
+                TokenType::integer("2"),
+            ]
+        );
+        assert_eq!(
+            lexer.take_comments(),
+            vec![
+                Comment {
+                    text: " leading".to_string(),
+                    location: Location::new(1, 1),
+                },
+                Comment {
+                    text: " trailing".to_string(),
+                    location: Location::new(2, 12),
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_read_integer() {
         let mut lexer = Lexer::new("1234\n6789");
@@ -352,6 +510,12 @@ mod tests {
             10 != 9;
             10 % 3;
             x == null;
+            \"foo\";
+            [1, 2];
+            {\"a\": 1};
+            match (x) { [a, ..b] => a }
+            fn(...rest) {}
+            try { throw e; } catch (e) {} finally {}
         "});
 
         let expected_token_types = vec![
@@ -436,6 +600,56 @@ mod tests {
             TokenType::Eq,
             TokenType::Null,
             TokenType::Semicolon,
+            TokenType::string("foo"),
+            TokenType::Semicolon,
+            TokenType::LBracket,
+            TokenType::integer("1"),
+            TokenType::Comma,
+            TokenType::integer("2"),
+            TokenType::RBracket,
+            TokenType::Semicolon,
+            TokenType::LBrace,
+            TokenType::string("a"),
+            TokenType::Colon,
+            TokenType::integer("1"),
+            TokenType::RBrace,
+            TokenType::Semicolon,
+            TokenType::Match,
+            TokenType::LParen,
+            TokenType::identifier("x"),
+            TokenType::RParen,
+            TokenType::LBrace,
+            TokenType::LBracket,
+            TokenType::identifier("a"),
+            TokenType::Comma,
+            TokenType::DotDot,
+            TokenType::identifier("b"),
+            TokenType::RBracket,
+            TokenType::FatArrow,
+            TokenType::identifier("a"),
+            TokenType::RBrace,
+            TokenType::Function,
+            TokenType::LParen,
+            TokenType::Ellipsis,
+            TokenType::identifier("rest"),
+            TokenType::RParen,
+            TokenType::LBrace,
+            TokenType::RBrace,
+            TokenType::Try,
+            TokenType::LBrace,
+            TokenType::Throw,
+            TokenType::identifier("e"),
+            TokenType::Semicolon,
+            TokenType::RBrace,
+            TokenType::Catch,
+            TokenType::LParen,
+            TokenType::identifier("e"),
+            TokenType::RParen,
+            TokenType::LBrace,
+            TokenType::RBrace,
+            TokenType::Finally,
+            TokenType::LBrace,
+            TokenType::RBrace,
             TokenType::EOF,
         ];
 
@@ -17,6 +17,12 @@ impl Token {
     }
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub struct Comment {
+    pub text: String,
+    pub location: Location,
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum TokenType {
     Let,
@@ -48,6 +54,18 @@ pub enum TokenType {
     NotEq,
     Modulo,
     Null,
+    String(String),
+    LBracket,
+    RBracket,
+    Colon,
+    DotDot,
+    Ellipsis,
+    FatArrow,
+    Match,
+    Throw,
+    Try,
+    Catch,
+    Finally,
 }
 
 impl TokenType {
@@ -58,6 +76,10 @@ impl TokenType {
     pub fn integer(integer: impl Into<String>) -> TokenType {
         TokenType::Integer(integer.into())
     }
+
+    pub fn string(string: impl Into<String>) -> TokenType {
+        TokenType::String(string.into())
+    }
 }
 
 impl Display for TokenType {
@@ -92,6 +114,18 @@ impl Display for TokenType {
             TokenType::NotEq => write!(f, "!="),
             TokenType::Modulo => write!(f, "%"),
             TokenType::Null => write!(f, "null"),
+            TokenType::String(string) => write!(f, "string \"{}\"", string),
+            TokenType::LBracket => write!(f, "["),
+            TokenType::RBracket => write!(f, "]"),
+            TokenType::Colon => write!(f, ":"),
+            TokenType::DotDot => write!(f, ".."),
+            TokenType::Ellipsis => write!(f, "..."),
+            TokenType::FatArrow => write!(f, "=>"),
+            TokenType::Match => write!(f, "match"),
+            TokenType::Throw => write!(f, "throw"),
+            TokenType::Try => write!(f, "try"),
+            TokenType::Catch => write!(f, "catch"),
+            TokenType::Finally => write!(f, "finally"),
         }
     }
 }
@@ -0,0 +1,17 @@
+pub mod debugger;
+pub mod evaluator;
+pub mod formatter;
+pub mod lexer;
+pub mod linter;
+pub mod parser;
+#[cfg(feature = "serde")]
+pub mod rpc;
+pub mod testing;
+
+mod host;
+
+pub use evaluator::object::Object as Value;
+pub use host::{Error, Interpreter};
+
+// lets the derive macros refer to `::interpreter` from inside this crate too
+extern crate self as interpreter;
@@ -0,0 +1,430 @@
+use crate::{
+    lexer::{lexer::Lexer, location::Location, token::Comment},
+    parser::{
+        ast::{expression::Expression, program::Program, statement::Statement},
+        error::ParserError,
+        parser::Parser,
+    },
+};
+
+use super::{
+    symbols::{ScopeKind, SymbolKind, SymbolTable},
+    warning::{Code, Warning},
+};
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LintOptions {
+    // names the host defines before running the program, such as registered natives
+    pub globals: Vec<String>,
+}
+
+pub fn lint(source: &str, options: &LintOptions) -> Result<Vec<Warning>, Vec<ParserError>> {
+    let mut parser = Parser::new(Lexer::new(source));
+    let program = parser.parse_program();
+
+    if !parser.errors.is_empty() {
+        return Err(parser.errors);
+    }
+
+    Ok(lint_program(&program, &parser.source_map.comments, options))
+}
+
+pub fn lint_program(
+    program: &Program,
+    comments: &[Comment],
+    options: &LintOptions,
+) -> Vec<Warning> {
+    let table = SymbolTable::new(program);
+    let mut warnings = vec![];
+
+    for symbol in &table.symbols {
+        if let Some(shadowed) = symbol.shadows {
+            warnings.push(Warning::new(
+                Code::ShadowedVariable,
+                &symbol.location,
+                format!(
+                    "`{}` shadows the binding {}",
+                    symbol.name, table.symbols[shadowed].location
+                ),
+            ));
+        }
+
+        if !symbol.references.is_empty() || symbol.name.starts_with('_') {
+            continue;
+        }
+
+        // top-level bindings stay readable by the host through get_global
+        let code = match symbol.kind {
+            SymbolKind::Let if table.scopes[symbol.scope].kind != ScopeKind::Program => {
+                Code::UnusedVariable
+            }
+            SymbolKind::Let => continue,
+            SymbolKind::Parameter => Code::UnusedParameter,
+            SymbolKind::Pattern => Code::UnusedVariable,
+        };
+        warnings.push(Warning::new(
+            code,
+            &symbol.location,
+            format!("`{}` is never used", symbol.name),
+        ));
+    }
+
+    for unresolved in table
+        .unresolved
+        .iter()
+        .filter(|unresolved| unresolved.called)
+    {
+        let defined = options.globals.contains(&unresolved.name)
+            || table
+                .symbols
+                .iter()
+                .any(|symbol| symbol.name == unresolved.name);
+
+        if !defined {
+            warnings.push(Warning::new(
+                Code::UndefinedFunction,
+                &unresolved.location,
+                format!("`{}` is called but never defined", unresolved.name),
+            ));
+        }
+    }
+
+    let mut checker = Checker::default();
+    checker.statements(&program.statements);
+    warnings.extend(checker.warnings);
+
+    warnings.retain(|warning| !is_allowed(warning, comments));
+    warnings.sort_by_key(|warning| (warning.location.line(), warning.location.column()));
+    warnings
+}
+
+// a `// lint: allow(W001, shadowed-variable)` comment silences the listed warnings
+// on its own line and on the line that follows it
+fn is_allowed(warning: &Warning, comments: &[Comment]) -> bool {
+    let line = warning.location.line();
+
+    comments
+        .iter()
+        .filter(|comment| comment.location.line() == line || comment.location.line() + 1 == line)
+        .filter_map(|comment| {
+            comment
+                .text
+                .trim()
+                .strip_prefix("lint: allow(")
+                .and_then(|list| list.strip_suffix(')'))
+        })
+        .flat_map(|list| list.split(','))
+        .any(|code| Code::parse(code.trim()) == Some(warning.code))
+}
+
+// the checks that look at control flow rather than at names
+#[derive(Default)]
+struct Checker<'a> {
+    // the statement being checked, for warnings about nodes without a position
+    statement: Option<&'a Location>,
+    warnings: Vec<Warning>,
+}
+
+impl<'a> Checker<'a> {
+    fn statements(&mut self, statements: &'a [Statement]) {
+        let mut returned = false;
+
+        for statement in statements {
+            if returned {
+                if let Some(location) = statement.location() {
+                    self.warnings.push(Warning::new(
+                        Code::UnreachableCode,
+                        location,
+                        "statement is never reached",
+                    ));
+                }
+                returned = false;
+            } else if matches!(
+                statement,
+                Statement::Return { .. } | Statement::Throw { .. }
+            ) {
+                returned = true;
+            }
+
+            self.statement(statement);
+        }
+    }
+
+    fn statement(&mut self, statement: &'a Statement) {
+        let outer = self.statement.take();
+        self.statement = statement.location().or(outer);
+
+        match statement {
+            Statement::Let { value, .. }
+            | Statement::Return { value, .. }
+            | Statement::Throw { value, .. }
+            | Statement::Expression(value, _) => self.expression(value),
+            Statement::Block(statements) => self.statements(statements),
+        }
+
+        self.statement = outer;
+    }
+
+    fn expression(&mut self, expression: &'a Expression) {
+        match expression {
+            Expression::Int(_)
+            | Expression::Bool(_)
+            | Expression::Str(_)
+            | Expression::Null
+            | Expression::Identifier(..) => {}
+            Expression::Array(elements) => {
+                elements.iter().for_each(|element| self.expression(element))
+            }
+            Expression::Hash(entries) => {
+                for (key, value) in entries {
+                    self.expression(key);
+                    self.expression(value);
+                }
+            }
+            Expression::If {
+                condition,
+                consequence,
+                alternative,
+                ..
+            } => {
+                if is_constant(condition) {
+                    if let Some(location) = self.statement {
+                        self.warnings.push(Warning::new(
+                            Code::ConstantCondition,
+                            location,
+                            format!("`if` condition `{}` is always the same", condition),
+                        ));
+                    }
+                }
+                self.expression(condition);
+                self.statements(consequence);
+                if let Some(alternative) = alternative {
+                    self.statements(alternative);
+                }
+            }
+            Expression::Function {
+                parameters, body, ..
+            } => {
+                for default in parameters.iter().filter_map(|p| p.default.as_ref()) {
+                    self.expression(default);
+                }
+                self.statements(body);
+            }
+            Expression::Call {
+                function,
+                arguments,
+                named_arguments,
+            } => {
+                self.expression(function);
+                arguments
+                    .iter()
+                    .for_each(|argument| self.expression(argument));
+                named_arguments
+                    .iter()
+                    .for_each(|(_, argument)| self.expression(argument));
+            }
+            Expression::Prefix { rhs, .. } => self.expression(rhs),
+            Expression::Infix { lhs, rhs, .. } | Expression::Index { lhs, index: rhs } => {
+                self.expression(lhs);
+                self.expression(rhs);
+            }
+            Expression::Match { subject, arms, .. } => {
+                self.expression(subject);
+                for arm in arms {
+                    if let Some(guard) = &arm.guard {
+                        self.expression(guard);
+                    }
+                    self.statements(&arm.body);
+                }
+            }
+            Expression::Try {
+                body,
+                catch,
+                finally,
+            } => {
+                self.statements(body);
+                if let Some(catch) = catch {
+                    self.statements(&catch.body);
+                }
+                if let Some(finally) = finally {
+                    self.statements(finally);
+                }
+            }
+        }
+    }
+}
+
+// literals and operators applied to literals, which evaluate the same way every time
+fn is_constant(expression: &Expression) -> bool {
+    match expression {
+        Expression::Int(_) | Expression::Bool(_) | Expression::Str(_) | Expression::Null => true,
+        Expression::Prefix { rhs, .. } => is_constant(rhs),
+        Expression::Infix { lhs, rhs, .. } => is_constant(lhs) && is_constant(rhs),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    fn warnings(source: &str) -> Vec<(Code, usize, usize)> {
+        lint(source, &LintOptions::default())
+            .unwrap()
+            .into_iter()
+            .map(|warning| {
+                (
+                    warning.code,
+                    warning.location.line(),
+                    warning.location.column(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_unused_bindings() {
+        let source = indoc! {"
+            let unused_global = 1;
+            let f = fn(a, b, _c, ...rest) {
+                let x = 1;
+                let [y, z] = [a, 2];
+                y
+            };
+            f(1, 2);
+            match (f(3)) { [head, ..tail] => head, {key} => 1, _rest => 2 };
+            try { f(4) } catch (e) { 0 };
+        "};
+
+        assert_eq!(
+            warnings(source),
+            vec![
+                (Code::UnusedParameter, 2, 9),
+                (Code::UnusedParameter, 2, 15),
+                (Code::UnusedVariable, 3, 9),
+                (Code::UnusedVariable, 4, 13),
+                (Code::UnusedVariable, 8, 25),
+                (Code::UnusedVariable, 8, 41),
+                (Code::UnusedVariable, 9, 21),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_closures_see_later_bindings() {
+        let source = indoc! {"
+            let f = fn() {
+                let even = fn(n) { if (n == 0) { true } else { odd(n - 1) } };
+                let odd = fn(n) { if (n == 0) { false } else { even(n - 1) } };
+                even(4)
+            };
+            f();
+        "};
+
+        assert_eq!(warnings(source), vec![]);
+    }
+
+    #[test]
+    fn test_shadowing() {
+        let source = indoc! {"
+            let x = 1;
+            let f = fn(x) {
+                match (x) { [x] => { x }, _ => { 0 } }
+            };
+            let x = 2;
+            f(x);
+        "};
+
+        assert_eq!(
+            warnings(source),
+            vec![
+                (Code::ShadowedVariable, 2, 12),
+                (Code::ShadowedVariable, 3, 18)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unreachable_code() {
+        let source = indoc! {"
+            let f = fn() {
+                return 1;
+                let dead = 2;
+                dead
+            };
+            f();
+        "};
+
+        assert_eq!(warnings(source), vec![(Code::UnreachableCode, 3, 5)]);
+    }
+
+    #[test]
+    fn test_constant_condition() {
+        let source = indoc! {"
+            let x = 1;
+            if (!(1 > 2)) { x };
+            if (x) { x };
+        "};
+
+        assert_eq!(warnings(source), vec![(Code::ConstantCondition, 2, 1)]);
+    }
+
+    #[test]
+    fn test_undefined_function() {
+        let source = indoc! {"
+            let f = fn() { g(1) + h(2) };
+            let h = fn(n) { n };
+            f();
+            puts(f());
+        "};
+
+        assert_eq!(
+            warnings(source),
+            vec![
+                (Code::UndefinedFunction, 1, 16),
+                (Code::UndefinedFunction, 4, 1)
+            ]
+        );
+
+        let options = LintOptions {
+            globals: vec!["g".to_string(), "puts".to_string()],
+        };
+        assert_eq!(lint(source, &options).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_allow_comments() {
+        let source = indoc! {"
+            let f = fn(unused) { // lint: allow(W002)
+                // lint: allow(unused-variable, W006)
+                let x = g();
+                return 1;
+                2 // lint: allow(unreachable-code)
+            };
+            f();
+        "};
+
+        assert_eq!(warnings(source), vec![]);
+    }
+
+    #[test]
+    fn test_display() {
+        let warnings = lint("missing();", &LintOptions::default()).unwrap();
+        assert_eq!(
+            warnings[0].to_string(),
+            "1:1: warning[W006]: `missing` is called but never defined (undefined-function)"
+        );
+
+        let warnings = lint(
+            "let x = 1; let f = fn(x) { x }; f(x);",
+            &LintOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            warnings[0].to_string(),
+            "1:23: warning[W003]: `x` shadows the binding @1:5 (shadowed-variable)"
+        );
+    }
+}
@@ -0,0 +1,418 @@
+use crate::{
+    lexer::location::Location,
+    parser::ast::{
+        expression::Expression, pattern::Pattern, program::Program, statement::Statement,
+    },
+};
+
+pub type ScopeId = usize;
+pub type SymbolId = usize;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ScopeKind {
+    Program,
+    Function,
+    // the bindings of a match arm or catch clause
+    Pattern,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SymbolKind {
+    Let,
+    Parameter,
+    Pattern,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Scope {
+    pub kind: ScopeKind,
+    pub parent: Option<ScopeId>,
+    // where the function literal opening a function scope starts
+    pub location: Option<Location>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub scope: ScopeId,
+    pub location: Location,
+    pub references: Vec<Location>,
+    // the outer binding this one hides
+    pub shadows: Option<SymbolId>,
+    // `fn(a, b = 2)` when the binding is initialized with a function literal
+    pub signature: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Unresolved {
+    pub name: String,
+    pub location: Location,
+    pub called: bool,
+}
+
+// the bindings of a program and the uses that refer to them, resolved the way the
+// evaluator looks names up in its environments
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct SymbolTable {
+    pub scopes: Vec<Scope>,
+    pub symbols: Vec<Symbol>,
+    pub unresolved: Vec<Unresolved>,
+}
+
+impl SymbolTable {
+    pub fn new(program: &Program) -> Self {
+        let mut builder = Builder::default();
+
+        builder.open(ScopeKind::Program, None);
+        builder.statements(&program.statements);
+        builder.close();
+
+        builder.table
+    }
+
+    // the symbol defined or referenced by the identifier covering the given position
+    pub fn symbol_at(&self, line: usize, column: usize) -> Option<SymbolId> {
+        let covers = |name: &str, location: &Location| {
+            location.line() == line
+                && (location.column()..location.column() + name.chars().count()).contains(&column)
+        };
+
+        self.symbols.iter().position(|symbol| {
+            covers(&symbol.name, &symbol.location)
+                || symbol
+                    .references
+                    .iter()
+                    .any(|reference| covers(&symbol.name, reference))
+        })
+    }
+
+    // the symbols of a scope and of every scope around it, innermost and latest first
+    pub fn visible(&self, scope: ScopeId) -> Vec<&Symbol> {
+        let mut visible: Vec<&Symbol> = vec![];
+        let mut current = Some(scope);
+
+        while let Some(scope) = current {
+            for symbol in self
+                .symbols
+                .iter()
+                .rev()
+                .filter(|symbol| symbol.scope == scope)
+            {
+                if !visible.iter().any(|seen| seen.name == symbol.name) {
+                    visible.push(symbol);
+                }
+            }
+            current = self.scopes[scope].parent;
+        }
+
+        visible
+    }
+}
+
+#[derive(Default)]
+struct Builder<'a> {
+    table: SymbolTable,
+    // the open scopes, each with the function literals it defines; function bodies run
+    // after the surrounding scope has been set up, so they are resolved when it closes
+    // and see every binding it ends up with
+    stack: Vec<(ScopeId, Vec<&'a Expression>)>,
+}
+
+impl<'a> Builder<'a> {
+    fn open(&mut self, kind: ScopeKind, location: Option<Location>) {
+        let parent = self.stack.last().map(|(scope, _)| *scope);
+
+        self.table.scopes.push(Scope {
+            kind,
+            parent,
+            location,
+        });
+        self.stack.push((self.table.scopes.len() - 1, vec![]));
+    }
+
+    fn close(&mut self) {
+        while let Some(function) = self
+            .stack
+            .last_mut()
+            .and_then(|(_, functions)| functions.pop())
+        {
+            self.function(function);
+        }
+
+        self.stack.pop();
+    }
+
+    fn lookup(&self, name: &str, scopes: &[(ScopeId, Vec<&'a Expression>)]) -> Option<SymbolId> {
+        scopes.iter().rev().find_map(|(scope, _)| {
+            self.table
+                .symbols
+                .iter()
+                .rposition(|symbol| symbol.scope == *scope && symbol.name == name)
+        })
+    }
+
+    fn declare(&mut self, pattern: &Pattern, kind: SymbolKind, signature: Option<String>) {
+        match pattern {
+            Pattern::Identifier(name, position) => self.bind(name, &position.0, kind, signature),
+            Pattern::Array { elements, rest } => {
+                for element in elements.iter().chain(rest.as_deref()) {
+                    self.declare(element, kind, None);
+                }
+            }
+            Pattern::Hash(entries) => {
+                for (_, pattern) in entries {
+                    self.declare(pattern, kind, None);
+                }
+            }
+            Pattern::Wildcard
+            | Pattern::Int(_)
+            | Pattern::Bool(_)
+            | Pattern::Str(_)
+            | Pattern::Null => {}
+        }
+    }
+
+    fn bind(
+        &mut self,
+        name: &str,
+        location: &Location,
+        kind: SymbolKind,
+        signature: Option<String>,
+    ) {
+        let Some(((scope, _), outer)) = self.stack.split_last() else {
+            return;
+        };
+
+        let symbol = Symbol {
+            name: name.to_string(),
+            kind,
+            scope: *scope,
+            location: location.clone(),
+            references: vec![],
+            shadows: self.lookup(name, outer),
+            signature,
+        };
+        self.table.symbols.push(symbol);
+    }
+
+    fn reference(&mut self, name: &str, location: &Location, called: bool) {
+        match self.lookup(name, &self.stack) {
+            Some(symbol) => self.table.symbols[symbol].references.push(location.clone()),
+            None => self.table.unresolved.push(Unresolved {
+                name: name.to_string(),
+                location: location.clone(),
+                called,
+            }),
+        }
+    }
+
+    fn statements(&mut self, statements: &'a [Statement]) {
+        for statement in statements {
+            match statement {
+                Statement::Let { pattern, value, .. } => {
+                    self.expression(value);
+                    let signature = match value {
+                        Expression::Function {
+                            parameters, rest, ..
+                        } => Some(format!(
+                            "fn({})",
+                            parameters
+                                .iter()
+                                .map(|parameter| parameter.to_string())
+                                .chain(rest.iter().map(|rest| format!("...{}", rest)))
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        )),
+                        _ => None,
+                    };
+                    self.declare(pattern, SymbolKind::Let, signature);
+                }
+                Statement::Return { value, .. }
+                | Statement::Throw { value, .. }
+                | Statement::Expression(value, _) => self.expression(value),
+                Statement::Block(statements) => self.statements(statements),
+            }
+        }
+    }
+
+    fn expression(&mut self, expression: &'a Expression) {
+        match expression {
+            Expression::Int(_) | Expression::Bool(_) | Expression::Str(_) | Expression::Null => {}
+            Expression::Identifier(name, position) => self.reference(name, &position.0, false),
+            Expression::Array(elements) => {
+                elements.iter().for_each(|element| self.expression(element))
+            }
+            Expression::Hash(entries) => {
+                for (key, value) in entries {
+                    self.expression(key);
+                    self.expression(value);
+                }
+            }
+            Expression::If {
+                condition,
+                consequence,
+                alternative,
+                ..
+            } => {
+                self.expression(condition);
+                self.statements(consequence);
+                if let Some(alternative) = alternative {
+                    self.statements(alternative);
+                }
+            }
+            Expression::Function { .. } => {
+                if let Some((_, functions)) = self.stack.last_mut() {
+                    functions.push(expression);
+                }
+            }
+            Expression::Call {
+                function,
+                arguments,
+                named_arguments,
+            } => {
+                match function.as_ref() {
+                    Expression::Identifier(name, position) => {
+                        self.reference(name, &position.0, true)
+                    }
+                    function => self.expression(function),
+                }
+                arguments
+                    .iter()
+                    .for_each(|argument| self.expression(argument));
+                named_arguments
+                    .iter()
+                    .for_each(|(_, argument)| self.expression(argument));
+            }
+            Expression::Prefix { rhs, .. } => self.expression(rhs),
+            Expression::Infix { lhs, rhs, .. } | Expression::Index { lhs, index: rhs } => {
+                self.expression(lhs);
+                self.expression(rhs);
+            }
+            Expression::Match { subject, arms, .. } => {
+                self.expression(subject);
+                for arm in arms {
+                    self.open(ScopeKind::Pattern, None);
+                    self.declare(&arm.pattern, SymbolKind::Pattern, None);
+                    if let Some(guard) = &arm.guard {
+                        self.expression(guard);
+                    }
+                    self.statements(&arm.body);
+                    self.close();
+                }
+            }
+            Expression::Try {
+                body,
+                catch,
+                finally,
+            } => {
+                self.statements(body);
+                if let Some(catch) = catch {
+                    self.open(ScopeKind::Pattern, None);
+                    self.declare(&catch.pattern, SymbolKind::Pattern, None);
+                    self.statements(&catch.body);
+                    self.close();
+                }
+                if let Some(finally) = finally {
+                    self.statements(finally);
+                }
+            }
+        }
+    }
+
+    fn function(&mut self, function: &'a Expression) {
+        let Expression::Function {
+            parameters,
+            rest,
+            body,
+            position,
+        } = function
+        else {
+            return;
+        };
+
+        self.open(ScopeKind::Function, Some(position.0.clone()));
+        for parameter in parameters {
+            if let Some(default) = &parameter.default {
+                self.expression(default);
+            }
+            self.declare(&parameter.pattern, SymbolKind::Parameter, None);
+        }
+        if let Some(rest) = rest {
+            self.bind(rest, &position.0, SymbolKind::Parameter, None);
+        }
+        self.statements(body);
+        self.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::{lexer::lexer::Lexer, parser::parser::Parser};
+
+    use super::*;
+
+    fn table(source: &str) -> SymbolTable {
+        let mut parser = Parser::new(Lexer::new(source));
+        let program = parser.parse_program();
+        assert_eq!(parser.errors, vec![]);
+        SymbolTable::new(&program)
+    }
+
+    #[test]
+    fn test_resolution() {
+        let table = table(indoc! {"
+            let add = fn(a, b = 1) { a + b };
+            let x = add(x, 2);
+            let x = 3;
+            missing(x);
+        "});
+
+        let names = |ids: Vec<&Symbol>| ids.iter().map(|s| s.name.clone()).collect::<Vec<_>>();
+        assert_eq!(
+            names(table.symbols.iter().collect()),
+            ["add", "x", "x", "a", "b"]
+        );
+
+        let add = &table.symbols[0];
+        assert_eq!(add.signature.as_deref(), Some("fn(a, b = 1)"));
+        assert_eq!(add.references, vec![Location::new(2, 9)]);
+        assert_eq!(table.symbols[2].references, vec![Location::new(4, 9)]);
+        assert_eq!(table.symbols[3].references, vec![Location::new(1, 26)]);
+
+        assert_eq!(
+            table.unresolved,
+            vec![
+                Unresolved {
+                    name: "x".to_string(),
+                    location: Location::new(2, 13),
+                    called: false,
+                },
+                Unresolved {
+                    name: "missing".to_string(),
+                    location: Location::new(4, 1),
+                    called: true,
+                },
+            ]
+        );
+
+        assert_eq!(table.symbol_at(1, 26), Some(3));
+        assert_eq!(table.symbol_at(2, 11), Some(0));
+        assert_eq!(table.symbol_at(2, 12), None);
+        assert_eq!(names(table.visible(1)), ["b", "a", "x", "add"]);
+    }
+
+    #[test]
+    fn test_shadowing() {
+        let table = table(indoc! {"
+            let x = 1;
+            let f = fn(x) { try { x } catch (x) { x } };
+        "});
+
+        let shadows: Vec<_> = table.symbols.iter().map(|symbol| symbol.shadows).collect();
+        assert_eq!(shadows, [None, None, Some(0), Some(2)]);
+        assert_eq!(table.scopes[1].location, Some(Location::new(2, 9)));
+    }
+}
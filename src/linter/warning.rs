@@ -0,0 +1,103 @@
+use std::fmt::Display;
+
+use crate::lexer::location::Location;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Code {
+    UnusedVariable,
+    UnusedParameter,
+    ShadowedVariable,
+    UnreachableCode,
+    ConstantCondition,
+    UndefinedFunction,
+}
+
+impl Code {
+    pub const ALL: [Code; 6] = [
+        Code::UnusedVariable,
+        Code::UnusedParameter,
+        Code::ShadowedVariable,
+        Code::UnreachableCode,
+        Code::ConstantCondition,
+        Code::UndefinedFunction,
+    ];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Code::UnusedVariable => "W001",
+            Code::UnusedParameter => "W002",
+            Code::ShadowedVariable => "W003",
+            Code::UnreachableCode => "W004",
+            Code::ConstantCondition => "W005",
+            Code::UndefinedFunction => "W006",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Code::UnusedVariable => "unused-variable",
+            Code::UnusedParameter => "unused-parameter",
+            Code::ShadowedVariable => "shadowed-variable",
+            Code::UnreachableCode => "unreachable-code",
+            Code::ConstantCondition => "constant-condition",
+            Code::UndefinedFunction => "undefined-function",
+        }
+    }
+
+    // accepts either the short code or the name, as written in an allow comment
+    pub fn parse(text: &str) -> Option<Code> {
+        Code::ALL
+            .into_iter()
+            .find(|code| code.code().eq_ignore_ascii_case(text) || code.name() == text)
+    }
+}
+
+impl Display for Code {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Warning {
+    pub code: Code,
+    pub location: Location,
+    pub message: String,
+}
+
+impl Warning {
+    pub fn new(code: Code, location: &Location, message: impl Into<String>) -> Self {
+        Warning {
+            code,
+            location: location.clone(),
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}: warning[{}]: {} ({})",
+            self.location.line(),
+            self.location.column(),
+            self.code,
+            self.message,
+            self.code.name()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_code() {
+        assert_eq!(Code::parse("W003"), Some(Code::ShadowedVariable));
+        assert_eq!(Code::parse("w003"), Some(Code::ShadowedVariable));
+        assert_eq!(Code::parse("unused-parameter"), Some(Code::UnusedParameter));
+        assert_eq!(Code::parse("W999"), None);
+    }
+}
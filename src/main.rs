@@ -1,13 +1,44 @@
+use std::{env, process::ExitCode};
+
+use cli::Command;
 use repl::Repl;
 
-mod evaluator;
-mod lexer;
-mod parser;
+mod cli;
+mod debug;
 mod repl;
 
-fn main() {
-    println!("Monkey repl! enter empty string to exit");
+fn main() -> ExitCode {
+    let command = match Command::parse(env::args().skip(1)) {
+        Ok(command) => command,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, cli::USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let result = match command {
+        Command::Repl => {
+            println!("Monkey repl! enter empty string to exit");
+
+            let mut repl = Repl::new(std::io::stdin());
+            repl.start();
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Run { path, options } => cli::run(&path, &options),
+        Command::DumpAst { format, path } => cli::dump_ast(&format, path.as_deref()),
+        Command::DumpTokens(path) => cli::dump_tokens(path.as_deref()),
+        Command::Fmt {
+            check,
+            options,
+            paths,
+        } => cli::fmt(check, &options, &paths),
+        Command::Lint { options, paths } => cli::lint(&options, &paths),
+        Command::Debug { breakpoints, path } => cli::debug(&path, &breakpoints),
+        Command::Test { filter, path } => cli::test(&path, filter.as_deref()),
+    };
 
-    let repl = Repl::new(std::io::stdin());
-    repl.start();
+    result.unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        ExitCode::FAILURE
+    })
 }
@@ -1,27 +1,39 @@
 use std::fmt::Display;
 
+use crate::lexer::location::Location;
+
 use super::{
     operator::{InfixOperator, PrefixOperator},
+    pattern::Pattern,
+    position::Position,
     statement::Statement,
 };
 
 #[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Expression {
     Int(i64),
     Bool(bool),
-    Identifier(String),
+    Str(String),
+    Identifier(String, Position),
+    Array(Vec<Expression>),
+    Hash(Vec<(Expression, Expression)>),
     If {
         condition: Box<Expression>,
         consequence: Vec<Statement>,
         alternative: Option<Vec<Statement>>,
+        position: Position,
     },
     Function {
-        parameters: Vec<String>,
+        parameters: Vec<Parameter>,
+        rest: Option<String>,
         body: Vec<Statement>,
+        position: Position,
     },
     Call {
         function: Box<Expression>,
         arguments: Vec<Expression>,
+        named_arguments: Vec<(String, Expression)>,
     },
     Prefix {
         rhs: Box<Expression>,
@@ -32,18 +44,98 @@ pub enum Expression {
         operator: InfixOperator,
         rhs: Box<Expression>,
     },
+    Match {
+        subject: Box<Expression>,
+        arms: Vec<MatchArm>,
+        position: Position,
+    },
+    Index {
+        lhs: Box<Expression>,
+        index: Box<Expression>,
+    },
+    Try {
+        body: Vec<Statement>,
+        catch: Option<CatchClause>,
+        finally: Option<Vec<Statement>>,
+    },
     Null,
 }
 
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CatchClause {
+    pub pattern: Pattern,
+    pub body: Vec<Statement>,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<Expression>,
+    pub body: Vec<Statement>,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Parameter {
+    pub pattern: Pattern,
+    pub default: Option<Expression>,
+}
+
+impl Parameter {
+    pub fn new(pattern: Pattern, default: Option<Expression>) -> Self {
+        Parameter { pattern, default }
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        match &self.pattern {
+            Pattern::Identifier(name, _) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+impl<T: Into<Pattern>> From<T> for Parameter {
+    fn from(pattern: T) -> Self {
+        Parameter::new(pattern.into(), None)
+    }
+}
+
+impl MatchArm {
+    pub fn new(pattern: Pattern, guard: Option<Expression>, body: Vec<Statement>) -> Self {
+        MatchArm {
+            pattern,
+            guard,
+            body,
+        }
+    }
+}
+
 impl Expression {
     pub fn identifier(identifier: impl Into<String>) -> Self {
-        Expression::Identifier(identifier.into())
+        Expression::Identifier(identifier.into(), Position::default())
     }
 
-    pub fn function(parameters: Vec<impl Into<String>>, body: Vec<Statement>) -> Self {
+    pub fn function(parameters: Vec<impl Into<Parameter>>, body: Vec<Statement>) -> Self {
         Expression::Function {
             parameters: parameters.into_iter().map(|p| p.into()).collect(),
+            rest: None,
             body,
+            position: Position::default(),
+        }
+    }
+
+    pub fn variadic_function(
+        parameters: Vec<impl Into<Parameter>>,
+        rest: impl Into<String>,
+        body: Vec<Statement>,
+    ) -> Self {
+        Expression::Function {
+            parameters: parameters.into_iter().map(|p| p.into()).collect(),
+            rest: Some(rest.into()),
+            body,
+            position: Position::default(),
         }
     }
 
@@ -71,6 +163,7 @@ impl Expression {
             condition: Box::new(condition),
             consequence,
             alternative,
+            position: Position::default(),
         }
     }
 
@@ -78,10 +171,79 @@ impl Expression {
         Expression::Call {
             function: Box::new(function),
             arguments,
+            named_arguments: vec![],
+        }
+    }
+
+    pub fn call_with_named(
+        function: Expression,
+        arguments: Vec<Expression>,
+        named_arguments: Vec<(impl Into<String>, Expression)>,
+    ) -> Self {
+        Expression::Call {
+            function: Box::new(function),
+            arguments,
+            named_arguments: named_arguments
+                .into_iter()
+                .map(|(name, argument)| (name.into(), argument))
+                .collect(),
+        }
+    }
+
+    pub fn string(string: impl Into<String>) -> Self {
+        Expression::Str(string.into())
+    }
+
+    pub fn index(lhs: Expression, index: Expression) -> Self {
+        Expression::Index {
+            lhs: Box::new(lhs),
+            index: Box::new(index),
+        }
+    }
+
+    pub fn r#try(
+        body: Vec<Statement>,
+        catch: Option<(Pattern, Vec<Statement>)>,
+        finally: Option<Vec<Statement>>,
+    ) -> Self {
+        Expression::Try {
+            body,
+            catch: catch.map(|(pattern, body)| CatchClause { pattern, body }),
+            finally,
+        }
+    }
+
+    pub fn r#match(subject: Expression, arms: Vec<MatchArm>) -> Self {
+        Expression::Match {
+            subject: Box::new(subject),
+            arms,
+            position: Position::default(),
+        }
+    }
+
+    // only some expressions record where they start; the others are located by the
+    // statement around them
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            Expression::Identifier(_, position)
+            | Expression::If { position, .. }
+            | Expression::Function { position, .. }
+            | Expression::Match { position, .. } => Some(&position.0),
+            _ => None,
         }
     }
 }
 
+impl Expression {
+    fn join_statements(statements: &[Statement]) -> String {
+        statements
+            .iter()
+            .map(|s| format!("{}", s))
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
+
 impl Display for Expression {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self {
@@ -89,36 +251,59 @@ impl Display for Expression {
             Expression::Prefix { operator, rhs } => write!(f, "({}{})", operator, rhs),
             Expression::Bool(b) => write!(f, "{}", b),
             Expression::Int(i) => write!(f, "{}", i),
-            Expression::Identifier(identifier) => write!(f, "{}", identifier),
+            Expression::Str(s) => write!(f, "\"{}\"", s),
+            Expression::Identifier(identifier, _) => write!(f, "{}", identifier),
+            Expression::Array(elements) => write!(
+                f,
+                "[{}]",
+                elements
+                    .iter()
+                    .map(|e| format!("{}", e))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Expression::Hash(entries) => write!(
+                f,
+                "{{{}}}",
+                entries
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key, value))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
             Expression::If {
                 condition,
                 consequence,
                 alternative,
+                ..
+            } => {
+                write!(
+                    f,
+                    "if {} {{ {} }}",
+                    condition,
+                    Expression::join_statements(consequence)
+                )?;
+                if let Some(alternative) = alternative {
+                    write!(
+                        f,
+                        " else {{ {} }}",
+                        Expression::join_statements(alternative)
+                    )?;
+                }
+                Ok(())
+            }
+            Expression::Function {
+                parameters,
+                rest,
+                body,
+                ..
             } => write!(
-                f,
-                "if {} {{ {} }} else {{ {} }}",
-                condition,
-                consequence
-                    .iter()
-                    .map(|s| format!("{}", s))
-                    .collect::<Vec<String>>()
-                    .join(" "),
-                alternative
-                    .as_ref()
-                    .map(|s| {
-                        s.iter()
-                            .map(|s| format!("{}", s))
-                            .collect::<Vec<String>>()
-                            .join(" ")
-                    })
-                    .unwrap_or_else(|| "".to_string())
-            ),
-            Expression::Function { parameters, body } => write!(
                 f,
                 "fn({}) {{ {} }}",
                 parameters
                     .iter()
                     .map(|p| format!("{}", p))
+                    .chain(rest.iter().map(|r| format!("...{}", r)))
                     .collect::<Vec<String>>()
                     .join(", "),
                 body.iter()
@@ -129,6 +314,7 @@ impl Display for Expression {
             Expression::Call {
                 function,
                 arguments,
+                named_arguments,
             } => write!(
                 f,
                 "{}({})",
@@ -136,14 +322,75 @@ impl Display for Expression {
                 arguments
                     .iter()
                     .map(|a| format!("{}", a))
+                    .chain(
+                        named_arguments
+                            .iter()
+                            .map(|(name, a)| format!("{}: {}", name, a))
+                    )
                     .collect::<Vec<String>>()
                     .join(", ")
             ),
+            Expression::Match { subject, arms, .. } => write!(
+                f,
+                "match {} {{ {} }}",
+                subject,
+                arms.iter()
+                    .map(|a| format!("{}", a))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Expression::Index { lhs, index } => write!(f, "({}[{}])", lhs, index),
+            Expression::Try {
+                body,
+                catch,
+                finally,
+            } => {
+                write!(f, "try {{ {} }}", Expression::join_statements(body))?;
+                if let Some(catch) = catch {
+                    write!(
+                        f,
+                        " catch ({}) {{ {} }}",
+                        catch.pattern,
+                        Expression::join_statements(&catch.body)
+                    )?;
+                }
+                if let Some(finally) = finally {
+                    write!(f, " finally {{ {} }}", Expression::join_statements(finally))?;
+                }
+                Ok(())
+            }
             Expression::Null => write!(f, "null"),
         }
     }
 }
 
+impl Display for Parameter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.default {
+            Some(default) => write!(f, "{} = {}", self.pattern, default),
+            None => write!(f, "{}", self.pattern),
+        }
+    }
+}
+
+impl Display for MatchArm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.pattern)?;
+        if let Some(guard) = &self.guard {
+            write!(f, " if {}", guard)?;
+        }
+        write!(
+            f,
+            " => {{ {} }}",
+            self.body
+                .iter()
+                .map(|s| format!("{}", s))
+                .collect::<Vec<String>>()
+                .join(" ")
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -182,19 +429,97 @@ mod tests {
     fn test_condition() {
         let condition = Expression::If {
             condition: Box::new(Expression::Bool(true)),
-            consequence: vec![Statement::Expression(Expression::Int(1))],
-            alternative: Some(vec![Statement::Expression(Expression::Int(2))]),
+            consequence: vec![Statement::expression(Expression::Int(1))],
+            alternative: Some(vec![Statement::expression(Expression::Int(2))]),
+            position: Position::default(),
         };
         assert_eq!(format!("{}", condition), "if true { 1 } else { 2 }");
+
+        let condition = Expression::r#if(
+            Expression::Bool(true),
+            vec![Statement::expression(Expression::Int(1))],
+            None,
+        );
+        assert_eq!(format!("{}", condition), "if true { 1 }");
     }
 
     #[test]
     fn test_function() {
         let function = Expression::Function {
-            parameters: vec!["foo".to_string()],
-            body: vec![Statement::Expression(Expression::Int(1))],
+            parameters: vec![Parameter::from("foo")],
+            rest: None,
+            body: vec![Statement::expression(Expression::Int(1))],
+            position: Position::default(),
         };
         assert_eq!(format!("{}", function), "fn(foo) { 1 }");
+
+        let function = Expression::variadic_function(
+            vec![
+                Parameter::from("a"),
+                Parameter::new(Pattern::identifier("b"), Some(Expression::Int(2))),
+            ],
+            "rest",
+            vec![Statement::expression(Expression::Int(1))],
+        );
+        assert_eq!(format!("{}", function), "fn(a, b = 2, ...rest) { 1 }");
+    }
+
+    #[test]
+    fn test_collections() {
+        let array = Expression::Array(vec![Expression::Int(1), Expression::string("two")]);
+        assert_eq!(format!("{}", array), "[1, \"two\"]");
+
+        let hash = Expression::Hash(vec![(Expression::string("a"), Expression::Bool(true))]);
+        assert_eq!(format!("{}", hash), "{\"a\": true}");
+    }
+
+    #[test]
+    fn test_match() {
+        let r#match = Expression::r#match(
+            Expression::identifier("x"),
+            vec![
+                MatchArm::new(
+                    Pattern::identifier("n"),
+                    Some(Expression::infix(
+                        Expression::identifier("n"),
+                        Expression::Int(0),
+                        InfixOperator::GreaterThan,
+                    )),
+                    vec![Statement::expression(Expression::Int(1))],
+                ),
+                MatchArm::new(
+                    Pattern::Wildcard,
+                    None,
+                    vec![Statement::expression(Expression::Int(2))],
+                ),
+            ],
+        );
+        assert_eq!(
+            format!("{}", r#match),
+            "match x { n if (n > 0) => { 1 }, _ => { 2 } }"
+        );
+    }
+
+    #[test]
+    fn test_index() {
+        let index = Expression::index(Expression::identifier("xs"), Expression::Int(0));
+        assert_eq!(format!("{}", index), "(xs[0])");
+    }
+
+    #[test]
+    fn test_try() {
+        let r#try = Expression::r#try(
+            vec![Statement::expression(Expression::Int(1))],
+            Some((
+                Pattern::identifier("e"),
+                vec![Statement::expression(Expression::Int(2))],
+            )),
+            Some(vec![Statement::expression(Expression::Int(3))]),
+        );
+        assert_eq!(
+            format!("{}", r#try),
+            "try { 1 } catch (e) { 2 } finally { 3 }"
+        );
     }
 
     #[test]
@@ -202,7 +527,15 @@ mod tests {
         let call = Expression::Call {
             function: Box::new(Expression::identifier("foo")),
             arguments: vec![Expression::Int(1)],
+            named_arguments: vec![],
         };
         assert_eq!(format!("{}", call), "foo(1)");
+
+        let call = Expression::call_with_named(
+            Expression::identifier("foo"),
+            vec![Expression::Int(1)],
+            vec![("b", Expression::Int(2))],
+        );
+        assert_eq!(format!("{}", call), "foo(1, b: 2)");
     }
 }
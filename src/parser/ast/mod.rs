@@ -1,5 +1,8 @@
 pub mod expression;
 pub mod node;
 pub mod operator;
+pub mod pattern;
+pub mod position;
 pub mod program;
+pub mod sexpr;
 pub mod statement;
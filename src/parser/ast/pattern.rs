@@ -0,0 +1,124 @@
+use std::fmt::Display;
+
+use super::position::Position;
+
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Pattern {
+    Wildcard,
+    Identifier(String, Position),
+    Int(i64),
+    Bool(bool),
+    Str(String),
+    Null,
+    Array {
+        elements: Vec<Pattern>,
+        rest: Option<Box<Pattern>>,
+    },
+    Hash(Vec<(String, Pattern)>),
+}
+
+impl Pattern {
+    pub fn identifier(identifier: impl Into<String>) -> Self {
+        Pattern::Identifier(identifier.into(), Position::default())
+    }
+
+    pub fn array(elements: Vec<Pattern>, rest: Option<Pattern>) -> Self {
+        Pattern::Array {
+            elements,
+            rest: rest.map(Box::new),
+        }
+    }
+
+    pub fn hash(entries: Vec<(impl Into<String>, Pattern)>) -> Self {
+        Pattern::Hash(
+            entries
+                .into_iter()
+                .map(|(key, pattern)| (key.into(), pattern))
+                .collect(),
+        )
+    }
+
+    fn is_bare_key(key: &str) -> bool {
+        !key.is_empty() && key.chars().all(|ch| ch.is_ascii_alphabetic() || ch == '_')
+    }
+}
+
+impl From<&str> for Pattern {
+    fn from(identifier: &str) -> Self {
+        Pattern::identifier(identifier)
+    }
+}
+
+impl From<String> for Pattern {
+    fn from(identifier: String) -> Self {
+        Pattern::Identifier(identifier, Position::default())
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self {
+            Pattern::Wildcard => write!(f, "_"),
+            Pattern::Identifier(identifier, _) => write!(f, "{}", identifier),
+            Pattern::Int(i) => write!(f, "{}", i),
+            Pattern::Bool(b) => write!(f, "{}", b),
+            Pattern::Str(s) => write!(f, "\"{}\"", s),
+            Pattern::Null => write!(f, "null"),
+            Pattern::Array { elements, rest } => {
+                let mut parts = elements
+                    .iter()
+                    .map(|e| format!("{}", e))
+                    .collect::<Vec<String>>();
+                match rest.as_deref() {
+                    Some(Pattern::Wildcard) => parts.push("..".to_string()),
+                    Some(rest) => parts.push(format!("..{}", rest)),
+                    None => {}
+                }
+                write!(f, "[{}]", parts.join(", "))
+            }
+            Pattern::Hash(entries) => write!(
+                f,
+                "{{{}}}",
+                entries
+                    .iter()
+                    .map(|(key, pattern)| match pattern {
+                        Pattern::Identifier(identifier, _) if identifier == key => key.clone(),
+                        pattern if Pattern::is_bare_key(key) => format!("{}: {}", key, pattern),
+                        pattern => format!("\"{}\": {}", key, pattern),
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_array_pattern() {
+        let pattern = Pattern::array(
+            vec![Pattern::identifier("head"), Pattern::Int(1)],
+            Some(Pattern::identifier("tail")),
+        );
+        assert_eq!(format!("{}", pattern), "[head, 1, ..tail]");
+
+        let pattern = Pattern::array(vec![Pattern::Wildcard], Some(Pattern::Wildcard));
+        assert_eq!(format!("{}", pattern), "[_, ..]");
+    }
+
+    #[test]
+    fn test_hash_pattern() {
+        let pattern = Pattern::hash(vec![
+            ("x", Pattern::identifier("x")),
+            ("y", Pattern::identifier("renamed")),
+        ]);
+        assert_eq!(format!("{}", pattern), "{x, y: renamed}");
+
+        let pattern = Pattern::hash(vec![("first name", Pattern::Wildcard)]);
+        assert_eq!(format!("{}", pattern), "{\"first name\": _}");
+    }
+}
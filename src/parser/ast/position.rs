@@ -0,0 +1,33 @@
+use crate::lexer::location::Location;
+
+// Where a node starts in the source. Positions are ignored when comparing nodes, so trees
+// built by hand or parsed from differently laid out sources compare by structure alone.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Position(pub Location);
+
+impl Position {
+    pub fn line(&self) -> usize {
+        self.0.line()
+    }
+
+    pub fn column(&self) -> usize {
+        self.0.column()
+    }
+}
+
+impl PartialEq for Position {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl From<Location> for Position {
+    fn from(location: Location) -> Self {
+        Position(location)
+    }
+}
@@ -2,7 +2,8 @@ use std::fmt::Display;
 
 use super::statement::Statement;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Program {
     pub statements: Vec<Statement>,
 }
@@ -13,6 +14,12 @@ impl Program {
     }
 }
 
+impl Default for Program {
+    fn default() -> Self {
+        Program::new()
+    }
+}
+
 impl Display for Program {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for statement in &self.statements {
@@ -22,3 +29,31 @@ impl Display for Program {
         Ok(())
     }
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use crate::{lexer::lexer::Lexer, parser::parser::Parser};
+
+    use super::Program;
+
+    #[test]
+    fn test_json_round_trip() {
+        let program = Parser::new(Lexer::new(
+            "let f = fn(a, b = 1) { match (a) { [x, ..] => { x }, _ => { b } } }; f([3]);",
+        ))
+        .parse_program();
+
+        let json = serde_json::to_string(&program).unwrap();
+        let decoded: Program = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.statements, program.statements);
+
+        let json = serde_json::to_string(&Parser::new(Lexer::new("-x")).parse_program()).unwrap();
+        assert_eq!(
+            json,
+            concat!(
+                r#"{"statements":[{"Expression":[{"Prefix":{"rhs":{"Identifier":["x",{"line":1,"column":2}]},"#,
+                r#""operator":"Negative"}},{"line":1,"column":1}]}]}"#
+            )
+        );
+    }
+}
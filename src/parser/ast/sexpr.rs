@@ -0,0 +1,236 @@
+use super::{
+    expression::{Expression, MatchArm, Parameter},
+    pattern::Pattern,
+    program::Program,
+    statement::Statement,
+};
+
+fn list(head: &str, items: impl IntoIterator<Item = String>) -> String {
+    let mut parts = vec![head.to_string()];
+    parts.extend(items);
+    format!("({})", parts.join(" "))
+}
+
+fn block(statements: &[Statement]) -> String {
+    list("block", statements.iter().map(Statement::to_sexpr))
+}
+
+impl Program {
+    pub fn to_sexpr(&self) -> String {
+        self.statements
+            .iter()
+            .map(|statement| format!("{}\n", statement.to_sexpr()))
+            .collect()
+    }
+}
+
+impl Statement {
+    pub fn to_sexpr(&self) -> String {
+        match self {
+            Statement::Let { pattern, value, .. } => {
+                list("let", [pattern.to_sexpr(), value.to_sexpr()])
+            }
+            Statement::Return { value, .. } => list("return", [value.to_sexpr()]),
+            Statement::Throw { value, .. } => list("throw", [value.to_sexpr()]),
+            Statement::Expression(expression, _) => expression.to_sexpr(),
+            Statement::Block(statements) => block(statements),
+        }
+    }
+}
+
+impl Expression {
+    pub fn to_sexpr(&self) -> String {
+        match self {
+            Expression::Int(value) => value.to_string(),
+            Expression::Bool(value) => value.to_string(),
+            Expression::Str(value) => format!("{:?}", value),
+            Expression::Identifier(identifier, _) => identifier.clone(),
+            Expression::Null => "null".to_string(),
+            Expression::Array(elements) => list("array", elements.iter().map(Self::to_sexpr)),
+            Expression::Hash(entries) => list(
+                "hash",
+                entries
+                    .iter()
+                    .map(|(key, value)| format!("({} {})", key.to_sexpr(), value.to_sexpr())),
+            ),
+            Expression::If {
+                condition,
+                consequence,
+                alternative,
+                ..
+            } => list(
+                "if",
+                [condition.to_sexpr(), block(consequence)]
+                    .into_iter()
+                    .chain(alternative.as_deref().map(block)),
+            ),
+            Expression::Function {
+                parameters,
+                rest,
+                body,
+                ..
+            } => list(
+                "fn",
+                [
+                    list(
+                        "params",
+                        parameters
+                            .iter()
+                            .map(Parameter::to_sexpr)
+                            .chain(rest.iter().map(|rest| format!("(rest {})", rest))),
+                    ),
+                    block(body),
+                ],
+            ),
+            Expression::Call {
+                function,
+                arguments,
+                named_arguments,
+            } => list(
+                "call",
+                [function.to_sexpr()]
+                    .into_iter()
+                    .chain(arguments.iter().map(Self::to_sexpr))
+                    .chain(
+                        named_arguments
+                            .iter()
+                            .map(|(name, value)| format!("(named {} {})", name, value.to_sexpr())),
+                    ),
+            ),
+            Expression::Prefix { rhs, operator } => list(&operator.to_string(), [rhs.to_sexpr()]),
+            Expression::Infix { lhs, operator, rhs } => {
+                list(&operator.to_string(), [lhs.to_sexpr(), rhs.to_sexpr()])
+            }
+            Expression::Match { subject, arms, .. } => list(
+                "match",
+                [subject.to_sexpr()]
+                    .into_iter()
+                    .chain(arms.iter().map(MatchArm::to_sexpr)),
+            ),
+            Expression::Index { lhs, index } => list("index", [lhs.to_sexpr(), index.to_sexpr()]),
+            Expression::Try {
+                body,
+                catch,
+                finally,
+            } => {
+                list(
+                    "try",
+                    [block(body)]
+                        .into_iter()
+                        .chain(catch.iter().map(|catch| {
+                            list("catch", [catch.pattern.to_sexpr(), block(&catch.body)])
+                        }))
+                        .chain(
+                            finally
+                                .iter()
+                                .map(|finally| list("finally", [block(finally)])),
+                        ),
+                )
+            }
+        }
+    }
+}
+
+impl Parameter {
+    pub fn to_sexpr(&self) -> String {
+        match &self.default {
+            Some(default) => list("=", [self.pattern.to_sexpr(), default.to_sexpr()]),
+            None => self.pattern.to_sexpr(),
+        }
+    }
+}
+
+impl MatchArm {
+    pub fn to_sexpr(&self) -> String {
+        list(
+            "arm",
+            [self.pattern.to_sexpr()]
+                .into_iter()
+                .chain(
+                    self.guard
+                        .iter()
+                        .map(|guard| list("guard", [guard.to_sexpr()])),
+                )
+                .chain([block(&self.body)]),
+        )
+    }
+}
+
+impl Pattern {
+    pub fn to_sexpr(&self) -> String {
+        match self {
+            Pattern::Wildcard => "_".to_string(),
+            Pattern::Identifier(identifier, _) => identifier.clone(),
+            Pattern::Int(value) => value.to_string(),
+            Pattern::Bool(value) => value.to_string(),
+            Pattern::Str(value) => format!("{:?}", value),
+            Pattern::Null => "null".to_string(),
+            Pattern::Array { elements, rest } => list(
+                "array",
+                elements
+                    .iter()
+                    .map(Self::to_sexpr)
+                    .chain(rest.iter().map(|rest| list("rest", [rest.to_sexpr()]))),
+            ),
+            Pattern::Hash(entries) => list(
+                "hash",
+                entries
+                    .iter()
+                    .map(|(key, pattern)| format!("({:?} {})", key, pattern.to_sexpr())),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::{lexer::lexer::Lexer, parser::parser::Parser};
+
+    fn sexpr(input: &str) -> String {
+        let mut parser = Parser::new(Lexer::new(input));
+        let program = parser.parse_program();
+        assert_eq!(parser.errors, vec![]);
+        program.to_sexpr()
+    }
+
+    #[test]
+    fn test_statements() {
+        assert_eq!(
+            sexpr("let x = 1 + 2 * 3; return -x; throw \"boom\";"),
+            indoc! {r#"
+                (let x (+ 1 (* 2 3)))
+                (return (- x))
+                (throw "boom")
+            "#}
+        );
+    }
+
+    #[test]
+    fn test_expressions() {
+        let tests = vec![
+            ("if (x) { 1 }", "(if x (block 1))\n"),
+            ("if (x) { 1 } else { 2 }", "(if x (block 1) (block 2))\n"),
+            (
+                "fn(a, b = 2, ...rest) { a }",
+                "(fn (params a (= b 2) (rest rest)) (block a))\n",
+            ),
+            ("f(1, b: 2)", "(call f 1 (named b 2))\n"),
+            ("[1, \"two\"][0]", "(index (array 1 \"two\") 0)\n"),
+            ("{\"a\": true}", "(hash (\"a\" true))\n"),
+            (
+                "match (x) { [h, ..t] if h => { h }, _ => { null } }",
+                "(match x (arm (array h (rest t)) (guard h) (block h)) (arm _ (block null)))\n",
+            ),
+            (
+                "try { 1 } catch ({kind}) { 2 } finally { 3 }",
+                "(try (block 1) (catch (hash (\"kind\" kind)) (block 2)) (finally (block 3)))\n",
+            ),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(sexpr(input), expected, "input: {}", input);
+        }
+    }
+}
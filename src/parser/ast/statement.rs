@@ -1,38 +1,85 @@
 use std::fmt::Display;
 
-use super::expression::Expression;
+use crate::lexer::location::Location;
+
+use super::{expression::Expression, pattern::Pattern, position::Position};
 
 #[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Statement {
-    Let { name: String, value: Expression },
-    Return { value: Expression },
-    Expression(Expression),
+    Let {
+        pattern: Pattern,
+        value: Expression,
+        position: Position,
+    },
+    Return {
+        value: Expression,
+        position: Position,
+    },
+    Throw {
+        value: Expression,
+        position: Position,
+    },
+    Expression(Expression, Position),
     Block(Vec<Statement>),
 }
 
 impl Statement {
-    pub fn r#let(name: impl Into<String>, value: Expression) -> Self {
+    pub fn r#let(pattern: impl Into<Pattern>, value: Expression) -> Self {
         Statement::Let {
-            name: name.into(),
+            pattern: pattern.into(),
             value,
+            position: Position::default(),
         }
     }
 
     pub fn r#return(value: Expression) -> Self {
-        Statement::Return { value }
+        Statement::Return {
+            value,
+            position: Position::default(),
+        }
+    }
+
+    pub fn throw(value: Expression) -> Self {
+        Statement::Throw {
+            value,
+            position: Position::default(),
+        }
     }
 
     pub fn expression(expression: Expression) -> Self {
-        Statement::Expression(expression)
+        Statement::Expression(expression, Position::default())
+    }
+
+    pub fn with_position(mut self, location: Location) -> Self {
+        match &mut self {
+            Statement::Let { position, .. }
+            | Statement::Return { position, .. }
+            | Statement::Throw { position, .. }
+            | Statement::Expression(_, position) => *position = Position(location),
+            Statement::Block(_) => {}
+        }
+        self
+    }
+
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            Statement::Let { position, .. }
+            | Statement::Return { position, .. }
+            | Statement::Throw { position, .. }
+            | Statement::Expression(_, position) => Some(&position.0),
+            Statement::Block(_) => None,
+        }
     }
 }
 
 impl Display for Statement {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self {
-            Statement::Let { name, value } => write!(f, "let {} = {}", name, value),
-            Statement::Return { value } => write!(f, "return {}", value),
-            Statement::Expression(expression) => write!(f, "{}", expression),
+            Statement::Let { pattern, value, .. } => write!(f, "let {} = {}", pattern, value),
+            Statement::Return { value, .. } => write!(f, "return {}", value),
+            Statement::Throw { value, .. } => write!(f, "throw {}", value),
+            Statement::Expression(expression, _) => write!(f, "{}", expression),
             Statement::Block(statements) => {
                 let mut result = String::new();
                 for statement in statements {
@@ -0,0 +1,178 @@
+use std::fmt::Display;
+
+use crate::lexer::{
+    location::Location,
+    token::{Token, TokenType},
+};
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ParserErrorKind {
+    UnexpectedToken {
+        found: TokenType,
+        expected: Vec<TokenType>,
+    },
+    UnexpectedEof {
+        expected: Vec<TokenType>,
+    },
+    InvalidIntegerLiteral(String),
+    MissingClosingDelimiter {
+        delimiter: TokenType,
+        opened_at: Location,
+    },
+    ExpectedExpression(TokenType),
+    ExpectedPattern(TokenType),
+    MisplacedRest,
+    PositionalAfterNamedArgument,
+    MissingCatchOrFinally,
+    NestingTooDeep(usize),
+    UnterminatedString,
+}
+
+impl ParserErrorKind {
+    fn describe_expected(expected: &[TokenType]) -> String {
+        let expected = expected
+            .iter()
+            .map(|token_type| match token_type {
+                TokenType::Identifier(_) => "identifier".to_string(),
+                TokenType::Integer(_) => "integer".to_string(),
+                TokenType::String(_) => "string".to_string(),
+                token_type => token_type.to_string(),
+            })
+            .collect::<Vec<String>>();
+
+        match expected.split_last() {
+            Some((last, [])) => last.clone(),
+            Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+            None => String::new(),
+        }
+    }
+}
+
+impl Display for ParserErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParserErrorKind::UnexpectedToken { found, expected } if expected.is_empty() => {
+                write!(f, "unexpected token {}", found)
+            }
+            ParserErrorKind::UnexpectedToken { found, expected } => write!(
+                f,
+                "expected {}, got {}",
+                ParserErrorKind::describe_expected(expected),
+                found
+            ),
+            ParserErrorKind::UnexpectedEof { expected } if expected.is_empty() => {
+                write!(f, "unexpected end of file")
+            }
+            ParserErrorKind::UnexpectedEof { expected } => write!(
+                f,
+                "unexpected end of file, expected {}",
+                ParserErrorKind::describe_expected(expected)
+            ),
+            ParserErrorKind::InvalidIntegerLiteral(literal) => {
+                write!(f, "failed to parse integer {}", literal)
+            }
+            ParserErrorKind::MissingClosingDelimiter {
+                delimiter,
+                opened_at,
+            } => write!(
+                f,
+                "missing closing {} for delimiter opened {}",
+                delimiter, opened_at
+            ),
+            ParserErrorKind::ExpectedExpression(found) => {
+                write!(f, "expected expression, got {}", found)
+            }
+            ParserErrorKind::ExpectedPattern(found) => write!(f, "expected pattern, got {}", found),
+            ParserErrorKind::MisplacedRest => write!(f, "rest element must come last"),
+            ParserErrorKind::PositionalAfterNamedArgument => {
+                write!(f, "positional argument after named argument")
+            }
+            ParserErrorKind::MissingCatchOrFinally => {
+                write!(f, "expected catch or finally after try block")
+            }
+            ParserErrorKind::NestingTooDeep(limit) => {
+                write!(f, "expression nested deeper than {} levels", limit)
+            }
+            ParserErrorKind::UnterminatedString => write!(f, "unterminated string literal"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ParserError {
+    pub kind: ParserErrorKind,
+    pub location: Location,
+}
+
+impl ParserError {
+    pub fn new(kind: ParserErrorKind, location: &Location) -> Self {
+        ParserError {
+            kind,
+            location: location.clone(),
+        }
+    }
+
+    pub fn unexpected(token: &Token, expected: Vec<TokenType>) -> Self {
+        let kind = match &token.token_type {
+            TokenType::EOF => ParserErrorKind::UnexpectedEof { expected },
+            found => ParserErrorKind::UnexpectedToken {
+                found: found.clone(),
+                expected,
+            },
+        };
+
+        ParserError::new(kind, &token.location)
+    }
+
+    pub fn is_incomplete(&self) -> bool {
+        matches!(
+            self.kind,
+            ParserErrorKind::UnexpectedEof { .. }
+                | ParserErrorKind::MissingClosingDelimiter { .. }
+                | ParserErrorKind::UnterminatedString
+        )
+    }
+}
+
+impl Display for ParserError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[ParserError:{}] {} ", self.location, self.kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let error = ParserError::unexpected(
+            &Token::new(TokenType::Semicolon, Location::new(1, 4)),
+            vec![
+                TokenType::identifier(""),
+                TokenType::LBracket,
+                TokenType::LBrace,
+            ],
+        );
+        assert_eq!(error.kind.to_string(), "expected identifier, [ or {, got ;");
+
+        let error = ParserError::unexpected(
+            &Token::new(TokenType::EOF, Location::new(1, 4)),
+            vec![TokenType::RParen],
+        );
+        assert_eq!(error.kind.to_string(), "unexpected end of file, expected )");
+    }
+
+    #[test]
+    fn test_is_incomplete() {
+        let error =
+            ParserError::unexpected(&Token::new(TokenType::EOF, Location::new(1, 4)), vec![]);
+        assert!(error.is_incomplete());
+
+        let error = ParserError::new(
+            ParserErrorKind::InvalidIntegerLiteral("99999999999999999999".to_string()),
+            &Location::new(1, 1),
+        );
+        assert!(!error.is_incomplete());
+    }
+}
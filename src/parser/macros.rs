@@ -6,10 +6,29 @@ macro_rules! expect_peek {
                 $self.next_token();
                 Ok(())
             }
-            _ => Err(ParserError::new(
-                format!("unexpected token {}", $self.peeking_token.token_type,),
+            _ => Err(ParserError::unexpected(
+                &$self.peeking_token,
+                vec![TokenType::$token],
+            )),
+        }
+    };
+    ($self: ident, $token: ident, $opened_at: expr) => {
+        match $self.peeking_token.token_type {
+            TokenType::$token => {
+                $self.next_token();
+                Ok(())
+            }
+            TokenType::EOF => Err(ParserError::new(
+                ParserErrorKind::MissingClosingDelimiter {
+                    delimiter: TokenType::$token,
+                    opened_at: $opened_at.clone(),
+                },
                 &$self.peeking_token.location,
             )),
+            _ => Err(ParserError::unexpected(
+                &$self.peeking_token,
+                vec![TokenType::$token],
+            )),
         }
     };
 }
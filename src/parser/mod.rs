@@ -1,4 +1,6 @@
 pub mod ast;
+pub mod error;
 mod macros;
 pub mod parser;
-mod precedence;
+pub(crate) mod precedence;
+pub mod source_map;
@@ -1,5 +1,3 @@
-use std::fmt::Display;
-
 use crate::{
     expect_peek,
     lexer::{
@@ -11,40 +9,31 @@ use crate::{
 
 use super::{
     ast::{
-        expression::Expression,
+        expression::{Expression, MatchArm, Parameter},
         operator::{InfixOperator, PrefixOperator},
+        pattern::Pattern,
+        position::Position,
         program::Program,
         statement::Statement,
     },
+    error::{ParserError, ParserErrorKind},
     precedence::Precedence,
+    source_map::{List, SourceMap, Span},
 };
 
-#[derive(Debug)]
-pub struct ParserError {
-    msg: String,
-    location: Location,
-}
-
-impl Display for ParserError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "[ParserError:{}] {} ", self.location, self.msg)
-    }
-}
+type CallArguments = (Vec<Expression>, Vec<(String, Expression)>);
 
-impl ParserError {
-    fn new(msg: impl Into<String>, location: &Location) -> ParserError {
-        ParserError {
-            msg: msg.into(),
-            location: location.clone(),
-        }
-    }
-}
+const MAX_NESTING_DEPTH: usize = 256;
+const STACK_RED_ZONE: usize = 64 * 1024;
+const STACK_GROWTH: usize = 1024 * 1024;
 
 pub struct Parser<'a> {
     lexer: Lexer<'a>,
     current_token: Token,
     peeking_token: Token,
+    depth: usize,
     pub errors: Vec<ParserError>,
+    pub source_map: SourceMap,
 }
 
 impl<'a> Parser<'a> {
@@ -56,7 +45,9 @@ impl<'a> Parser<'a> {
             lexer,
             current_token,
             peeking_token,
+            depth: 0,
             errors: vec![],
+            source_map: SourceMap::default(),
         };
 
         parser
@@ -68,23 +59,74 @@ impl<'a> Parser<'a> {
         while self.current_token.token_type != TokenType::EOF {
             let stmt = self.parse_statement();
 
+            // a parsed statement ends on its last token; only an error skips ahead, to
+            // the next semicolon, so that no code is dropped silently
             match stmt {
-                Ok(stmt) => program.statements.push(stmt),
-                Err(err) => self.errors.push(err),
+                Ok(stmt) => {
+                    program.statements.push(stmt);
+                    self.next_token();
+                }
+                Err(err) => {
+                    self.errors.push(err);
+                    self.advance_tokens();
+                }
             }
-
-            self.advance_tokens();
         }
 
+        self.source_map.comments = self.lexer.take_comments();
+
         program
     }
 
     fn parse_statement(&mut self) -> Result<Statement, ParserError> {
-        match self.current_token.token_type {
-            TokenType::Let => self.parse_let_statement(),
-            TokenType::Return => self.parse_return_statement(),
-            _ => self.parse_expression_statement(),
-        }
+        let location = self.current_token.location.clone();
+
+        self.parse_spanned(|parser| match parser.current_token.token_type {
+            TokenType::Let => parser.parse_let_statement(),
+            TokenType::Return => parser.parse_return_statement(),
+            TokenType::Throw => parser.parse_throw_statement(),
+            _ => parser.parse_expression_statement(),
+        })
+        .map(|statement| statement.with_position(location))
+    }
+
+    fn position(&self) -> Position {
+        Position(self.current_token.location.clone())
+    }
+
+    fn parse_spanned<T>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<T, ParserError>,
+    ) -> Result<T, ParserError> {
+        let index = self.source_map.statements.len();
+        self.source_map.statements.push(Span {
+            start: self.current_token.location.clone(),
+            end: self.current_token.location.clone(),
+        });
+
+        let result = parse(self);
+        self.source_map.statements[index].end = self.current_token.location.clone();
+
+        result
+    }
+
+    // lists are recorded before their items are parsed, so that the lists nested in
+    // them come after them in the source map
+    fn start_list(&mut self) -> usize {
+        self.source_map.lists.push(List {
+            items: vec![],
+            close: self.current_token.location.clone(),
+        });
+        self.source_map.lists.len() - 1
+    }
+
+    fn end_list_item(&mut self, list: usize, start: Location) {
+        let end = self.peeking_token.location.clone();
+        self.source_map.lists[list].items.push(Span { start, end });
+    }
+
+    fn end_list(&mut self, list: usize) {
+        self.source_map.lists[list].close = self.current_token.location.clone();
     }
 
     fn parse_expression_statement(&mut self) -> Result<Statement, ParserError> {
@@ -98,12 +140,29 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_expression(&mut self, precedence: Precedence) -> Result<Expression, ParserError> {
-        let mut lhs = self.parse_prefix()?;
+        stacker::maybe_grow(STACK_RED_ZONE, STACK_GROWTH, || {
+            let lhs = self.parse_prefix()?;
+
+            // every infix operator nests the expression parsed so far one
+            // level deeper, so the levels add up until the expression ends
+            let depth = self.depth;
+            let result = self.parse_infixes(lhs, precedence);
+            self.depth = depth;
 
+            result
+        })
+    }
+
+    fn parse_infixes(
+        &mut self,
+        mut lhs: Expression,
+        precedence: Precedence,
+    ) -> Result<Expression, ParserError> {
         while self.peeking_token.token_type != TokenType::Semicolon
             && precedence < Precedence::from(&self.peeking_token)
         {
             self.next_token();
+            self.enter_nested()?;
 
             lhs = self.parse_infix(lhs)?;
         }
@@ -111,6 +170,29 @@ impl<'a> Parser<'a> {
         Ok(lhs)
     }
 
+    fn parse_nested<T>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<T, ParserError>,
+    ) -> Result<T, ParserError> {
+        self.enter_nested()?;
+        let result = parse(self);
+        self.depth -= 1;
+
+        result
+    }
+
+    fn enter_nested(&mut self) -> Result<(), ParserError> {
+        if self.depth >= MAX_NESTING_DEPTH {
+            return Err(ParserError::new(
+                ParserErrorKind::NestingTooDeep(MAX_NESTING_DEPTH),
+                &self.current_token.location,
+            ));
+        }
+
+        self.depth += 1;
+        Ok(())
+    }
+
     fn advance_tokens(&mut self) {
         while self.current_token.token_type != TokenType::Semicolon
             && self.current_token.token_type != TokenType::EOF
@@ -125,91 +207,537 @@ impl<'a> Parser<'a> {
 
     fn parse_prefix(&mut self) -> Result<Expression, ParserError> {
         match &self.current_token.token_type {
-            TokenType::Identifier(identifier) => Ok(Expression::identifier(identifier)),
+            TokenType::Identifier(identifier) => {
+                Ok(Expression::Identifier(identifier.clone(), self.position()))
+            }
             TokenType::Integer(integer_literal) => self.parse_integer(integer_literal),
-            TokenType::LParen => self.parse_grouped_expression(),
+            TokenType::String(string) => Ok(Expression::string(string)),
+            TokenType::LParen => self.parse_nested(Self::parse_grouped_expression),
+            TokenType::LBracket => self.parse_nested(Self::parse_array_literal),
+            TokenType::LBrace => self.parse_nested(Self::parse_hash_literal),
+            TokenType::Match => self.parse_nested(Self::parse_match_expression),
+            TokenType::Try => self.parse_nested(Self::parse_try_expression),
             TokenType::True | TokenType::False => self.parse_boolean(),
-            TokenType::Bang | TokenType::Minus => self.parse_prefix_expression(),
-            TokenType::If => self.parse_if_expression(),
-            TokenType::Function => self.parse_function_literal(),
+            TokenType::Bang | TokenType::Minus => self.parse_nested(Self::parse_prefix_expression),
+            TokenType::If => self.parse_nested(Self::parse_if_expression),
+            TokenType::Function => self.parse_nested(Self::parse_function_literal),
             TokenType::Null => Ok(Expression::Null),
+            TokenType::EOF => Err(ParserError::unexpected(&self.current_token, vec![])),
+            TokenType::Illegal('"') => Err(ParserError::new(
+                ParserErrorKind::UnterminatedString,
+                &self.current_token.location,
+            )),
             token_type => Err(ParserError::new(
-                format!("Expected prefix expression, got {:?}", token_type),
+                ParserErrorKind::ExpectedExpression(token_type.clone()),
                 &self.current_token.location,
             )),
         }
     }
 
     fn parse_call_expression(&mut self, function: Expression) -> Result<Expression, ParserError> {
-        let arguments = self.parse_call_arguments()?;
-        Ok(Expression::call(function, arguments))
+        let (arguments, named_arguments) = self.parse_call_arguments()?;
+
+        if named_arguments.is_empty() {
+            return Ok(Expression::call(function, arguments));
+        }
+
+        Ok(Expression::call_with_named(
+            function,
+            arguments,
+            named_arguments,
+        ))
     }
 
-    fn parse_call_arguments(&mut self) -> Result<Vec<Expression>, ParserError> {
+    fn parse_call_arguments(&mut self) -> Result<CallArguments, ParserError> {
+        let opened_at = self.current_token.location.clone();
+        let list = self.start_list();
+
         let mut arguments = vec![];
+        let mut named_arguments = vec![];
 
         if self.peeking_token.token_type == TokenType::RParen {
             self.next_token();
-            return Ok(arguments);
+            self.end_list(list);
+            return Ok((arguments, named_arguments));
+        }
+
+        loop {
+            self.next_token();
+            let start = self.current_token.location.clone();
+
+            match (
+                &self.current_token.token_type,
+                &self.peeking_token.token_type,
+            ) {
+                (TokenType::Identifier(name), TokenType::Colon) => {
+                    let name = name.clone();
+                    self.next_token();
+                    self.next_token();
+                    named_arguments.push((name, self.parse_expression(Precedence::LOWEST)?));
+                }
+                _ if !named_arguments.is_empty() => {
+                    return Err(ParserError::new(
+                        ParserErrorKind::PositionalAfterNamedArgument,
+                        &self.current_token.location,
+                    ))
+                }
+                _ => arguments.push(self.parse_expression(Precedence::LOWEST)?),
+            }
+
+            self.end_list_item(list, start);
+
+            if self.peeking_token.token_type != TokenType::Comma {
+                break;
+            }
+
+            self.next_token();
+        }
+
+        expect_peek!(self, RParen, opened_at)?;
+        self.end_list(list);
+
+        Ok((arguments, named_arguments))
+    }
+
+    fn parse_array_literal(&mut self) -> Result<Expression, ParserError> {
+        let opened_at = self.current_token.location.clone();
+        let list = self.start_list();
+
+        let mut elements = vec![];
+
+        if self.peeking_token.token_type == TokenType::RBracket {
+            self.next_token();
+            self.end_list(list);
+            return Ok(Expression::Array(elements));
         }
 
         self.next_token();
 
-        arguments.push(self.parse_expression(Precedence::LOWEST)?);
+        let start = self.current_token.location.clone();
+        elements.push(self.parse_expression(Precedence::LOWEST)?);
+        self.end_list_item(list, start);
 
         while self.peeking_token.token_type == TokenType::Comma {
             self.next_token();
             self.next_token();
-            arguments.push(self.parse_expression(Precedence::LOWEST)?);
+            let start = self.current_token.location.clone();
+            elements.push(self.parse_expression(Precedence::LOWEST)?);
+            self.end_list_item(list, start);
         }
 
-        expect_peek!(self, RParen)?;
+        expect_peek!(self, RBracket, opened_at)?;
+        self.end_list(list);
 
-        Ok(arguments)
+        Ok(Expression::Array(elements))
     }
 
-    fn parse_function_literal(&mut self) -> Result<Expression, ParserError> {
-        expect_peek!(self, LParen)?;
+    fn parse_hash_literal(&mut self) -> Result<Expression, ParserError> {
+        let opened_at = self.current_token.location.clone();
+        let list = self.start_list();
+
+        let mut entries = vec![];
+
+        while self.peeking_token.token_type != TokenType::RBrace {
+            self.next_token();
 
-        let parameters = self.parse_function_params()?;
+            let start = self.current_token.location.clone();
+            let key = self.parse_expression(Precedence::LOWEST)?;
+
+            expect_peek!(self, Colon)?;
+
+            self.next_token();
+
+            let value = self.parse_expression(Precedence::LOWEST)?;
+
+            entries.push((key, value));
+            self.end_list_item(list, start);
+
+            if self.peeking_token.token_type != TokenType::RBrace {
+                expect_peek!(self, Comma)?;
+            }
+        }
+
+        expect_peek!(self, RBrace, opened_at)?;
+        self.end_list(list);
+
+        Ok(Expression::Hash(entries))
+    }
+
+    fn parse_index_expression(&mut self, lhs: Expression) -> Result<Expression, ParserError> {
+        let opened_at = self.current_token.location.clone();
+
+        self.next_token();
+
+        let index = self.parse_expression(Precedence::LOWEST)?;
+
+        expect_peek!(self, RBracket, opened_at)?;
+
+        Ok(Expression::index(lhs, index))
+    }
+
+    fn parse_try_expression(&mut self) -> Result<Expression, ParserError> {
+        let location = self.current_token.location.clone();
 
         expect_peek!(self, LBrace)?;
 
         let body = self.parse_block_statement()?;
 
-        Ok(Expression::function(parameters, body))
+        let mut catch = None;
+
+        if self.peeking_token.token_type == TokenType::Catch {
+            self.next_token();
+
+            expect_peek!(self, LParen)?;
+
+            let opened_at = self.current_token.location.clone();
+
+            self.next_token();
+
+            let pattern = self.parse_binding_pattern()?;
+
+            expect_peek!(self, RParen, opened_at)?;
+
+            expect_peek!(self, LBrace)?;
+
+            catch = Some((pattern, self.parse_block_statement()?));
+        }
+
+        let mut finally = None;
+
+        if self.peeking_token.token_type == TokenType::Finally {
+            self.next_token();
+
+            expect_peek!(self, LBrace)?;
+
+            finally = Some(self.parse_block_statement()?);
+        }
+
+        if catch.is_none() && finally.is_none() {
+            return Err(ParserError::new(
+                ParserErrorKind::MissingCatchOrFinally,
+                &location,
+            ));
+        }
+
+        Ok(Expression::r#try(body, catch, finally))
     }
 
-    fn parse_function_params(&mut self) -> Result<Vec<String>, ParserError> {
-        let mut params = vec![];
+    fn parse_match_expression(&mut self) -> Result<Expression, ParserError> {
+        let position = self.position();
 
-        if self.peeking_token.token_type == TokenType::RParen {
+        expect_peek!(self, LParen)?;
+
+        let opened_at = self.current_token.location.clone();
+
+        self.next_token();
+
+        let subject = self.parse_expression(Precedence::LOWEST)?;
+
+        expect_peek!(self, RParen, opened_at)?;
+
+        expect_peek!(self, LBrace)?;
+
+        let opened_at = self.current_token.location.clone();
+        let list = self.start_list();
+
+        let mut arms = vec![];
+
+        while self.peeking_token.token_type != TokenType::RBrace {
             self.next_token();
-            return Ok(params);
+
+            let start = self.current_token.location.clone();
+            arms.push(self.parse_match_arm()?);
+            self.end_list_item(list, start);
+
+            if self.peeking_token.token_type != TokenType::RBrace {
+                expect_peek!(self, Comma)?;
+            }
+        }
+
+        expect_peek!(self, RBrace, opened_at)?;
+        self.end_list(list);
+
+        Ok(Expression::Match {
+            subject: Box::new(subject),
+            arms,
+            position,
+        })
+    }
+
+    fn parse_match_arm(&mut self) -> Result<MatchArm, ParserError> {
+        let pattern = self.parse_pattern()?;
+
+        let mut guard = None;
+
+        if self.peeking_token.token_type == TokenType::If {
+            self.next_token();
+            self.next_token();
+
+            guard = Some(self.parse_expression(Precedence::LOWEST)?);
         }
 
+        expect_peek!(self, FatArrow)?;
+
         self.next_token();
 
-        while let TokenType::Identifier(identifier) = &self.current_token.token_type {
-            params.push(identifier.clone());
+        // a brace after `=>` opens a block body, so a hash literal must be parenthesized
+        let body = match self.current_token.token_type {
+            TokenType::LBrace => self.parse_block_statement()?,
+            _ => {
+                self.source_map.bodies.push(None);
+                let location = self.current_token.location.clone();
+                vec![self.parse_spanned(|parser| {
+                    parser
+                        .parse_expression(Precedence::LOWEST)
+                        .map(|expression| Statement::expression(expression).with_position(location))
+                })?]
+            }
+        };
+
+        Ok(MatchArm::new(pattern, guard, body))
+    }
+
+    fn parse_pattern(&mut self) -> Result<Pattern, ParserError> {
+        match &self.current_token.token_type {
+            TokenType::Identifier(identifier) if identifier == "_" => Ok(Pattern::Wildcard),
+            TokenType::Identifier(identifier) => {
+                Ok(Pattern::Identifier(identifier.clone(), self.position()))
+            }
+            TokenType::Integer(integer_literal) => self
+                .parse_integer_literal(integer_literal)
+                .map(Pattern::Int),
+            TokenType::Minus => match self.peeking_token.token_type.clone() {
+                TokenType::Integer(integer_literal) => {
+                    self.next_token();
+                    self.parse_integer_literal(&integer_literal)
+                        .map(|integer| Pattern::Int(-integer))
+                }
+                _ => Err(ParserError::unexpected(
+                    &self.peeking_token,
+                    vec![TokenType::integer("")],
+                )),
+            },
+            TokenType::String(string) => Ok(Pattern::Str(string.clone())),
+            TokenType::True => Ok(Pattern::Bool(true)),
+            TokenType::False => Ok(Pattern::Bool(false)),
+            TokenType::Null => Ok(Pattern::Null),
+            TokenType::LBracket => self.parse_nested(Self::parse_array_pattern),
+            TokenType::LBrace => self.parse_nested(Self::parse_hash_pattern),
+            TokenType::EOF => Err(ParserError::unexpected(&self.current_token, vec![])),
+            TokenType::Illegal('"') => Err(ParserError::new(
+                ParserErrorKind::UnterminatedString,
+                &self.current_token.location,
+            )),
+            token_type => Err(ParserError::new(
+                ParserErrorKind::ExpectedPattern(token_type.clone()),
+                &self.current_token.location,
+            )),
+        }
+    }
+
+    fn parse_binding_pattern(&mut self) -> Result<Pattern, ParserError> {
+        match &self.current_token.token_type {
+            TokenType::Identifier(_) | TokenType::LBracket | TokenType::LBrace => {
+                self.parse_pattern()
+            }
+            _ => Err(ParserError::unexpected(
+                &self.current_token,
+                vec![
+                    TokenType::identifier(""),
+                    TokenType::LBracket,
+                    TokenType::LBrace,
+                ],
+            )),
+        }
+    }
+
+    fn parse_array_pattern(&mut self) -> Result<Pattern, ParserError> {
+        let opened_at = self.current_token.location.clone();
+
+        let mut elements = vec![];
+        let mut rest = None;
+
+        while self.peeking_token.token_type != TokenType::RBracket {
+            self.next_token();
+
+            if self.current_token.token_type == TokenType::DotDot {
+                rest = Some(self.parse_rest_pattern()?);
+
+                if self.peeking_token.token_type != TokenType::RBracket {
+                    return Err(ParserError::new(
+                        ParserErrorKind::MisplacedRest,
+                        &self.peeking_token.location,
+                    ));
+                }
+                break;
+            }
+
+            elements.push(self.parse_pattern()?);
+
+            if self.peeking_token.token_type != TokenType::RBracket {
+                expect_peek!(self, Comma)?;
+            }
+        }
+
+        expect_peek!(self, RBracket, opened_at)?;
+
+        Ok(Pattern::array(elements, rest))
+    }
+
+    fn parse_rest_pattern(&mut self) -> Result<Pattern, ParserError> {
+        match &self.peeking_token.token_type {
+            TokenType::Identifier(identifier) => {
+                let pattern = match identifier.as_str() {
+                    "_" => Pattern::Wildcard,
+                    identifier => Pattern::Identifier(
+                        identifier.to_string(),
+                        Position(self.peeking_token.location.clone()),
+                    ),
+                };
+                self.next_token();
+                Ok(pattern)
+            }
+            _ => Ok(Pattern::Wildcard),
+        }
+    }
+
+    fn parse_hash_pattern(&mut self) -> Result<Pattern, ParserError> {
+        let opened_at = self.current_token.location.clone();
+
+        let mut entries = vec![];
 
+        while self.peeking_token.token_type != TokenType::RBrace {
             self.next_token();
-            if let TokenType::Comma = self.current_token.token_type {
+
+            let key = match &self.current_token.token_type {
+                TokenType::Identifier(key) | TokenType::String(key) => key.clone(),
+                _ => {
+                    return Err(ParserError::unexpected(
+                        &self.current_token,
+                        vec![TokenType::identifier(""), TokenType::string("")],
+                    ))
+                }
+            };
+
+            let pattern = if self.peeking_token.token_type == TokenType::Colon {
+                self.next_token();
+                self.next_token();
+                self.parse_pattern()?
+            } else if let TokenType::Identifier(_) = self.current_token.token_type {
+                Pattern::Identifier(key.clone(), self.position())
+            } else {
+                return Err(ParserError::unexpected(
+                    &self.peeking_token,
+                    vec![TokenType::Colon],
+                ));
+            };
+
+            entries.push((key, pattern));
+
+            if self.peeking_token.token_type != TokenType::RBrace {
+                expect_peek!(self, Comma)?;
+            }
+        }
+
+        expect_peek!(self, RBrace, opened_at)?;
+
+        Ok(Pattern::hash(entries))
+    }
+
+    fn parse_function_literal(&mut self) -> Result<Expression, ParserError> {
+        let position = self.position();
+
+        expect_peek!(self, LParen)?;
+
+        let (parameters, rest) = self.parse_function_params()?;
+
+        expect_peek!(self, LBrace)?;
+
+        let body = self.parse_block_statement()?;
+
+        Ok(Expression::Function {
+            parameters,
+            rest,
+            body,
+            position,
+        })
+    }
+
+    fn parse_function_params(&mut self) -> Result<(Vec<Parameter>, Option<String>), ParserError> {
+        let opened_at = self.current_token.location.clone();
+        let list = self.start_list();
+
+        let mut params = vec![];
+        let mut rest = None;
+
+        while self.peeking_token.token_type != TokenType::RParen {
+            self.next_token();
+
+            let start = self.current_token.location.clone();
+
+            if self.current_token.token_type == TokenType::Ellipsis {
+                rest = Some(self.parse_rest_parameter()?);
+                self.end_list_item(list, start);
+                break;
+            }
+
+            let pattern = self.parse_binding_pattern()?;
+
+            let mut default = None;
+
+            if self.peeking_token.token_type == TokenType::Assign {
                 self.next_token();
+                self.next_token();
+
+                default = Some(self.parse_expression(Precedence::LOWEST)?);
+            }
+
+            params.push(Parameter::new(pattern, default));
+            self.end_list_item(list, start);
+
+            if self.peeking_token.token_type != TokenType::RParen {
+                expect_peek!(self, Comma)?;
             }
         }
 
-        Ok(params)
+        expect_peek!(self, RParen, opened_at)?;
+        self.end_list(list);
+
+        Ok((params, rest))
+    }
+
+    fn parse_rest_parameter(&mut self) -> Result<String, ParserError> {
+        let TokenType::Identifier(name) = self.peeking_token.token_type.clone() else {
+            return Err(ParserError::unexpected(
+                &self.peeking_token,
+                vec![TokenType::identifier("")],
+            ));
+        };
+
+        self.next_token();
+
+        if self.peeking_token.token_type != TokenType::RParen {
+            return Err(ParserError::new(
+                ParserErrorKind::MisplacedRest,
+                &self.peeking_token.location,
+            ));
+        }
+
+        Ok(name)
     }
 
     fn parse_if_expression(&mut self) -> Result<Expression, ParserError> {
+        let position = self.position();
+
         expect_peek!(self, LParen)?;
 
+        let opened_at = self.current_token.location.clone();
+
         self.next_token();
 
         let condition = self.parse_expression(Precedence::LOWEST)?;
 
-        expect_peek!(self, RParen)?;
+        expect_peek!(self, RParen, opened_at)?;
 
         expect_peek!(self, LBrace)?;
 
@@ -225,10 +753,23 @@ impl<'a> Parser<'a> {
             alternative = Some(self.parse_block_statement()?);
         }
 
-        Ok(Expression::r#if(condition, consequence, alternative))
+        Ok(Expression::If {
+            condition: Box::new(condition),
+            consequence,
+            alternative,
+            position,
+        })
     }
 
     fn parse_block_statement(&mut self) -> Result<Vec<Statement>, ParserError> {
+        self.parse_nested(Self::parse_block_statements)
+    }
+
+    fn parse_block_statements(&mut self) -> Result<Vec<Statement>, ParserError> {
+        let opened_at = self.current_token.location.clone();
+        let body = self.source_map.bodies.len();
+        self.source_map.bodies.push(None);
+
         self.next_token();
 
         let mut statements = vec![];
@@ -241,29 +782,38 @@ impl<'a> Parser<'a> {
             self.next_token();
         }
 
+        if self.current_token.token_type == TokenType::EOF {
+            return Err(ParserError::new(
+                ParserErrorKind::MissingClosingDelimiter {
+                    delimiter: TokenType::RBrace,
+                    opened_at,
+                },
+                &self.current_token.location,
+            ));
+        }
+
+        self.source_map.bodies[body] = Some(self.current_token.location.clone());
+
         Ok(statements)
     }
 
     fn parse_grouped_expression(&mut self) -> Result<Expression, ParserError> {
+        let opened_at = self.current_token.location.clone();
+
         self.next_token();
 
-        let expression = self.parse_expression(Precedence::LOWEST);
+        let expression = self.parse_expression(Precedence::LOWEST)?;
 
-        expect_peek!(self, RParen)?;
+        expect_peek!(self, RParen, opened_at)?;
 
-        expression
+        Ok(expression)
     }
 
     fn parse_prefix_expression(&mut self) -> Result<Expression, ParserError> {
         let operator = match &self.current_token.token_type {
             TokenType::Bang => PrefixOperator::Not,
             TokenType::Minus => PrefixOperator::Negative,
-            token_type => {
-                return Err(ParserError::new(
-                    format!("unexpected token {}", token_type),
-                    &self.current_token.location,
-                ))
-            }
+            _ => return Err(ParserError::unexpected(&self.current_token, vec![])),
         };
 
         self.next_token();
@@ -286,12 +836,8 @@ impl<'a> Parser<'a> {
             TokenType::LT => InfixOperator::LessThan,
             TokenType::Modulo => InfixOperator::Modulo,
             TokenType::LParen => return self.parse_call_expression(lhs),
-            token_type => {
-                return Err(ParserError::new(
-                    format!("unexpected token {}", token_type),
-                    &self.current_token.location,
-                ))
-            }
+            TokenType::LBracket => return self.parse_index_expression(lhs),
+            _ => return Err(ParserError::unexpected(&self.current_token, vec![])),
         };
 
         self.next_token();
@@ -304,17 +850,21 @@ impl<'a> Parser<'a> {
         match &self.current_token.token_type {
             TokenType::True => Ok(Expression::Bool(true)),
             TokenType::False => Ok(Expression::Bool(false)),
-            _ => Err(ParserError::new(
-                format!("expected boolean, got {}", self.current_token.token_type),
-                &self.current_token.location,
+            _ => Err(ParserError::unexpected(
+                &self.current_token,
+                vec![TokenType::True, TokenType::False],
             )),
         }
     }
 
-    fn parse_integer(&self, literal: &String) -> Result<Expression, ParserError> {
-        literal.parse().map(Expression::Int).map_err(|_| {
+    fn parse_integer(&self, literal: &str) -> Result<Expression, ParserError> {
+        self.parse_integer_literal(literal).map(Expression::Int)
+    }
+
+    fn parse_integer_literal(&self, literal: &str) -> Result<i64, ParserError> {
+        literal.parse().map_err(|_| {
             ParserError::new(
-                format!("failed to parse integer {}", literal),
+                ParserErrorKind::InvalidIntegerLiteral(literal.to_string()),
                 &self.current_token.location,
             )
         })
@@ -323,15 +873,7 @@ impl<'a> Parser<'a> {
     fn parse_let_statement(&mut self) -> Result<Statement, ParserError> {
         self.next_token();
 
-        let identifier = match &self.current_token.token_type {
-            TokenType::Identifier(identifier) => identifier.clone(),
-            _ => {
-                return Err(ParserError::new(
-                    format!("expected identifier, got {}", self.current_token.token_type),
-                    &self.current_token.location.clone(),
-                ))
-            }
-        };
+        let pattern = self.parse_binding_pattern()?;
 
         expect_peek!(self, Assign)?;
 
@@ -343,7 +885,19 @@ impl<'a> Parser<'a> {
             self.next_token();
         }
 
-        Ok(Statement::r#let(identifier, expression))
+        Ok(Statement::r#let(pattern, expression))
+    }
+
+    fn parse_throw_statement(&mut self) -> Result<Statement, ParserError> {
+        self.next_token();
+
+        let expression = self.parse_expression(Precedence::LOWEST)?;
+
+        if self.peeking_token.token_type == TokenType::Semicolon {
+            self.next_token();
+        }
+
+        Ok(Statement::throw(expression))
     }
 
     fn parse_return_statement(&mut self) -> Result<Statement, ParserError> {
@@ -369,15 +923,20 @@ mod tests {
     use indoc::indoc;
 
     use crate::{
-        lexer::{lexer::Lexer, token::TokenType},
+        lexer::{lexer::Lexer, location::Location, token::TokenType},
         parser::ast::{
-            expression::Expression,
+            expression::{Expression, MatchArm, Parameter},
             operator::{InfixOperator, PrefixOperator},
+            pattern::Pattern,
             statement::Statement,
         },
     };
 
     use super::Parser;
+    use crate::parser::{
+        error::ParserErrorKind,
+        source_map::{List, Span},
+    };
 
     #[test]
     fn test_if_with_multiple_statements() {
@@ -392,7 +951,7 @@ mod tests {
         assert_eq!(program.statements.len(), 1);
         assert_eq!(
             program.statements[0],
-            Statement::Expression(Expression::r#if(
+            Statement::expression(Expression::r#if(
                 Expression::infix(
                     Expression::identifier("x"),
                     Expression::identifier("y"),
@@ -509,10 +1068,10 @@ mod tests {
         ));
         assert_eq!(
             program.statements[0],
-            Statement::Expression(Expression::r#if(
+            Statement::expression(Expression::r#if(
                 first_condition,
                 vec![first_let, first_return],
-                Some(vec![Statement::Expression(Expression::r#if(
+                Some(vec![Statement::expression(Expression::r#if(
                     second_condition,
                     vec![second_let, second_return],
                     Some(vec![third_return])
@@ -542,7 +1101,7 @@ mod tests {
                 "counter",
                 Expression::function(
                     vec!["x"],
-                    vec![Statement::Expression(Expression::r#if(
+                    vec![Statement::expression(Expression::r#if(
                         Expression::infix(
                             Expression::identifier("x"),
                             Expression::Int(100),
@@ -551,7 +1110,7 @@ mod tests {
                         vec![Statement::r#return(Expression::Bool(true))],
                         Some(vec![
                             Statement::r#let("foobar", Expression::Int(9999)),
-                            Statement::Expression(Expression::identifier("foobar"))
+                            Statement::expression(Expression::identifier("foobar"))
                         ])
                     ))]
                 )
@@ -578,13 +1137,13 @@ mod tests {
         assert_eq!(program.statements.len(), 1);
         assert_eq!(
             program.statements[0],
-            Statement::Expression(Expression::r#if(
+            Statement::expression(Expression::r#if(
                 Expression::infix(
                     Expression::identifier("x"),
                     Expression::identifier("y"),
                     InfixOperator::LessThan
                 ),
-                vec![Statement::Expression(Expression::r#if(
+                vec![Statement::expression(Expression::r#if(
                     Expression::infix(
                         Expression::identifier("x"),
                         Expression::identifier("y"),
@@ -607,7 +1166,7 @@ mod tests {
         assert_eq!(program.statements.len(), 1);
         assert_eq!(
             program.statements[0],
-            Statement::Expression(Expression::call(
+            Statement::expression(Expression::call(
                 Expression::identifier("add"),
                 vec![
                     Expression::Int(1),
@@ -633,7 +1192,7 @@ mod tests {
 
         assert_eq!(
             program.statements[0],
-            Statement::Expression(Expression::function(
+            Statement::expression(Expression::function(
                 vec!["x", "y"],
                 vec![Statement::expression(Expression::infix(
                     Expression::identifier("x"),
@@ -644,6 +1203,115 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_function_literal_with_destructuring_params() {
+        let mut parser = make_parser("fn([a, ..b], {c}) { a }");
+        let program = parser.parse_program();
+
+        assert_eq!(parser.errors.len(), 0);
+        assert_eq!(
+            program.statements[0],
+            Statement::expression(Expression::function(
+                vec![
+                    Pattern::array(
+                        vec![Pattern::identifier("a")],
+                        Some(Pattern::identifier("b"))
+                    ),
+                    Pattern::hash(vec![("c", Pattern::identifier("c"))]),
+                ],
+                vec![Statement::expression(Expression::identifier("a"))]
+            ))
+        );
+    }
+
+    #[test]
+    fn test_function_literal_with_default_and_rest_params() {
+        let mut parser = make_parser("fn(a, b = a * 2, ...rest) { a }");
+        let program = parser.parse_program();
+
+        assert_eq!(parser.errors.len(), 0);
+        assert_eq!(
+            program.statements[0],
+            Statement::expression(Expression::variadic_function(
+                vec![
+                    Parameter::from("a"),
+                    Parameter::new(
+                        Pattern::identifier("b"),
+                        Some(Expression::infix(
+                            Expression::identifier("a"),
+                            Expression::Int(2),
+                            InfixOperator::Mult
+                        ))
+                    ),
+                ],
+                "rest",
+                vec![Statement::expression(Expression::identifier("a"))]
+            ))
+        );
+    }
+
+    #[test]
+    fn test_invalid_function_params() {
+        let tests = vec![
+            ("fn(...rest, a) { a }", ParserErrorKind::MisplacedRest),
+            (
+                "fn(...) { 1 }",
+                ParserErrorKind::UnexpectedToken {
+                    found: TokenType::RParen,
+                    expected: vec![TokenType::identifier("")],
+                },
+            ),
+            (
+                "fn(a b) { a }",
+                ParserErrorKind::UnexpectedToken {
+                    found: TokenType::identifier("b"),
+                    expected: vec![TokenType::Comma],
+                },
+            ),
+        ];
+
+        for test in tests {
+            let mut parser = make_parser(test.0);
+            parser.parse_program();
+            assert_eq!(parser.errors.len(), 1, "{}", test.0);
+            assert_eq!(parser.errors[0].kind, test.1, "{}", test.0);
+        }
+    }
+
+    #[test]
+    fn test_call_expression_with_named_arguments() {
+        let mut parser = make_parser("add(1, b: 2, c: 3 + 4);");
+        let program = parser.parse_program();
+
+        assert_eq!(parser.errors.len(), 0);
+        assert_eq!(
+            program.statements[0],
+            Statement::expression(Expression::call_with_named(
+                Expression::identifier("add"),
+                vec![Expression::Int(1)],
+                vec![
+                    ("b", Expression::Int(2)),
+                    (
+                        "c",
+                        Expression::infix(
+                            Expression::Int(3),
+                            Expression::Int(4),
+                            InfixOperator::Add
+                        )
+                    ),
+                ]
+            ))
+        );
+
+        let mut parser = make_parser("add(b: 2, 1);");
+        parser.parse_program();
+        assert_eq!(parser.errors.len(), 1);
+        assert_eq!(
+            parser.errors[0].kind,
+            ParserErrorKind::PositionalAfterNamedArgument
+        );
+    }
+
     #[test]
     fn test_parsing_infix_expressions_with_integers() {
         let mut parser = make_parser(indoc! {"
@@ -698,7 +1366,7 @@ mod tests {
         assert_eq!(program.statements.len(), 3);
         assert_eq!(
             program.statements[0],
-            Statement::Expression(Expression::infix(
+            Statement::expression(Expression::infix(
                 Expression::Int(5),
                 Expression::infix(Expression::Int(7), Expression::Int(10), InfixOperator::Mult),
                 InfixOperator::Add
@@ -706,7 +1374,7 @@ mod tests {
         );
         assert_eq!(
             program.statements[1],
-            Statement::Expression(Expression::infix(
+            Statement::expression(Expression::infix(
                 Expression::infix(Expression::Int(1), Expression::Int(2), InfixOperator::Sub),
                 Expression::Int(3),
                 InfixOperator::Add
@@ -714,7 +1382,7 @@ mod tests {
         );
         assert_eq!(
             program.statements[2],
-            Statement::Expression(Expression::infix(
+            Statement::expression(Expression::infix(
                 Expression::infix(Expression::Int(5), Expression::Int(7), InfixOperator::Mult),
                 Expression::Int(10),
                 InfixOperator::Add
@@ -787,13 +1455,13 @@ mod tests {
 
         assert_eq!(
             program.statements[0],
-            Statement::Expression(Expression::r#if(
+            Statement::expression(Expression::r#if(
                 Expression::infix(
                     Expression::identifier("x"),
                     Expression::identifier("y"),
                     InfixOperator::LessThan,
                 ),
-                vec![Statement::Expression(Expression::identifier("x"))],
+                vec![Statement::expression(Expression::identifier("x"))],
                 None
             ))
         )
@@ -811,14 +1479,14 @@ mod tests {
 
         assert_eq!(
             program.statements[0],
-            Statement::Expression(Expression::r#if(
+            Statement::expression(Expression::r#if(
                 Expression::infix(
                     Expression::identifier("x"),
                     Expression::identifier("y"),
                     InfixOperator::LessThan,
                 ),
-                vec![Statement::Expression(Expression::identifier("x"))],
-                Some(vec![Statement::Expression(Expression::identifier("y"))])
+                vec![Statement::expression(Expression::identifier("x"))],
+                Some(vec![Statement::expression(Expression::identifier("y"))])
             ))
         )
     }
@@ -850,6 +1518,70 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_destructuring_let_statement() {
+        let mut parser = make_parser(indoc! {"
+            let [a, b, ..rest] = arr;
+            let {x, y: renamed} = h;
+            let [{x}, ..] = h;
+        "});
+
+        let program = parser.parse_program();
+
+        assert_eq!(parser.errors.len(), 0);
+        assert_eq!(program.statements.len(), 3);
+
+        assert_eq!(
+            program.statements[0],
+            Statement::r#let(
+                Pattern::array(
+                    vec![Pattern::identifier("a"), Pattern::identifier("b")],
+                    Some(Pattern::identifier("rest"))
+                ),
+                Expression::identifier("arr")
+            )
+        );
+        assert_eq!(
+            program.statements[1],
+            Statement::r#let(
+                Pattern::hash(vec![
+                    ("x", Pattern::identifier("x")),
+                    ("y", Pattern::identifier("renamed"))
+                ]),
+                Expression::identifier("h")
+            )
+        );
+        assert_eq!(
+            program.statements[2],
+            Statement::r#let(
+                Pattern::array(
+                    vec![Pattern::hash(vec![("x", Pattern::identifier("x"))])],
+                    Some(Pattern::Wildcard)
+                ),
+                Expression::identifier("h")
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_let_statement() {
+        let mut parser = make_parser("let 5 = x;");
+        parser.parse_program();
+        assert_eq!(parser.errors.len(), 1);
+        assert_eq!(
+            parser.errors[0].kind,
+            ParserErrorKind::UnexpectedToken {
+                found: TokenType::integer("5"),
+                expected: vec![
+                    TokenType::identifier(""),
+                    TokenType::LBracket,
+                    TokenType::LBrace
+                ],
+            }
+        );
+        assert_eq!(parser.errors[0].location, Location::new(1, 5));
+    }
+
     #[test]
     fn test_parse_return_statement() {
         let mut parser = make_parser(indoc! {"
@@ -968,6 +1700,178 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_collection_literals() {
+        let mut parser = make_parser(indoc! {"
+            \"hello world\";
+            [1, 2 * 2, \"three\"];
+            [];
+            {\"one\": 1, 2: true};
+            {};
+        "});
+        let program = parser.parse_program();
+
+        assert_eq!(parser.errors.len(), 0);
+        assert_eq!(program.statements.len(), 5);
+        assert_eq!(
+            program.statements[0],
+            Statement::expression(Expression::string("hello world"))
+        );
+        assert_eq!(
+            program.statements[1],
+            Statement::expression(Expression::Array(vec![
+                Expression::Int(1),
+                Expression::infix(Expression::Int(2), Expression::Int(2), InfixOperator::Mult),
+                Expression::string("three"),
+            ]))
+        );
+        assert_eq!(
+            program.statements[2],
+            Statement::expression(Expression::Array(vec![]))
+        );
+        assert_eq!(
+            program.statements[3],
+            Statement::expression(Expression::Hash(vec![
+                (Expression::string("one"), Expression::Int(1)),
+                (Expression::Int(2), Expression::Bool(true)),
+            ]))
+        );
+        assert_eq!(
+            program.statements[4],
+            Statement::expression(Expression::Hash(vec![]))
+        );
+    }
+
+    #[test]
+    fn test_match_expression() {
+        let mut parser = make_parser(indoc! {"
+            match (x) {
+                0 => \"zero\",
+                -1 => { let y = 1; y },
+                [head, ..tail] if head > 0 => head,
+                [_, ..] => null,
+                {name, \"age\": age} => age,
+                _ => false
+            }
+        "});
+        let program = parser.parse_program();
+
+        assert_eq!(parser.errors.len(), 0);
+        assert_eq!(program.statements.len(), 1);
+        assert_eq!(
+            program.statements[0],
+            Statement::expression(Expression::r#match(
+                Expression::identifier("x"),
+                vec![
+                    MatchArm::new(
+                        Pattern::Int(0),
+                        None,
+                        vec![Statement::expression(Expression::string("zero"))]
+                    ),
+                    MatchArm::new(
+                        Pattern::Int(-1),
+                        None,
+                        vec![
+                            Statement::r#let("y", Expression::Int(1)),
+                            Statement::expression(Expression::identifier("y"))
+                        ]
+                    ),
+                    MatchArm::new(
+                        Pattern::array(
+                            vec![Pattern::identifier("head")],
+                            Some(Pattern::identifier("tail"))
+                        ),
+                        Some(Expression::infix(
+                            Expression::identifier("head"),
+                            Expression::Int(0),
+                            InfixOperator::GreaterThan
+                        )),
+                        vec![Statement::expression(Expression::identifier("head"))]
+                    ),
+                    MatchArm::new(
+                        Pattern::array(vec![Pattern::Wildcard], Some(Pattern::Wildcard)),
+                        None,
+                        vec![Statement::expression(Expression::Null)]
+                    ),
+                    MatchArm::new(
+                        Pattern::hash(vec![
+                            ("name", Pattern::identifier("name")),
+                            ("age", Pattern::identifier("age"))
+                        ]),
+                        None,
+                        vec![Statement::expression(Expression::identifier("age"))]
+                    ),
+                    MatchArm::new(
+                        Pattern::Wildcard,
+                        None,
+                        vec![Statement::expression(Expression::Bool(false))]
+                    ),
+                ]
+            ))
+        );
+    }
+
+    #[test]
+    fn test_match_expression_errors() {
+        let tests = vec![
+            "match (x) { [..rest, last] => 1 }",
+            "match (x) { 1 + 2 => 1 }",
+            "match (x) { {\"key\"} => 1 }",
+            "match (x) { _ 1 }",
+        ];
+
+        for test in tests {
+            let mut parser = make_parser(test);
+            parser.parse_program();
+            assert_eq!(parser.errors.len(), 1, "{}", test);
+        }
+    }
+
+    #[test]
+    fn test_try_expression() {
+        let mut parser = make_parser(indoc! {"
+            try {
+                throw 1;
+            } catch ({message}) {
+                message
+            } finally {
+                2
+            };
+            try { 1 } finally { 2 }
+        "});
+        let program = parser.parse_program();
+
+        assert_eq!(parser.errors.len(), 0);
+        assert_eq!(program.statements.len(), 2);
+        assert_eq!(
+            program.statements[0],
+            Statement::expression(Expression::r#try(
+                vec![Statement::throw(Expression::Int(1))],
+                Some((
+                    Pattern::hash(vec![("message", Pattern::identifier("message"))]),
+                    vec![Statement::expression(Expression::identifier("message"))]
+                )),
+                Some(vec![Statement::expression(Expression::Int(2))])
+            ))
+        );
+        assert_eq!(
+            program.statements[1],
+            Statement::expression(Expression::r#try(
+                vec![Statement::expression(Expression::Int(1))],
+                None,
+                Some(vec![Statement::expression(Expression::Int(2))])
+            ))
+        );
+
+        let mut parser = make_parser("try { 1 }");
+        parser.parse_program();
+        assert_eq!(parser.errors.len(), 1);
+        assert_eq!(
+            parser.errors[0].kind,
+            ParserErrorKind::MissingCatchOrFinally
+        );
+    }
+
     #[test]
     fn test_precedences() {
         let tests = vec![
@@ -995,6 +1899,8 @@ mod tests {
             ("2 / (5 + 5)", "(2 / (5 + 5))"),
             ("-(5 + 5)", "(-(5 + 5))"),
             ("!(true == true)", "(!(true == true))"),
+            ("a * [1, 2][b * c] * d", "((a * ([1, 2][(b * c)])) * d)"),
+            ("add(a * b[2], b[1])", "add((a * (b[2])), (b[1]))"),
         ];
 
         for test in tests {
@@ -1005,6 +1911,155 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parser_error_kinds() {
+        let tests = vec![
+            (
+                "let x = ;",
+                ParserErrorKind::ExpectedExpression(TokenType::Semicolon),
+            ),
+            (
+                "99999999999999999999",
+                ParserErrorKind::InvalidIntegerLiteral("99999999999999999999".to_string()),
+            ),
+            (
+                "let x = 1 +",
+                ParserErrorKind::UnexpectedEof { expected: vec![] },
+            ),
+            (
+                "add(1, 2",
+                ParserErrorKind::MissingClosingDelimiter {
+                    delimiter: TokenType::RParen,
+                    opened_at: Location::new(1, 4),
+                },
+            ),
+            (
+                "if (x) {\n  1",
+                ParserErrorKind::MissingClosingDelimiter {
+                    delimiter: TokenType::RBrace,
+                    opened_at: Location::new(1, 8),
+                },
+            ),
+            ("let s = \"abc", ParserErrorKind::UnterminatedString),
+        ];
+
+        for test in tests {
+            let mut parser = make_parser(test.0);
+            parser.parse_program();
+            assert_eq!(parser.errors.len(), 1, "{}", test.0);
+            assert_eq!(parser.errors[0].kind, test.1, "{}", test.0);
+        }
+    }
+
+    #[test]
+    fn test_nesting_depth_limit() {
+        let input = format!("{}1{}", "(".repeat(200), ")".repeat(200));
+        let mut parser = make_parser(&input);
+        parser.parse_program();
+        assert_eq!(parser.errors.len(), 0);
+
+        let tests = vec![
+            format!("{}1{}", "(".repeat(300), ")".repeat(300)),
+            format!("{}1", "-".repeat(300)),
+            format!("{}true", "!(".repeat(150)),
+            format!("{}{};", "[".repeat(300), "]".repeat(300)),
+            format!("{}1{};", "{1: ".repeat(300), "}".repeat(300)),
+            format!("{}{};", "fn() { ".repeat(300), "}".repeat(300)),
+            format!("{}{};", "if (true) { ".repeat(300), "}".repeat(300)),
+            format!(
+                "match (x) {{ {}{} => 1 }};",
+                "[".repeat(300),
+                "]".repeat(300)
+            ),
+            format!("let {}x{} = 1;", "{a: ".repeat(300), "}".repeat(300)),
+            format!("1{};", " + 1".repeat(300)),
+            format!("f{};", "()".repeat(300)),
+        ];
+
+        for test in tests {
+            let mut parser = make_parser(&test);
+            parser.parse_program();
+            assert_eq!(parser.errors.len(), 1);
+            assert_eq!(parser.errors[0].kind, ParserErrorKind::NestingTooDeep(256));
+        }
+    }
+
+    #[test]
+    fn test_incomplete_input() {
+        let tests = vec![
+            ("let f = fn(x) {", true),
+            ("[1, 2", true),
+            ("match (x) {", true),
+            ("let x = 1 +", true),
+            ("puts(\"a", true),
+            ("let 5 = x;", false),
+            ("add(b: 2, 1);", false),
+        ];
+
+        for test in tests {
+            let mut parser = make_parser(test.0);
+            parser.parse_program();
+            assert!(!parser.errors.is_empty(), "{}", test.0);
+            assert_eq!(
+                parser.errors.iter().all(|error| error.is_incomplete()),
+                test.1,
+                "{}",
+                test.0
+            );
+        }
+    }
+
+    #[test]
+    fn test_source_map() {
+        let mut parser = make_parser(indoc! {"
+            // answer
+            let f = fn(x) {
+                x
+            };
+            match (f(1)) { 1 => true, _ => { false } };
+        "});
+        parser.parse_program();
+        assert_eq!(parser.errors.len(), 0);
+
+        let span = |start: (usize, usize), end: (usize, usize)| Span {
+            start: Location::new(start.0, start.1),
+            end: Location::new(end.0, end.1),
+        };
+        assert_eq!(
+            parser.source_map.statements,
+            vec![
+                span((2, 1), (4, 2)),
+                span((3, 5), (3, 5)),
+                span((5, 1), (5, 43)),
+                span((5, 21), (5, 21)),
+                span((5, 34), (5, 34)),
+            ]
+        );
+        assert_eq!(
+            parser.source_map.bodies,
+            vec![Some(Location::new(4, 1)), None, Some(Location::new(5, 40))]
+        );
+        assert_eq!(
+            parser.source_map.lists,
+            vec![
+                List {
+                    items: vec![span((2, 12), (2, 13))],
+                    close: Location::new(2, 13),
+                },
+                List {
+                    items: vec![span((5, 10), (5, 11))],
+                    close: Location::new(5, 11),
+                },
+                List {
+                    items: vec![span((5, 16), (5, 25)), span((5, 27), (5, 42))],
+                    close: Location::new(5, 42),
+                },
+            ]
+        );
+        assert_eq!(parser.source_map.comments.len(), 1);
+        assert_eq!(parser.source_map.comments[0].text, " answer");
+    }
+
     fn make_parser<'a>(input: &'a str) -> Parser<'a> {
         let lexer = Lexer::new(input);
         let parser = Parser::new(lexer);
@@ -1,6 +1,8 @@
 use crate::lexer::token::{Token, TokenType};
 
-#[derive(Debug, PartialEq, PartialOrd)]
+use super::ast::operator::InfixOperator;
+
+#[derive(Debug, PartialEq, PartialOrd, Clone, Copy)]
 pub enum Precedence {
     LOWEST = 1,
     EQUALS = 2,
@@ -9,6 +11,7 @@ pub enum Precedence {
     PRODUCT = 5,
     PREFIX = 6,
     CALL = 7,
+    INDEX = 8,
 }
 
 impl From<&Token> for Precedence {
@@ -23,12 +26,24 @@ impl From<&Token> for Precedence {
             TokenType::GT => Precedence::LESSGREATER,
             TokenType::LT => Precedence::LESSGREATER,
             TokenType::LParen => Precedence::CALL,
+            TokenType::LBracket => Precedence::INDEX,
             TokenType::Modulo => Precedence::PRODUCT,
             _ => Precedence::LOWEST,
         }
     }
 }
 
+impl From<&InfixOperator> for Precedence {
+    fn from(operator: &InfixOperator) -> Self {
+        match operator {
+            InfixOperator::Equal | InfixOperator::NotEqual => Precedence::EQUALS,
+            InfixOperator::GreaterThan | InfixOperator::LessThan => Precedence::LESSGREATER,
+            InfixOperator::Add | InfixOperator::Sub => Precedence::SUM,
+            InfixOperator::Mult | InfixOperator::Div | InfixOperator::Modulo => Precedence::PRODUCT,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::parser::precedence::Precedence;
@@ -41,5 +56,6 @@ mod tests {
         assert!(Precedence::SUM < Precedence::PRODUCT);
         assert!(Precedence::PRODUCT < Precedence::PREFIX);
         assert!(Precedence::PREFIX < Precedence::CALL);
+        assert!(Precedence::CALL < Precedence::INDEX);
     }
 }
@@ -0,0 +1,27 @@
+use crate::lexer::{location::Location, token::Comment};
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Span {
+    pub start: Location,
+    pub end: Location,
+}
+
+// a comma-separated list: each item spans from its first token to the comma or
+// closing delimiter after it
+#[derive(Debug, PartialEq, Clone)]
+pub struct List {
+    pub items: Vec<Span>,
+    pub close: Location,
+}
+
+// positions the AST does not keep, recorded in the order the parser meets them:
+// one span per statement, one entry per statement body, holding the closing
+// brace for braced bodies and None for the bare expression body of a match arm,
+// and one list per argument, parameter, array, hash and match arm list
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    pub statements: Vec<Span>,
+    pub bodies: Vec<Option<Location>>,
+    pub lists: Vec<List>,
+    pub comments: Vec<Comment>,
+}
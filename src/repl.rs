@@ -1,74 +1,107 @@
 use std::{
     io::{self, Stdin, Write},
-    rc::Rc,
-};
-
-use crate::{
-    evaluator::{
-        environment::Environment,
-        evaluator::{EvaluationError, Evaluator},
-        object::Object,
-    },
-    lexer::lexer::Lexer,
-    parser::{
-        ast::{node::Node, program::Program},
-        parser::Parser,
+    process,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, OnceLock,
     },
 };
 
+use interpreter::{parser::error::ParserError, Error, Interpreter};
+
 pub struct Repl {
     stdin: Stdin,
+    interpreter: Interpreter,
+    interrupted: Arc<AtomicBool>,
 }
 
 const PROMPT: &'static str = ">> ";
+const CONTINUATION_PROMPT: &str = ".. ";
+
+// a process can only have one Ctrl-C handler, so every Repl shares it
+static INTERRUPTED: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+static EVALUATING: AtomicBool = AtomicBool::new(false);
+
+// Ctrl-C stops a running evaluation, and leaves the REPL when it is waiting for input
+fn interrupt_handle() -> Arc<AtomicBool> {
+    let interrupted = INTERRUPTED.get_or_init(|| {
+        let interrupted = Arc::new(AtomicBool::new(false));
+        let handle = Arc::clone(&interrupted);
+        let result = ctrlc::set_handler(move || {
+            if EVALUATING.load(Ordering::Relaxed) {
+                handle.store(true, Ordering::Relaxed);
+            } else {
+                println!();
+                process::exit(130);
+            }
+        });
+        if let Err(err) = result {
+            eprintln!("failed to set Ctrl-C handler: {}", err);
+        }
+        interrupted
+    });
+    Arc::clone(interrupted)
+}
 
 impl Repl {
     pub fn new(stdin: Stdin) -> Self {
-        Repl { stdin }
+        let interrupted = interrupt_handle();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_interrupt_handle(Arc::clone(&interrupted));
+
+        Repl {
+            stdin,
+            interpreter,
+            interrupted,
+        }
     }
 
-    pub fn start(&self) {
+    pub fn start(&mut self) {
         let mut line = String::new();
-        self.read_input(&mut line, &self.stdin);
+        self.read_input(&mut line, &self.stdin, PROMPT);
 
         while !line.trim().is_empty() {
-            let lexer = Lexer::new(&line);
-            let mut parser = Parser::new(lexer);
-            let program = parser.parse_program();
+            let mut input = line.clone();
+            let result = loop {
+                self.interrupted.store(false, Ordering::Relaxed);
+                EVALUATING.store(true, Ordering::Relaxed);
+                let result = self.interpreter.eval_str(&input);
+                EVALUATING.store(false, Ordering::Relaxed);
 
-            if parser.errors.len() == 0 {
-                match self.evaluate_program(program) {
-                    Ok(object) => println!("{}", object),
-                    Err(err) => println!("{}", err),
+                match result {
+                    Err(Error::Parse(errors)) if self.needs_more_input(&errors) => {
+                        self.read_input(&mut line, &self.stdin, CONTINUATION_PROMPT);
+                        if line.trim().is_empty() {
+                            break Err(Error::Parse(errors));
+                        }
+                        input.push_str(&line);
+                    }
+                    result => break result,
                 }
-            } else {
-                println!("Woops! parser got {} errors!", parser.errors.len());
-                for error in parser.errors {
-                    println!("{}", error);
+            };
+
+            match result {
+                Ok(object) => println!("{}", object),
+                Err(Error::Parse(errors)) => {
+                    println!("Woops! parser got {} errors!", errors.len());
+                    for error in errors {
+                        println!("{}", error);
+                    }
                 }
+                Err(Error::Evaluation(err)) => println!("{}", err),
             }
 
-            self.read_input(&mut line, &self.stdin);
+            self.read_input(&mut line, &self.stdin, PROMPT);
         }
     }
 
-    fn evaluate_program(&self, program: Program) -> Result<Object, EvaluationError> {
-        let environment = Environment::new();
-        let mut evaluator = Evaluator::new();
-
-        evaluator.eval(Node::Program(program), Rc::clone(&environment))
-    }
-
-    #[allow(dead_code)]
-    fn print_program(&self, program: Program) {
-        for statement in program.statements {
-            println!("{:?}", statement);
-        }
+    fn needs_more_input(&self, errors: &[ParserError]) -> bool {
+        !errors.is_empty() && errors.iter().all(|error| error.is_incomplete())
     }
 
-    fn read_input(&self, input: &mut String, stdin: &Stdin) {
+    fn read_input(&self, input: &mut String, stdin: &Stdin, prompt: &str) {
         input.clear();
-        print!("{PROMPT}");
+        print!("{prompt}");
         io::stdout().flush().expect("failed to flush stdout");
         stdin.read_line(input).expect("failed to read line");
     }
@@ -0,0 +1,479 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    evaluator::{
+        environment::Environment,
+        error::{EvaluationError, EvaluationErrorKind},
+        evaluator::Evaluator,
+        native::{Arity, NativeFunction},
+        object::{HashKey, Object},
+    },
+    lexer::location::Location,
+    parser::ast::{
+        expression::Expression, pattern::Pattern, program::Program, statement::Statement,
+    },
+};
+
+// the kind of error the assertion builtins throw
+pub const ASSERTION_ERROR: &str = "AssertionError";
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct TestCase {
+    pub name: String,
+    pub location: Location,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Failure {
+    pub kind: String,
+    pub message: String,
+    // where the failing assertion or error is, when it is known
+    pub location: Option<Location>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Outcome {
+    Passed,
+    Failed(Failure),
+}
+
+// the `*_test.mk` files under path, in a stable order; a file path is returned as is
+pub fn discover(path: &Path) -> io::Result<Vec<PathBuf>> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut files = vec![];
+    for entry in fs::read_dir(path)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(discover(&path)?);
+        } else if path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with("_test.mk"))
+        {
+            files.push(path);
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+// the top-level `let test_... = fn() { ... }` bindings of a program
+pub fn test_cases(program: &Program) -> Vec<TestCase> {
+    program
+        .statements
+        .iter()
+        .filter_map(|statement| match statement {
+            Statement::Let {
+                pattern: Pattern::Identifier(name, position),
+                value: Expression::Function { .. },
+                ..
+            } if name.starts_with("test_") => Some(TestCase {
+                name: name.clone(),
+                location: position.0.clone(),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+// runs the program in a fresh environment, then calls the named test in it
+pub fn run_test(program: &Program, name: &str) -> Outcome {
+    let environment = Environment::new();
+    for native in natives() {
+        environment.borrow_mut().register(native);
+    }
+
+    let mut evaluator = Evaluator::new();
+
+    let result = evaluator
+        .eval(program.clone(), Rc::clone(&environment))
+        .and_then(|_| {
+            let test = environment.borrow().get(name).ok_or_else(|| {
+                EvaluationError::new(EvaluationErrorKind::UnboundIdentifier(name.to_string()))
+            })?;
+            evaluator.call(&test, vec![])
+        });
+
+    match result {
+        Ok(_) => Outcome::Passed,
+        Err(err) => Outcome::Failed(Failure {
+            kind: err.kind.name().to_string(),
+            message: err.kind.message(),
+            location: err.location.clone(),
+        }),
+    }
+}
+
+// the assertion builtins every test can call
+pub fn natives() -> Vec<NativeFunction> {
+    let assert = NativeFunction::new("assert", Arity::range(1, 2), |arguments| {
+        match (&arguments[0], arguments.get(1)) {
+            (Object::Boolean(true), _) => Ok(Object::Null),
+            (_, Some(message)) => Err(assertion_error(message.to_string())),
+            (value, None) => Err(assertion_error(format!(
+                "assertion failed: expected true, got {}",
+                describe(value)
+            ))),
+        }
+    });
+
+    let assert_eq = NativeFunction::new("assert_eq", Arity::exact(2), |arguments| {
+        let (actual, expected) = (&arguments[0], &arguments[1]);
+        match first_difference(actual, expected, String::new()) {
+            None => Ok(Object::Null),
+            Some((path, actual_part, expected_part)) if path.is_empty() => {
+                Err(assertion_error(format!(
+                    "values differ\n  expected: {}\n    actual: {}",
+                    describe_part(expected_part),
+                    describe_part(actual_part)
+                )))
+            }
+            Some((path, actual_part, expected_part)) => Err(assertion_error(format!(
+                "values differ at {}: expected {}, got {}\n  expected: {}\n    actual: {}",
+                path,
+                describe_part(expected_part),
+                describe_part(actual_part),
+                describe(expected),
+                describe(actual)
+            ))),
+        }
+    });
+
+    let assert_error = NativeFunction::with_context(
+        "assert_error",
+        Arity::range(1, 2),
+        |evaluator, arguments| {
+            let kind = match arguments.get(1) {
+                None => None,
+                Some(Object::String(kind)) => Some(kind),
+                Some(value) => {
+                    return Err(EvaluationError::new(EvaluationErrorKind::Thrown {
+                        kind: "TypeError".to_string(),
+                        message: format!("assert_error expects an error kind, got {}", value),
+                    }))
+                }
+            };
+
+            match evaluator.call(&arguments[0], vec![]) {
+                Ok(value) => Err(assertion_error(format!(
+                    "expected an error, but the function returned {}",
+                    describe(&value)
+                ))),
+                // running out of fuel or time is not the error a test is looking for
+                Err(err) if err.kind.is_limit() => Err(err),
+                Err(err) => match kind {
+                    Some(kind) if kind != err.kind.name() => Err(assertion_error(format!(
+                        "expected a {} error, got {}: {}",
+                        kind,
+                        err.kind.name(),
+                        err.kind.message()
+                    ))),
+                    _ => Ok(err.into_object()),
+                },
+            }
+        },
+    );
+
+    vec![assert, assert_eq, assert_error]
+}
+
+fn assertion_error(message: String) -> EvaluationError {
+    EvaluationError::new(EvaluationErrorKind::Thrown {
+        kind: ASSERTION_ERROR.to_string(),
+        message,
+    })
+}
+
+// where two values first differ, like `[1]["name"]`, and what each side has there
+fn first_difference<'a>(
+    actual: &'a Object,
+    expected: &'a Object,
+    path: String,
+) -> Option<(String, Option<&'a Object>, Option<&'a Object>)> {
+    match (actual, expected) {
+        (Object::Array(actual_elements), Object::Array(expected_elements)) => {
+            let length = actual_elements.len().max(expected_elements.len());
+            (0..length).find_map(|index| {
+                let path = format!("{}[{}]", path, index);
+                match (actual_elements.get(index), expected_elements.get(index)) {
+                    (Some(actual), Some(expected)) => first_difference(actual, expected, path),
+                    (actual, expected) => Some((path, actual, expected)),
+                }
+            })
+        }
+        (Object::Hash(actual_entries), Object::Hash(expected_entries)) => {
+            let mut keys: Vec<&HashKey> = actual_entries
+                .keys()
+                .chain(expected_entries.keys())
+                .collect();
+            keys.sort();
+            keys.dedup();
+
+            keys.into_iter().find_map(|key| {
+                let path = match key {
+                    HashKey::String(key) => format!("{}[{:?}]", path, key),
+                    key => format!("{}[{}]", path, key),
+                };
+                match (actual_entries.get(key), expected_entries.get(key)) {
+                    (Some(actual), Some(expected)) => first_difference(actual, expected, path),
+                    (actual, expected) => Some((path, actual, expected)),
+                }
+            })
+        }
+        // closures made by the same literal in the same environment are the same function;
+        // comparing them field by field would recurse through the environment holding them
+        (
+            Object::Function {
+                position,
+                environment,
+                ..
+            },
+            Object::Function {
+                position: expected_position,
+                environment: expected_environment,
+                ..
+            },
+        ) if position.0 == expected_position.0 && Rc::ptr_eq(environment, expected_environment) => {
+            None
+        }
+        (Object::Function { .. }, _) | (_, Object::Function { .. }) => {
+            Some((path, Some(actual), Some(expected)))
+        }
+        (actual, expected) if actual == expected => None,
+        (actual, expected) => Some((path, Some(actual), Some(expected))),
+    }
+}
+
+// strings are quoted so that `"1"` and `1` can be told apart
+fn describe(value: &Object) -> String {
+    match value {
+        Object::String(string) => format!("{:?}", string),
+        value => value.to_string(),
+    }
+}
+
+fn describe_part(value: Option<&Object>) -> String {
+    value.map_or("nothing".to_string(), describe)
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::{lexer::lexer::Lexer, parser::parser::Parser};
+
+    use super::*;
+
+    fn run(source: &str) -> Vec<(String, Outcome)> {
+        let program = Parser::new(Lexer::new(source)).parse_program();
+        test_cases(&program)
+            .into_iter()
+            .map(|test| {
+                let outcome = run_test(&program, &test.name);
+                (test.name, outcome)
+            })
+            .collect()
+    }
+
+    fn failure(kind: &str, message: &str, line: usize, column: usize) -> Outcome {
+        Outcome::Failed(Failure {
+            kind: kind.to_string(),
+            message: message.to_string(),
+            location: Some(Location::new(line, column)),
+        })
+    }
+
+    #[test]
+    fn test_test_cases() {
+        let program = Parser::new(Lexer::new(indoc! {"
+            let helper = fn() { 1 };
+            let test_one = fn() { assert(true) };
+            let test_value = 2;
+            let test_two = fn() { assert(true) };
+        "}))
+        .parse_program();
+
+        assert_eq!(
+            test_cases(&program),
+            vec![
+                TestCase {
+                    name: "test_one".to_string(),
+                    location: Location::new(2, 5),
+                },
+                TestCase {
+                    name: "test_two".to_string(),
+                    location: Location::new(4, 5),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_assertions() {
+        let results = run(indoc! {"
+            let add = fn(a, b) { a + b };
+            let test_passes = fn() {
+                assert(add(1, 2) == 3);
+                assert_eq(add(1, 2), 3);
+                let error = assert_error(fn() { 1 / 0 }, \"DivisionByZero\");
+                assert_eq(error[\"message\"], \"cannot divide by zero\");
+            };
+            let test_assert = fn() {
+                assert(add(1, 1) == 3, \"one and one\");
+            };
+            let test_assert_eq = fn() {
+                let x = 1;
+                assert_eq(add(x, 1), 3)
+            };
+            let test_nested = fn() {
+                assert_eq({\"a\": [1, add(1, 1)]}, {\"a\": [1, 3]});
+            };
+            let test_no_error = fn() {
+                assert_error(fn() { 1 })
+            };
+            let test_wrong_error = fn() {
+                assert_error(fn() { throw \"boom\"; }, \"DivisionByZero\");
+            };
+            let check = fn(x) { assert_eq(x, 2) };
+            let test_helper = fn() {
+                assert_error(fn() { 1 / 0 });
+                check(add(1, 2))
+            };
+        "});
+
+        assert_eq!(
+            results,
+            vec![
+                ("test_passes".to_string(), Outcome::Passed),
+                (
+                    "test_assert".to_string(),
+                    failure(ASSERTION_ERROR, "one and one", 9, 5)
+                ),
+                (
+                    "test_assert_eq".to_string(),
+                    failure(
+                        ASSERTION_ERROR,
+                        "values differ\n  expected: 3\n    actual: 2",
+                        13,
+                        5
+                    )
+                ),
+                (
+                    "test_nested".to_string(),
+                    failure(
+                        ASSERTION_ERROR,
+                        "values differ at [\"a\"][1]: expected 3, got 2\n  \
+                         expected: {a: [1, 3]}\n    actual: {a: [1, 2]}",
+                        16,
+                        5
+                    )
+                ),
+                (
+                    "test_no_error".to_string(),
+                    failure(
+                        ASSERTION_ERROR,
+                        "expected an error, but the function returned 1",
+                        19,
+                        5
+                    )
+                ),
+                (
+                    "test_wrong_error".to_string(),
+                    failure(
+                        ASSERTION_ERROR,
+                        "expected a DivisionByZero error, got Error: boom",
+                        22,
+                        5
+                    )
+                ),
+                (
+                    "test_helper".to_string(),
+                    failure(
+                        ASSERTION_ERROR,
+                        "values differ\n  expected: 2\n    actual: 3",
+                        24,
+                        21
+                    )
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_functions() {
+        let results = run(indoc! {"
+            let f = fn(x) { x };
+            let g = fn(x) { x };
+            let call = fn() { f + 1 };
+            let test_same = fn() {
+                assert_eq(f, f);
+                assert_eq([f, {\"g\": g}], [f, {\"g\": g}]);
+            };
+            let test_different = fn() {
+                assert_eq(f, g);
+            };
+            let test_assert = fn() {
+                assert(f);
+            };
+            let test_mismatch = fn() {
+                call();
+            };
+        "});
+
+        assert_eq!(results[0], ("test_same".to_string(), Outcome::Passed));
+        assert_eq!(
+            results[1],
+            (
+                "test_different".to_string(),
+                failure(
+                    ASSERTION_ERROR,
+                    "values differ\n  expected: fn(x) {\nx\n}\n    actual: fn(x) {\nx\n}",
+                    9,
+                    5
+                )
+            )
+        );
+        assert_eq!(
+            results[2],
+            (
+                "test_assert".to_string(),
+                failure(
+                    ASSERTION_ERROR,
+                    "assertion failed: expected true, got fn(x) {\nx\n}",
+                    12,
+                    5
+                )
+            )
+        );
+        let Outcome::Failed(mismatch) = &results[3].1 else {
+            panic!("expected test_mismatch to fail");
+        };
+        assert_eq!(mismatch.kind, "TypeMismatch");
+        assert_eq!(mismatch.location, Some(Location::new(3, 19)));
+    }
+
+    #[test]
+    fn test_fresh_environment() {
+        let results = run(indoc! {"
+            let counter = [0];
+            let test_first = fn() { let counter = [1]; assert_eq(counter, [1]) };
+            let test_second = fn() { assert_eq(counter, [0]) };
+            let test_error = fn() { let f = fn() { 1 / 0 }; f() };
+        "});
+
+        assert_eq!(results[0].1, Outcome::Passed);
+        assert_eq!(results[1].1, Outcome::Passed);
+        assert_eq!(
+            results[2].1,
+            failure("DivisionByZero", "cannot divide by zero", 4, 40)
+        );
+    }
+}
@@ -0,0 +1,183 @@
+#![cfg(feature = "serde")]
+
+use std::{
+    env, fs,
+    io::BufReader,
+    process::{self, Child, ChildStdin, ChildStdout, Command, Stdio},
+};
+
+use indoc::indoc;
+use interpreter::rpc;
+use serde_json::{json, Value};
+
+struct Client {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    seq: i64,
+}
+
+impl Client {
+    fn start() -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_monkey-dap"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        Client {
+            stdin: child.stdin.take().unwrap(),
+            stdout: BufReader::new(child.stdout.take().unwrap()),
+            child,
+            seq: 0,
+        }
+    }
+
+    fn request(&mut self, command: &str, arguments: Value) -> Value {
+        self.seq += 1;
+        let request =
+            json!({"seq": self.seq, "type": "request", "command": command, "arguments": arguments});
+        rpc::write_message(&mut self.stdin, &request).unwrap();
+
+        let response = self.receive();
+        assert_eq!(response["type"], "response");
+        assert_eq!(response["request_seq"], self.seq);
+        response
+    }
+
+    fn body(&mut self, command: &str, arguments: Value) -> Value {
+        let response = self.request(command, arguments);
+        assert_eq!(response["success"], true, "{}", response);
+        response["body"].clone()
+    }
+
+    fn event(&mut self, event: &str) -> Value {
+        let message = self.receive();
+        assert_eq!(message["event"], event, "{}", message);
+        message["body"].clone()
+    }
+
+    fn receive(&mut self) -> Value {
+        rpc::read_message(&mut self.stdout).unwrap().unwrap()
+    }
+
+    // the names and values of the variables behind a reference
+    fn variables(&mut self, reference: &Value) -> Vec<(String, String)> {
+        self.body("variables", json!({"variablesReference": reference}))["variables"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|variable| {
+                let name = variable["name"].as_str().unwrap().to_string();
+                let value = variable["value"].as_str().unwrap().to_string();
+                (name, value)
+            })
+            .collect()
+    }
+}
+
+fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_session() {
+    let path = env::temp_dir().join(format!("monkey-dap-{}.mk", process::id()));
+    fs::write(
+        &path,
+        indoc! {r#"
+            let point = {"x": 1, "tags": [true, "a"]};
+            let scale = fn(n) {
+                let scaled = n * 10;
+                scaled
+            };
+            let total = scale(4) + scale(point["x"]);
+            total;
+        "#},
+    )
+    .unwrap();
+    let program = path.to_str().unwrap();
+
+    let mut client = Client::start();
+    let capabilities = client.body("initialize", json!({"adapterID": "monkey"}));
+    assert_eq!(capabilities["supportsConfigurationDoneRequest"], true);
+    client.event("initialized");
+
+    client.body("launch", json!({"program": program}));
+    let breakpoints = client.body(
+        "setBreakpoints",
+        json!({"source": {"path": program}, "breakpoints": [{"line": 4}]}),
+    );
+    assert_eq!(breakpoints["breakpoints"][0]["verified"], true);
+    client.body("configurationDone", json!({}));
+
+    assert_eq!(client.event("stopped")["reason"], "breakpoint");
+    let trace = client.body("stackTrace", json!({"threadId": 1}));
+    let frames = trace["stackFrames"].as_array().unwrap();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0]["name"], "scale");
+    assert_eq!(frames[0]["line"], 4);
+    assert_eq!(frames[0]["source"]["path"], program);
+    assert_eq!(frames[1]["name"], "<program>");
+    assert_eq!(frames[1]["line"], 6);
+
+    let scopes = client.body("scopes", json!({"frameId": frames[0]["id"]}))["scopes"].clone();
+    let names: Vec<&str> = scopes
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|scope| scope["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["Locals", "Closure", "Globals"]);
+    assert_eq!(
+        client.variables(&scopes[0]["variablesReference"]),
+        pairs(&[("n", "4"), ("scaled", "40")])
+    );
+    assert_eq!(
+        client.variables(&scopes[2]["variablesReference"]),
+        pairs(&[("point", "{tags: [true, a], x: 1}"), ("scale", "fn scale")])
+    );
+
+    let evaluated = client.body(
+        "evaluate",
+        json!({"expression": "point", "frameId": frames[1]["id"]}),
+    );
+    let point = client.variables(&evaluated["variablesReference"]);
+    assert_eq!(point, pairs(&[("tags", "[true, a]"), ("x", "1")]));
+    let tags = client.body(
+        "evaluate",
+        json!({"expression": "point[\"tags\"]", "frameId": frames[0]["id"]}),
+    );
+    assert_eq!(
+        client.variables(&tags["variablesReference"]),
+        pairs(&[("[0]", "true"), ("[1]", "a")])
+    );
+
+    let failed = client.request("evaluate", json!({"expression": "missing", "frameId": 1}));
+    assert_eq!(failed["success"], false);
+
+    // leaving the first call runs into the breakpoint again in the second one
+    client.body("stepOut", json!({"threadId": 1}));
+    assert_eq!(client.event("stopped")["reason"], "breakpoint");
+    let evaluated = client.body("evaluate", json!({"expression": "n", "frameId": 2}));
+    assert_eq!(evaluated["result"], "1");
+
+    client.body(
+        "setBreakpoints",
+        json!({"source": {"path": program}, "breakpoints": []}),
+    );
+    client.body("next", json!({"threadId": 1}));
+    assert_eq!(client.event("stopped")["reason"], "step");
+    let trace = client.body("stackTrace", json!({"threadId": 1}));
+    assert_eq!(trace["stackFrames"][0]["line"], 7);
+    client.body("continue", json!({"threadId": 1}));
+    assert_eq!(client.event("exited")["exitCode"], 0);
+    client.event("terminated");
+
+    client.body("disconnect", json!({}));
+    assert!(client.child.wait().unwrap().success());
+    fs::remove_file(path).unwrap();
+}
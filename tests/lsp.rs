@@ -0,0 +1,184 @@
+#![cfg(feature = "serde")]
+
+use std::{
+    io::BufReader,
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+};
+
+use indoc::indoc;
+use interpreter::rpc;
+use serde_json::{json, Value};
+
+const URI: &str = "file:///main.mk";
+
+struct Client {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    next_id: i64,
+}
+
+impl Client {
+    fn start() -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_monkey-lsp"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        Client {
+            stdin: child.stdin.take().unwrap(),
+            stdout: BufReader::new(child.stdout.take().unwrap()),
+            child,
+            next_id: 1,
+        }
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> Value {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let request = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        rpc::write_message(&mut self.stdin, &request).unwrap();
+
+        let response = self.receive();
+        assert_eq!(response["id"], id);
+        response
+    }
+
+    fn notify(&mut self, method: &str, params: Value) {
+        let notification = json!({"jsonrpc": "2.0", "method": method, "params": params});
+        rpc::write_message(&mut self.stdin, &notification).unwrap();
+    }
+
+    fn receive(&mut self) -> Value {
+        rpc::read_message(&mut self.stdout).unwrap().unwrap()
+    }
+
+    fn at(&mut self, method: &str, line: usize, character: usize) -> Value {
+        self.request(
+            method,
+            json!({
+                "textDocument": {"uri": URI},
+                "position": {"line": line, "character": character},
+                "context": {"includeDeclaration": true},
+            }),
+        )["result"]
+            .clone()
+    }
+}
+
+fn range(line: usize, character: usize, width: usize) -> Value {
+    json!({
+        "start": {"line": line, "character": character},
+        "end": {"line": line, "character": character + width},
+    })
+}
+
+#[test]
+fn test_session() {
+    let mut client = Client::start();
+
+    let initialized = client.request("initialize", json!({"capabilities": {}}));
+    assert_eq!(
+        initialized["result"]["capabilities"]["definitionProvider"],
+        true
+    );
+    client.notify("initialized", json!({}));
+
+    client.notify(
+        "textDocument/didOpen",
+        json!({"textDocument": {"uri": URI, "languageId": "monkey", "version": 1, "text": "let x = ;"}}),
+    );
+    let diagnostics = client.receive();
+    assert_eq!(diagnostics["method"], "textDocument/publishDiagnostics");
+    assert_eq!(
+        diagnostics["params"]["diagnostics"][0]["message"],
+        "expected expression, got ;"
+    );
+    assert_eq!(diagnostics["params"]["diagnostics"][0]["severity"], 1);
+
+    let text = indoc! {"
+        let add = fn(a, b) {
+          let sum = a + b;
+          sum
+        };
+        add(1, 2);
+    "};
+    client.notify(
+        "textDocument/didChange",
+        json!({"textDocument": {"uri": URI, "version": 2}, "contentChanges": [{"text": text}]}),
+    );
+    let diagnostics = client.receive();
+    assert_eq!(diagnostics["params"]["diagnostics"], json!([]));
+
+    assert_eq!(
+        client.at("textDocument/definition", 2, 2),
+        json!({"uri": URI, "range": range(1, 6, 3)})
+    );
+    assert_eq!(
+        client.at("textDocument/definition", 1, 16),
+        json!({"uri": URI, "range": range(0, 16, 1)})
+    );
+    assert_eq!(client.at("textDocument/definition", 1, 14), Value::Null);
+
+    assert_eq!(
+        client.at("textDocument/references", 4, 1),
+        json!([
+            {"uri": URI, "range": range(0, 4, 3)},
+            {"uri": URI, "range": range(4, 0, 3)},
+        ])
+    );
+
+    assert_eq!(
+        client.at("textDocument/hover", 4, 0)["contents"]["value"],
+        "```monkey\nlet add = fn(a, b)\n```"
+    );
+
+    let labels = |completion: Value| -> Vec<String> {
+        completion
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["label"].as_str().unwrap().to_string())
+            .collect()
+    };
+    assert_eq!(
+        labels(client.at("textDocument/completion", 2, 2)),
+        ["sum", "b", "a", "add"]
+    );
+    assert_eq!(labels(client.at("textDocument/completion", 4, 0)), ["add"]);
+
+    let edits = client.request(
+        "textDocument/formatting",
+        json!({"textDocument": {"uri": URI}, "options": {"tabSize": 4, "insertSpaces": true}}),
+    );
+    assert_eq!(
+        edits["result"][0]["newText"],
+        "let add = fn(a, b) {\n    let sum = a + b;\n    sum\n};\nadd(1, 2);\n"
+    );
+
+    client.notify(
+        "textDocument/didChange",
+        json!({"textDocument": {"uri": URI, "version": 3}, "contentChanges": [{"text": "if (true) { 1 }\nimportant(2);\n"}]}),
+    );
+    client.receive();
+    let edits = client.request(
+        "textDocument/formatting",
+        json!({"textDocument": {"uri": URI}, "options": {"tabSize": 4, "insertSpaces": true}}),
+    );
+    assert_eq!(
+        edits["result"][0]["newText"],
+        "if (true) {\n    1\n};\nimportant(2);\n"
+    );
+
+    let unknown = client.request("workspace/symbol", json!({}));
+    assert_eq!(unknown["error"]["code"], -32601);
+
+    assert_eq!(
+        client.request("shutdown", Value::Null)["result"],
+        Value::Null
+    );
+    client.notify("exit", Value::Null);
+    assert!(client.child.wait().unwrap().success());
+}